@@ -0,0 +1,35 @@
+use asterios::{
+    echo::EchoServer,
+    multimap::MultiMap,
+    request::{Request, RequestMethod},
+    session::Session,
+};
+use criterion::{criterion_group, criterion_main, Criterion};
+use tokio::runtime::Runtime;
+
+fn repeated_requests(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let server = runtime.block_on(async { EchoServer::start().unwrap() });
+    let request = Request::new(
+        None,
+        MultiMap::new(),
+        RequestMethod::GET,
+        server.url("/get"),
+        MultiMap::new(),
+    );
+    let session = Session::new();
+
+    let mut group = c.benchmark_group("repeated_requests");
+    group.bench_function("fresh_client", |b| {
+        b.to_async(&runtime)
+            .iter(|| async { request.send_request().await.unwrap() })
+    });
+    group.bench_function("shared_session", |b| {
+        b.to_async(&runtime)
+            .iter(|| async { session.send(&request).await.unwrap() })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, repeated_requests);
+criterion_main!(benches);
@@ -0,0 +1,507 @@
+use crate::{jsonpath::JsonPath, request::Response};
+use regex::Regex;
+use serde::{de, Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::fmt;
+
+/// A check on a response, saved alongside a request to turn it into a test,
+/// e.g. `{ status = 200 }` or `{ json_path = { path = "$.id", op = "exists" } }`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Assertion {
+    Status(StatusCheck),
+    Header(HeaderCheck),
+    JsonPath(JsonCheck),
+    /// Upper bound on the response time, in milliseconds
+    ElapsedMs(u64),
+    /// Body length in bytes, both bounds are inclusive
+    BodySize {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        min: Option<usize>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max: Option<usize>,
+    },
+}
+
+/// `200`, a class like `"2xx"`, or an inclusive `{ min, max }` range
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum StatusCheck {
+    Code(u16),
+    Class(String),
+    Range { min: u16, max: u16 },
+}
+
+/// Checks the class when loading, so that a typo like `"200"` is reported
+/// there rather than as a failing test
+impl<'de> Deserialize<'de> for StatusCheck {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<StatusCheck, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Code(u16),
+            Class(String),
+            Range { min: u16, max: u16 },
+        }
+
+        Ok(match Raw::deserialize(deserializer)? {
+            Raw::Code(code) => StatusCheck::Code(code),
+            Raw::Class(class) => match status_class(&class) {
+                Some(_) => StatusCheck::Class(class),
+                None => {
+                    return Err(de::Error::custom(format!(
+                        "invalid status class `{}`, expected one of 1xx to 5xx",
+                        class
+                    )))
+                }
+            },
+            Raw::Range { min, max } => StatusCheck::Range { min, max },
+        })
+    }
+}
+
+/// Presence of a header, and optionally its exact value or a regex over it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HeaderCheck {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub equals: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matches: Option<String>,
+}
+
+/// An operator applied to the first value matched by `path`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct JsonCheck {
+    pub path: String,
+    #[serde(flatten)]
+    pub op: Operator,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "op", content = "value", rename_all = "snake_case")]
+pub enum Operator {
+    #[serde(rename = "==")]
+    Equals(Value),
+    #[serde(rename = "!=")]
+    NotEquals(Value),
+    /// Substring of a string, element of an array or key of an object
+    Contains(Value),
+    /// Regex over a string, other values are matched as JSON text
+    Matches(String),
+    Exists,
+    #[serde(rename = "type")]
+    TypeOf(JsonType),
+    /// Number of characters, elements or keys
+    Length(usize),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum JsonType {
+    Null,
+    Boolean,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+/// Outcome of one assertion
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AssertionResult {
+    pub name: String, // What was checked, e.g. `status is 200`
+    pub passed: bool,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl Assertion {
+    pub fn check(&self, res: &Response) -> AssertionResult {
+        let (expected, actual, passed) = match self {
+            Assertion::Status(check) => {
+                let passed = match check {
+                    StatusCheck::Code(code) => res.status() == *code,
+                    StatusCheck::Class(class) => status_class(class) == Some(res.status() / 100),
+                    StatusCheck::Range { min, max } => (*min..=*max).contains(&res.status()),
+                };
+                (check.to_string(), res.status().to_string(), passed)
+            }
+            Assertion::Header(check) => check.check(res),
+            Assertion::JsonPath(check) => check.check(res),
+            Assertion::ElapsedMs(max) => {
+                let elapsed = res.elapsed().as_millis();
+                (
+                    format!("<= {}ms", max),
+                    format!("{}ms", elapsed),
+                    elapsed <= u128::from(*max),
+                )
+            }
+            Assertion::BodySize { min, max } => {
+                let len = res.body().len();
+                let passed = min.is_none_or(|min| len >= min) && max.is_none_or(|max| len <= max);
+                (size_range(*min, *max), format!("{} bytes", len), passed)
+            }
+        };
+        AssertionResult {
+            name: self.to_string(),
+            passed,
+            expected,
+            actual,
+        }
+    }
+}
+
+/// Checks every assertion, failures don't stop the following ones
+pub fn check_all(assertions: &[Assertion], res: &Response) -> Vec<AssertionResult> {
+    assertions.iter().map(|a| a.check(res)).collect()
+}
+
+impl HeaderCheck {
+    fn check(&self, res: &Response) -> (String, String, bool) {
+        let value = res.headers().get_ignore_case(&self.name);
+        let actual = value.unwrap_or("missing").to_string();
+        let Some(value) = value else {
+            return (self.expected(), actual, false);
+        };
+        if let Some(equals) = &self.equals {
+            if value != equals {
+                return (self.expected(), actual, false);
+            }
+        }
+        if let Some(pattern) = &self.matches {
+            match Regex::new(pattern) {
+                Ok(regex) if !regex.is_match(value) => return (self.expected(), actual, false),
+                Ok(_) => {}
+                Err(e) => return (self.expected(), format!("invalid regex: {}", e), false),
+            }
+        }
+        (self.expected(), actual, true)
+    }
+
+    fn expected(&self) -> String {
+        match (&self.equals, &self.matches) {
+            (Some(equals), _) => equals.clone(),
+            (None, Some(pattern)) => format!("/{}/", pattern),
+            (None, None) => "present".to_string(),
+        }
+    }
+}
+
+impl JsonCheck {
+    fn check(&self, res: &Response) -> (String, String, bool) {
+        let expected = self.op.expected();
+        let path = match JsonPath::parse(&self.path) {
+            Ok(path) => path,
+            Err(e) => return (expected, e.to_string(), false),
+        };
+        let Some(json) = res.body().json() else {
+            return (expected, "body is not JSON".to_string(), false);
+        };
+        let value = path.first(json);
+        let actual = value.map_or("nothing".to_string(), |v| match &self.op {
+            Operator::TypeOf(_) => JsonType::of(v).to_string(),
+            Operator::Length(_) => length(v).map_or("no length".to_string(), |l| l.to_string()),
+            _ => v.to_string(),
+        });
+        let passed = match (&self.op, value) {
+            (Operator::Exists, value) => value.is_some(),
+            (_, None) => false,
+            (Operator::Equals(expected), Some(v)) => json_eq(v, expected),
+            (Operator::NotEquals(expected), Some(v)) => !json_eq(v, expected),
+            (Operator::Contains(needle), Some(v)) => contains(v, needle),
+            (Operator::Matches(pattern), Some(v)) => match Regex::new(pattern) {
+                Ok(regex) => regex.is_match(&text(v)),
+                Err(e) => return (expected, format!("invalid regex: {}", e), false),
+            },
+            (Operator::TypeOf(kind), Some(v)) => JsonType::of(v) == *kind,
+            (Operator::Length(len), Some(v)) => length(v) == Some(*len),
+        };
+        (expected, actual, passed)
+    }
+}
+
+impl Operator {
+    fn expected(&self) -> String {
+        match self {
+            Operator::Equals(v) | Operator::Contains(v) => v.to_string(),
+            Operator::NotEquals(v) => format!("not {}", v),
+            Operator::Matches(pattern) => format!("/{}/", pattern),
+            Operator::Exists => "a value".to_string(),
+            Operator::TypeOf(kind) => kind.to_string(),
+            Operator::Length(len) => len.to_string(),
+        }
+    }
+}
+
+impl JsonType {
+    fn of(value: &Value) -> JsonType {
+        match value {
+            Value::Null => JsonType::Null,
+            Value::Bool(_) => JsonType::Boolean,
+            Value::Number(_) => JsonType::Number,
+            Value::String(_) => JsonType::String,
+            Value::Array(_) => JsonType::Array,
+            Value::Object(_) => JsonType::Object,
+        }
+    }
+}
+
+/// `"2xx"` to 2
+fn status_class(class: &str) -> Option<u16> {
+    let digit = class
+        .strip_suffix("xx")
+        .or_else(|| class.strip_suffix("XX"))?;
+    digit.parse().ok().filter(|d| (1..=5).contains(d))
+}
+
+/// Numbers compare by value, so `1` equals `1.0`. Integers compare exactly,
+/// as f64 cannot tell apart IDs above 2^53.
+fn json_eq(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) if a.is_f64() || b.is_f64() => {
+            a.as_f64() == b.as_f64()
+        }
+        (Value::Number(a), Value::Number(b)) => {
+            a.as_i64() == b.as_i64() && a.as_u64() == b.as_u64()
+        }
+        _ => a == b,
+    }
+}
+
+fn contains(haystack: &Value, needle: &Value) -> bool {
+    match (haystack, needle) {
+        (Value::String(s), Value::String(sub)) => s.contains(sub.as_str()),
+        (Value::Array(items), needle) => items.iter().any(|item| json_eq(item, needle)),
+        (Value::Object(map), Value::String(key)) => map.contains_key(key),
+        _ => false,
+    }
+}
+
+fn length(value: &Value) -> Option<usize> {
+    match value {
+        Value::String(s) => Some(s.chars().count()),
+        Value::Array(items) => Some(items.len()),
+        Value::Object(map) => Some(map.len()),
+        _ => None,
+    }
+}
+
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn size_range(min: Option<usize>, max: Option<usize>) -> String {
+    match (min, max) {
+        (Some(min), Some(max)) => format!("{}..={} bytes", min, max),
+        (Some(min), None) => format!(">= {} bytes", min),
+        (None, Some(max)) => format!("<= {} bytes", max),
+        (None, None) => "any size".to_string(),
+    }
+}
+
+impl fmt::Display for Assertion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Assertion::Status(check) => write!(f, "status is {}", check),
+            Assertion::Header(check) => match (&check.equals, &check.matches) {
+                (Some(equals), _) => write!(f, "header {} is `{}`", check.name, equals),
+                (None, Some(pattern)) => write!(f, "header {} matches /{}/", check.name, pattern),
+                (None, None) => write!(f, "header {} is present", check.name),
+            },
+            Assertion::JsonPath(check) => match &check.op {
+                Operator::Equals(v) => write!(f, "{} == {}", check.path, v),
+                Operator::NotEquals(v) => write!(f, "{} != {}", check.path, v),
+                Operator::Contains(v) => write!(f, "{} contains {}", check.path, v),
+                Operator::Matches(pattern) => write!(f, "{} matches /{}/", check.path, pattern),
+                Operator::Exists => write!(f, "{} exists", check.path),
+                Operator::TypeOf(kind) => write!(f, "{} is of type {}", check.path, kind),
+                Operator::Length(len) => write!(f, "{} has length {}", check.path, len),
+            },
+            Assertion::ElapsedMs(max) => write!(f, "response time within {}ms", max),
+            Assertion::BodySize { min, max } => write!(f, "body size {}", size_range(*min, *max)),
+        }
+    }
+}
+
+impl fmt::Display for StatusCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatusCheck::Code(code) => write!(f, "{}", code),
+            StatusCheck::Class(class) => write!(f, "{}", class),
+            StatusCheck::Range { min, max } => write!(f, "{}..={}", min, max),
+        }
+    }
+}
+
+impl fmt::Display for JsonType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            JsonType::Null => "null",
+            JsonType::Boolean => "boolean",
+            JsonType::Number => "number",
+            JsonType::String => "string",
+            JsonType::Array => "array",
+            JsonType::Object => "object",
+        };
+        f.write_str(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_all, Assertion, HeaderCheck, JsonCheck, JsonType, Operator, StatusCheck};
+    use crate::{body::ResponseBody, multimap::MultiMap, request::Response};
+    use serde_json::json;
+    use std::time::Duration;
+
+    fn response() -> Response {
+        let body = json!({
+            "id": 42,
+            "name": "Widget",
+            "tags": ["new", "sale"],
+            "owner": {"email": "jo@example.com"},
+            "price": 9.5,
+            "big": 9007199254740992u64
+        });
+        Response::new(
+            201,
+            MultiMap::from([
+                ("content-type", "application/json"),
+                ("x-request-id", "abc-123"),
+            ]),
+            ResponseBody::new(body.to_string().into_bytes(), Some("application/json")),
+            Duration::from_millis(120),
+        )
+    }
+
+    fn json_path(path: &str, op: Operator) -> Assertion {
+        Assertion::JsonPath(JsonCheck {
+            path: path.to_string(),
+            op,
+        })
+    }
+
+    fn header(name: &str, equals: Option<&str>, matches: Option<&str>) -> Assertion {
+        Assertion::Header(HeaderCheck {
+            name: name.to_string(),
+            equals: equals.map(str::to_string),
+            matches: matches.map(str::to_string),
+        })
+    }
+
+    #[test]
+    fn passing_assertions() {
+        let assertions = [
+            Assertion::Status(StatusCheck::Code(201)),
+            Assertion::Status(StatusCheck::Class("2xx".to_string())),
+            Assertion::Status(StatusCheck::Range { min: 200, max: 299 }),
+            header("Content-Type", None, Some("json$")),
+            header("x-request-id", Some("abc-123"), None),
+            json_path("$.id", Operator::Equals(json!(42.0))),
+            json_path("$.name", Operator::NotEquals(json!("Gadget"))),
+            json_path("$.tags", Operator::Contains(json!("sale"))),
+            json_path("$.owner", Operator::Contains(json!("email"))),
+            json_path(
+                "$.owner.email",
+                Operator::Matches("@example\\.com$".to_string()),
+            ),
+            json_path("$.price", Operator::Exists),
+            json_path("$.tags", Operator::TypeOf(JsonType::Array)),
+            json_path("$.name", Operator::Length(6)),
+            Assertion::ElapsedMs(500),
+            Assertion::BodySize {
+                min: Some(10),
+                max: Some(1024),
+            },
+        ];
+        for result in check_all(&assertions, &response()) {
+            assert!(result.passed, "{:?}", result);
+        }
+    }
+
+    #[test]
+    fn failures_report_expected_and_actual() {
+        let results = check_all(
+            &[
+                Assertion::Status(StatusCheck::Class("4xx".to_string())),
+                header("etag", None, None),
+                json_path("$.id", Operator::Equals(json!(41))),
+                json_path("$.big", Operator::Equals(json!(9007199254740993u64))),
+                json_path("$.missing", Operator::Exists),
+                json_path("$.tags", Operator::Length(3)),
+                Assertion::ElapsedMs(100),
+            ],
+            &response(),
+        );
+        assert!(results.iter().all(|r| !r.passed));
+
+        let summary: Vec<_> = results
+            .iter()
+            .map(|r| (r.name.as_str(), r.expected.as_str(), r.actual.as_str()))
+            .collect();
+        assert_eq!(
+            vec![
+                ("status is 4xx", "4xx", "201"),
+                ("header etag is present", "present", "missing"),
+                ("$.id == 41", "41", "42"),
+                (
+                    "$.big == 9007199254740993",
+                    "9007199254740993",
+                    "9007199254740992"
+                ),
+                ("$.missing exists", "a value", "nothing"),
+                ("$.tags has length 3", "3", "2"),
+                ("response time within 100ms", "<= 100ms", "120ms"),
+            ],
+            summary
+        );
+    }
+
+    #[test]
+    fn parse_from_toml() {
+        #[derive(serde::Deserialize)]
+        struct Doc {
+            assert: Vec<Assertion>,
+        }
+        let doc: Doc = toml::from_str(
+            r#"
+            assert = [
+                { status = 200 },
+                { status = "2xx" },
+                { status = { min = 200, max = 204 } },
+                { header = { name = "content-type", matches = "json" } },
+                { json_path = { path = "$.id", op = "==", value = 42 } },
+                { json_path = { path = "$.tags", op = "type", value = "array" } },
+                { json_path = { path = "$.id", op = "exists" } },
+                { elapsed_ms = 500 },
+                { body_size = { max = 1024 } },
+            ]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(9, doc.assert.len());
+        assert_eq!(
+            json_path("$.id", Operator::Equals(json!(42))),
+            doc.assert[4]
+        );
+        assert_eq!(
+            Assertion::Status(StatusCheck::Range { min: 200, max: 204 }),
+            doc.assert[2]
+        );
+
+        for typo in ["\"200\"", "\"2XX \"", "\"6xx\""] {
+            let error = toml::from_str::<Doc>(&format!("assert = [{{ status = {} }}]", typo))
+                .err()
+                .unwrap();
+            assert!(
+                error.to_string().contains("invalid status class"),
+                "{}",
+                error
+            );
+        }
+    }
+}
@@ -0,0 +1,446 @@
+use crate::multimap::MultiMap;
+use encoding_rs::{Encoding, UTF_8};
+use reqwest::{
+    header::CONTENT_TYPE,
+    multipart::{Form, Part},
+    RequestBuilder,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{borrow::Cow, io, ops::Index, path::PathBuf};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum RequestBody {
+    Json(Value),
+    Form(MultiMap), // application/x-www-form-urlencoded
+    Multipart(Vec<MultipartPart>),
+    Binary(BinaryBody),
+    Raw {
+        content: String,
+        content_type: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum MultipartPart {
+    Text {
+        name: String,
+        value: String,
+    },
+    File {
+        name: String,
+        path: PathBuf,
+        content_type: Option<String>, // Guessed by the server when missing
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum BinaryBody {
+    Bytes(Vec<u8>),
+    File(PathBuf), // Read when the request is sent
+}
+
+/// A response payload as received, whatever its format
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ResponseBody {
+    bytes: Vec<u8>,
+    content_type: Option<String>, // Mime type without parameters, lowercased
+    charset: Option<String>,
+    json: Option<Value>, // Only set when the payload parsed as JSON
+}
+
+static NULL: Value = Value::Null;
+
+impl RequestBody {
+    /// Copy of the body with `f` applied to every string it holds, file paths included
+    pub fn map_strings(&self, f: &mut impl FnMut(&str) -> String) -> RequestBody {
+        match self {
+            RequestBody::Json(value) => RequestBody::Json(map_json_strings(value, f)),
+            RequestBody::Form(pairs) => {
+                RequestBody::Form(pairs.iter().map(|(k, v)| (f(k), f(v))).collect())
+            }
+            RequestBody::Multipart(parts) => RequestBody::Multipart(
+                parts
+                    .iter()
+                    .map(|part| match part {
+                        MultipartPart::Text { name, value } => MultipartPart::Text {
+                            name: f(name),
+                            value: f(value),
+                        },
+                        MultipartPart::File {
+                            name,
+                            path,
+                            content_type,
+                        } => MultipartPart::File {
+                            name: f(name),
+                            path: PathBuf::from(f(&path.to_string_lossy())),
+                            content_type: content_type.as_deref().map(&mut *f),
+                        },
+                    })
+                    .collect(),
+            ),
+            RequestBody::Binary(BinaryBody::Bytes(bytes)) => {
+                RequestBody::Binary(BinaryBody::Bytes(bytes.clone()))
+            }
+            RequestBody::Binary(BinaryBody::File(path)) => {
+                RequestBody::Binary(BinaryBody::File(PathBuf::from(f(&path.to_string_lossy()))))
+            }
+            RequestBody::Raw {
+                content,
+                content_type,
+            } => RequestBody::Raw {
+                content: f(content),
+                content_type: f(content_type),
+            },
+        }
+    }
+
+    /// Content-Type sent unless the request headers override it.
+    /// Multipart returns `None` because reqwest generates the boundary itself.
+    pub fn content_type(&self) -> Option<&str> {
+        match self {
+            RequestBody::Json(_) => Some("application/json"),
+            RequestBody::Form(_) => Some("application/x-www-form-urlencoded"),
+            RequestBody::Multipart(_) => None,
+            RequestBody::Binary(_) => Some("application/octet-stream"),
+            RequestBody::Raw { content_type, .. } => Some(content_type),
+        }
+    }
+
+    /// Readable form for reports, files are named rather than read
+    pub fn preview(&self) -> String {
+        match self {
+            RequestBody::Json(value) => serde_json::to_string_pretty(value).unwrap_or_default(),
+            RequestBody::Form(pairs) => {
+                serde_urlencoded::to_string(pairs.as_slice()).unwrap_or_default()
+            }
+            RequestBody::Multipart(parts) => parts
+                .iter()
+                .map(|part| match part {
+                    MultipartPart::Text { name, value } => format!("{}: {}", name, value),
+                    MultipartPart::File { name, path, .. } => {
+                        format!("{}: @{}", name, path.display())
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            RequestBody::Binary(BinaryBody::Bytes(bytes)) => {
+                format!("({} bytes of binary data)", bytes.len())
+            }
+            RequestBody::Binary(BinaryBody::File(path)) => {
+                format!("(contents of {})", path.display())
+            }
+            RequestBody::Raw { content, .. } => content.clone(),
+        }
+    }
+
+    /// Encodes the body onto `builder`, reading any referenced files from disk
+    pub(crate) async fn apply(&self, builder: RequestBuilder) -> io::Result<RequestBuilder> {
+        let builder = match self {
+            RequestBody::Json(value) => builder.body(serde_json::to_vec(value)?),
+            RequestBody::Form(pairs) => builder.body(
+                serde_urlencoded::to_string(pairs.as_slice())
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+            ),
+            RequestBody::Multipart(parts) => {
+                let mut form = Form::new();
+                for part in parts {
+                    form = match part {
+                        MultipartPart::Text { name, value } => {
+                            form.text(name.clone(), value.clone())
+                        }
+                        MultipartPart::File {
+                            name,
+                            path,
+                            content_type,
+                        } => {
+                            let mut file = Part::bytes(tokio::fs::read(path).await?);
+                            if let Some(file_name) = path.file_name() {
+                                file = file.file_name(file_name.to_string_lossy().into_owned());
+                            }
+                            if let Some(content_type) = content_type {
+                                file = file
+                                    .mime_str(content_type)
+                                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+                            }
+                            form.part(name.clone(), file)
+                        }
+                    };
+                }
+                builder.multipart(form)
+            }
+            RequestBody::Binary(BinaryBody::Bytes(bytes)) => builder.body(bytes.clone()),
+            RequestBody::Binary(BinaryBody::File(path)) => {
+                builder.body(tokio::fs::read(path).await?)
+            }
+            RequestBody::Raw { content, .. } => builder.body(content.clone()),
+        };
+
+        Ok(match self.content_type() {
+            Some(content_type) => builder.header(CONTENT_TYPE, content_type),
+            None => builder,
+        })
+    }
+}
+
+impl ResponseBody {
+    /// `content_type` is the raw Content-Type header value, if any
+    pub fn new(bytes: Vec<u8>, content_type: Option<&str>) -> ResponseBody {
+        let mut params = content_type.unwrap_or_default().split(';');
+        let mime = params
+            .next()
+            .map(|m| m.trim().to_ascii_lowercase())
+            .filter(|m| !m.is_empty());
+        let charset = params.find_map(|param| {
+            let (key, value) = param.split_once('=')?;
+            key.trim()
+                .eq_ignore_ascii_case("charset")
+                .then(|| value.trim().trim_matches('"').to_ascii_lowercase())
+        });
+        // Without a Content-Type, sniff for JSON anyway
+        let json = match &mime {
+            _ if bytes.is_empty() => None,
+            Some(mime) if !is_json_mime(mime) => None,
+            _ => serde_json::from_slice(&bytes).ok(),
+        };
+
+        ResponseBody {
+            bytes,
+            content_type: mime,
+            charset,
+            json,
+        }
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn content_type(&self) -> Option<&str> {
+        self.content_type.as_deref()
+    }
+
+    pub fn charset(&self) -> Option<&str> {
+        self.charset.as_deref()
+    }
+
+    pub fn json(&self) -> Option<&Value> {
+        self.json.as_ref()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Whether the payload is meant to be read as text rather than raw bytes
+    pub fn is_text(&self) -> bool {
+        match &self.content_type {
+            Some(mime) => {
+                mime.starts_with("text/")
+                    || is_json_mime(mime)
+                    || mime.ends_with("+xml")
+                    || matches!(
+                        mime.as_str(),
+                        "application/xml"
+                            | "application/javascript"
+                            | "application/x-www-form-urlencoded"
+                    )
+                    || self.charset.is_some()
+            }
+            None => std::str::from_utf8(&self.bytes).is_ok(),
+        }
+    }
+
+    /// Decodes the payload with its declared charset, UTF-8 by default.
+    /// Invalid sequences are replaced rather than rejected.
+    pub fn text(&self) -> Cow<'_, str> {
+        let encoding = self
+            .charset
+            .as_deref()
+            .and_then(|charset| Encoding::for_label(charset.as_bytes()))
+            .unwrap_or(UTF_8);
+        encoding.decode(&self.bytes).0
+    }
+
+    /// Readable form for reports: JSON is pretty-printed, binary data only described
+    pub fn preview(&self) -> String {
+        if self.bytes.is_empty() {
+            return "(empty body)".to_string();
+        }
+        match &self.json {
+            Some(json) => serde_json::to_string_pretty(json).unwrap_or_default(),
+            None if self.is_text() => self.text().into_owned(),
+            None => format!("({} bytes of binary data)", self.bytes.len()),
+        }
+    }
+}
+
+/// Indexes into the JSON view, yielding `null` for non-JSON bodies
+impl Index<&str> for ResponseBody {
+    type Output = Value;
+
+    fn index(&self, key: &str) -> &Value {
+        self.json.as_ref().map_or(&NULL, |json| &json[key])
+    }
+}
+
+fn map_json_strings(value: &Value, f: &mut impl FnMut(&str) -> String) -> Value {
+    match value {
+        Value::String(s) => Value::String(f(s)),
+        Value::Array(values) => Value::Array(
+            values
+                .iter()
+                .map(|value| map_json_strings(value, f))
+                .collect(),
+        ),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| (f(k), map_json_strings(v, f)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+fn is_json_mime(mime: &str) -> bool {
+    mime == "application/json" || mime.ends_with("+json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BinaryBody, MultipartPart, RequestBody, ResponseBody};
+    use crate::multimap::MultiMap;
+    use reqwest::Client;
+    use serde_json::json;
+    use std::path::PathBuf;
+
+    async fn encode(body: RequestBody) -> reqwest::Request {
+        let builder = Client::new().post("http://localhost/");
+        body.apply(builder).await.unwrap().build().unwrap()
+    }
+
+    fn bytes(req: &reqwest::Request) -> &[u8] {
+        req.body().unwrap().as_bytes().unwrap()
+    }
+
+    #[tokio::test]
+    async fn encode_json_body() {
+        let req = encode(RequestBody::Json(json!({"name": "john"}))).await;
+        assert_eq!("application/json", req.headers()["content-type"]);
+        assert_eq!(br#"{"name":"john"}"#, bytes(&req));
+    }
+
+    #[tokio::test]
+    async fn encode_form_body() {
+        let req = encode(RequestBody::Form(MultiMap::from([
+            ("tag", "a b"),
+            ("tag", "c&d"),
+        ])))
+        .await;
+        assert_eq!(
+            "application/x-www-form-urlencoded",
+            req.headers()["content-type"]
+        );
+        assert_eq!(b"tag=a+b&tag=c%26d", bytes(&req));
+    }
+
+    #[tokio::test]
+    async fn encode_multipart_body() {
+        let req = encode(RequestBody::Multipart(vec![
+            MultipartPart::Text {
+                name: "name".to_string(),
+                value: "john".to_string(),
+            },
+            MultipartPart::File {
+                name: "manifest".to_string(),
+                path: PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml"),
+                content_type: Some("application/toml".to_string()),
+            },
+        ]))
+        .await;
+        assert!(req.headers()["content-type"]
+            .to_str()
+            .unwrap()
+            .starts_with("multipart/form-data; boundary="));
+    }
+
+    #[tokio::test]
+    async fn encode_binary_and_raw_bodies() {
+        let req = encode(RequestBody::Binary(BinaryBody::Bytes(vec![
+            0, 159, 146, 150,
+        ])))
+        .await;
+        assert_eq!("application/octet-stream", req.headers()["content-type"]);
+        assert_eq!(&[0, 159, 146, 150], bytes(&req));
+
+        let req = encode(RequestBody::Raw {
+            content: "<a/>".to_string(),
+            content_type: "application/xml".to_string(),
+        })
+        .await;
+        assert_eq!("application/xml", req.headers()["content-type"]);
+        assert_eq!(b"<a/>", bytes(&req));
+    }
+
+    #[tokio::test]
+    async fn missing_binary_file_is_an_error() {
+        let body = RequestBody::Binary(BinaryBody::File(PathBuf::from("/does/not/exist")));
+        let builder = Client::new().post("http://localhost/");
+        assert!(body.apply(builder).await.is_err());
+    }
+
+    #[test]
+    fn decode_empty_response() {
+        let body = ResponseBody::new(Vec::new(), None);
+        assert!(body.is_empty());
+        assert!(body.json().is_none());
+        assert_eq!("", body.text());
+    }
+
+    #[test]
+    fn decode_html_response() {
+        let body = ResponseBody::new(
+            b"<h1>caf\xe9</h1>".to_vec(),
+            Some("text/html; charset=ISO-8859-1"),
+        );
+        assert_eq!(Some("text/html"), body.content_type());
+        assert_eq!(Some("iso-8859-1"), body.charset());
+        assert!(body.is_text());
+        assert!(body.json().is_none());
+        assert_eq!("<h1>café</h1>", body.text());
+    }
+
+    #[test]
+    fn decode_png_response() {
+        let png = vec![0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+        let body = ResponseBody::new(png.clone(), Some("image/png"));
+        assert_eq!(Some("image/png"), body.content_type());
+        assert!(!body.is_text());
+        assert!(body.json().is_none());
+        assert_eq!(png, body.bytes());
+    }
+
+    #[test]
+    fn decode_json_response() {
+        let body = ResponseBody::new(
+            br#"{"args": {"name": "john"}}"#.to_vec(),
+            Some("application/json; charset=utf-8"),
+        );
+        assert_eq!("john", body["args"]["name"]);
+
+        let body = ResponseBody::new(br#"{"name": "john"}"#.to_vec(), None);
+        assert_eq!("john", body["name"]);
+    }
+
+    #[test]
+    fn decode_malformed_json_response() {
+        let body = ResponseBody::new(br#"{"name": "jo"#.to_vec(), Some("application/json"));
+        assert!(body.json().is_none());
+        assert!(body["name"].is_null());
+        assert_eq!(r#"{"name": "jo"#, body.text());
+    }
+}
@@ -0,0 +1,898 @@
+use crate::{
+    body::{BinaryBody, MultipartPart, RequestBody},
+    multimap::MultiMap,
+    request::{Request, RequestMethod},
+};
+use serde_json::Value;
+use std::{fmt, path::Path, str::FromStr};
+
+/// Target of `render`, each one a snippet that sends the request and prints the response
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    Curl,
+    Httpie,
+    Python,     // requests
+    JavaScript, // fetch, run as an ES module
+    Go,         // net/http
+    Rust,       // reqwest with tokio
+}
+
+impl Language {
+    /// Renders `request` as given, resolve its variables first.
+    /// Files referenced by the body are read by the snippet, not inlined.
+    pub fn render(&self, request: &Request) -> String {
+        match self {
+            Language::Curl => curl(request),
+            Language::Httpie => httpie(request),
+            Language::Python => python(request),
+            Language::JavaScript => javascript(request),
+            Language::Go => go(request),
+            Language::Rust => rust(request),
+        }
+    }
+}
+
+impl FromStr for Language {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "curl" => Ok(Language::Curl),
+            "httpie" => Ok(Language::Httpie),
+            "python" => Ok(Language::Python),
+            "javascript" | "js" => Ok(Language::JavaScript),
+            "go" => Ok(Language::Go),
+            "rust" => Ok(Language::Rust),
+            _ => Err(format!(
+                "unknown language `{}`, expected curl, httpie, python, javascript, go or rust",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Language::Curl => "curl",
+            Language::Httpie => "httpie",
+            Language::Python => "python",
+            Language::JavaScript => "javascript",
+            Language::Go => "go",
+            Language::Rust => "rust",
+        })
+    }
+}
+
+fn curl(request: &Request) -> String {
+    let mut args = Vec::new();
+    match request.method() {
+        // curl would switch to POST on seeing the body
+        RequestMethod::GET if request.body().is_none() => {}
+        RequestMethod::HEAD => args.push("--head".to_string()),
+        method => args.push(format!("-X {}", shell(method.as_str()))),
+    }
+    args.push(shell(&request.full_url()));
+    for (name, value) in request.headers_as_sent().iter() {
+        args.push(format!("-H {}", shell(&format!("{}: {}", name, value))));
+    }
+    let mut stdin = None;
+    match request.body() {
+        None => {}
+        Some(RequestBody::Json(value)) => {
+            args.push(format!("--data-raw {}", shell(&value.to_string())))
+        }
+        Some(RequestBody::Form(pairs)) => args.push(format!("--data-raw {}", shell(&form(pairs)))),
+        Some(RequestBody::Multipart(parts)) => {
+            for part in parts {
+                args.push(match part {
+                    // `--form-string` keeps a leading `@` or `<` literal
+                    MultipartPart::Text { name, value } => {
+                        format!("--form-string {}", shell(&format!("{}={}", name, value)))
+                    }
+                    MultipartPart::File {
+                        name,
+                        path,
+                        content_type,
+                    } => {
+                        let mut field = format!("{}=@{}", name, path.display());
+                        if let Some(content_type) = content_type {
+                            field.push_str(&format!(";type={}", content_type));
+                        }
+                        format!("-F {}", shell(&field))
+                    }
+                });
+            }
+        }
+        Some(RequestBody::Binary(BinaryBody::File(path))) => args.push(format!(
+            "--data-binary {}",
+            shell(&format!("@{}", path.display()))
+        )),
+        Some(RequestBody::Binary(BinaryBody::Bytes(bytes))) => {
+            stdin = Some(printf(bytes));
+            args.push("--data-binary @-".to_string());
+        }
+        Some(RequestBody::Raw { content, .. }) => {
+            args.push(format!("--data-raw {}", shell(content)))
+        }
+    }
+    command(stdin, "curl", args)
+}
+
+fn httpie(request: &Request) -> String {
+    let mut options = Vec::new();
+    let mut items = Vec::new();
+    let mut stdin = None;
+    for (name, value) in request.headers_as_sent().iter() {
+        items.push(shell(&match value {
+            "" => format!("{};", item_name(name)),
+            _ => format!("{}:{}", item_name(name), item_value(value)),
+        }));
+    }
+    match request.body() {
+        None => {}
+        Some(RequestBody::Json(value)) => {
+            options.push(format!("--raw {}", shell(&value.to_string())))
+        }
+        Some(RequestBody::Form(pairs)) => options.push(format!("--raw {}", shell(&form(pairs)))),
+        Some(RequestBody::Multipart(parts)) => {
+            options.push("--multipart".to_string());
+            for part in parts {
+                items.push(shell(&match part {
+                    MultipartPart::Text { name, value } => {
+                        format!("{}={}", item_name(name), item_value(value))
+                    }
+                    MultipartPart::File {
+                        name,
+                        path,
+                        content_type,
+                    } => match content_type {
+                        Some(content_type) => {
+                            format!(
+                                "{}@{};type={}",
+                                item_name(name),
+                                path.display(),
+                                content_type
+                            )
+                        }
+                        None => format!("{}@{}", item_name(name), path.display()),
+                    },
+                }));
+            }
+        }
+        Some(RequestBody::Binary(BinaryBody::File(path))) => {
+            items.push(format!("< {}", shell(&path.display().to_string())))
+        }
+        Some(RequestBody::Binary(BinaryBody::Bytes(bytes))) => stdin = Some(printf(bytes)),
+        Some(RequestBody::Raw { content, .. }) => options.push(format!("--raw {}", shell(content))),
+    }
+    options.push(format!(
+        "{} {}",
+        shell(request.method().as_str()),
+        shell(&request.full_url())
+    ));
+    options.extend(items);
+    command(stdin, "http", options)
+}
+
+fn python(request: &Request) -> String {
+    let mut out = String::from("import requests\n\n");
+    out.push_str(&format!("url = {}\n", string(&request.full_url())));
+    let headers = merged(&request.headers_as_sent());
+    let mut arguments = vec![string(request.method().as_str()), "url".to_string()];
+    if !headers.is_empty() {
+        out.push_str("headers = {\n");
+        for (name, value) in &headers {
+            out.push_str(&format!("    {}: {},\n", string(name), string(value)));
+        }
+        out.push_str("}\n");
+        arguments.push("headers=headers".to_string());
+    }
+    match request.body() {
+        None => {}
+        Some(RequestBody::Json(value)) => {
+            out.push_str(&format!("payload = {}\n", python_value(value, 0)));
+            arguments.push("json=payload".to_string());
+        }
+        Some(RequestBody::Form(pairs)) => {
+            out.push_str("payload = [\n");
+            for (name, value) in pairs.iter() {
+                out.push_str(&format!("    ({}, {}),\n", string(name), string(value)));
+            }
+            out.push_str("]\n");
+            arguments.push("data=payload".to_string());
+        }
+        Some(RequestBody::Multipart(parts)) => {
+            out.push_str("files = [\n");
+            for part in parts {
+                out.push_str(&match part {
+                    MultipartPart::Text { name, value } => {
+                        format!("    ({}, (None, {})),\n", string(name), string(value))
+                    }
+                    MultipartPart::File {
+                        name,
+                        path,
+                        content_type,
+                    } => format!(
+                        "    ({}, ({}, open({}, \"rb\"){})),\n",
+                        string(name),
+                        string(&file_name(path)),
+                        string(&path.display().to_string()),
+                        content_type
+                            .as_deref()
+                            .map(|content_type| format!(", {}", string(content_type)))
+                            .unwrap_or_default()
+                    ),
+                });
+            }
+            out.push_str("]\n");
+            arguments.push("files=files".to_string());
+        }
+        Some(RequestBody::Binary(BinaryBody::File(path))) => {
+            out.push_str(&format!(
+                "payload = open({}, \"rb\")\n",
+                string(&path.display().to_string())
+            ));
+            arguments.push("data=payload".to_string());
+        }
+        Some(RequestBody::Binary(BinaryBody::Bytes(bytes))) => {
+            out.push_str(&format!("payload = b\"{}\"\n", byte_escape(bytes)));
+            arguments.push("data=payload".to_string());
+        }
+        Some(RequestBody::Raw { content, .. }) => {
+            out.push_str(&format!("payload = {}\n", string(content)));
+            arguments.push("data=payload".to_string());
+        }
+    }
+    out.push_str(&format!(
+        "\nresponse = requests.request({})\n\nprint(response.status_code)\nprint(response.text)\n",
+        arguments.join(", ")
+    ));
+    out
+}
+
+fn javascript(request: &Request) -> String {
+    let mut prelude = String::new();
+    let mut options = vec![format!("  method: {},", string(request.method().as_str()))];
+    let headers = merged(&request.headers_as_sent());
+    if !headers.is_empty() {
+        options.push("  headers: {".to_string());
+        for (name, value) in &headers {
+            options.push(format!("    {}: {},", string(name), string(value)));
+        }
+        options.push("  },".to_string());
+    }
+    let body = match request.body() {
+        None => None,
+        Some(RequestBody::Json(value)) => Some(format!(
+            "JSON.stringify({})",
+            indent(
+                &serde_json::to_string_pretty(value).unwrap_or_default(),
+                "  "
+            )
+        )),
+        Some(RequestBody::Form(pairs)) => Some(format!(
+            "new URLSearchParams([{}])",
+            pairs
+                .iter()
+                .map(|(name, value)| format!("[{}, {}]", string(name), string(value)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+        Some(RequestBody::Multipart(parts)) => {
+            let files = parts
+                .iter()
+                .any(|part| matches!(part, MultipartPart::File { .. }));
+            if files {
+                prelude.push_str("import { openAsBlob } from \"node:fs\";\n\n");
+            }
+            prelude.push_str("const form = new FormData();\n");
+            for part in parts {
+                prelude.push_str(&match part {
+                    MultipartPart::Text { name, value } => {
+                        format!("form.append({}, {});\n", string(name), string(value))
+                    }
+                    MultipartPart::File {
+                        name,
+                        path,
+                        content_type,
+                    } => format!(
+                        "form.append({}, await openAsBlob({}{}), {});\n",
+                        string(name),
+                        string(&path.display().to_string()),
+                        content_type
+                            .as_deref()
+                            .map(|content_type| format!(", {{ type: {} }}", string(content_type)))
+                            .unwrap_or_default(),
+                        string(&file_name(path))
+                    ),
+                });
+            }
+            prelude.push('\n');
+            Some("form".to_string())
+        }
+        Some(RequestBody::Binary(BinaryBody::File(path))) => {
+            prelude.push_str("import { openAsBlob } from \"node:fs\";\n\n");
+            Some(format!(
+                "await openAsBlob({})",
+                string(&path.display().to_string())
+            ))
+        }
+        Some(RequestBody::Binary(BinaryBody::Bytes(bytes))) => Some(format!(
+            "new Uint8Array([{}])",
+            bytes
+                .iter()
+                .map(u8::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+        Some(RequestBody::Raw { content, .. }) => Some(string(content)),
+    };
+    if let Some(body) = body {
+        options.push(format!("  body: {},", body));
+    }
+    format!(
+        "{}const response = await fetch({}, {{\n{}\n}});\n\n\
+         console.log(response.status);\nconsole.log(await response.text());\n",
+        prelude,
+        string(&request.full_url()),
+        options.join("\n")
+    )
+}
+
+fn go(request: &Request) -> String {
+    let mut imports = vec!["fmt", "io", "net/http"];
+    let mut setup = String::new();
+    let mut after = String::new();
+    let body = match request.body() {
+        None => "nil".to_string(),
+        Some(RequestBody::Json(value)) => {
+            imports.push("strings");
+            let json = serde_json::to_string_pretty(value).unwrap_or_default();
+            format!("strings.NewReader({})", go_string(&json))
+        }
+        Some(RequestBody::Form(pairs)) => {
+            imports.push("strings");
+            format!("strings.NewReader({})", go_string(&form(pairs)))
+        }
+        Some(RequestBody::Raw { content, .. }) => {
+            imports.push("strings");
+            format!("strings.NewReader({})", go_string(content))
+        }
+        Some(RequestBody::Binary(BinaryBody::Bytes(bytes))) => {
+            imports.push("bytes");
+            format!("bytes.NewReader([]byte(\"{}\"))", byte_escape(bytes))
+        }
+        Some(RequestBody::Binary(BinaryBody::File(path))) => {
+            imports.push("os");
+            setup.push_str(&format!(
+                "\tbody, err := os.Open({})\n\tif err != nil {{\n\t\tpanic(err)\n\t}}\n\tdefer body.Close()\n\n",
+                string(&path.display().to_string())
+            ));
+            "body".to_string()
+        }
+        Some(RequestBody::Multipart(parts)) => {
+            imports.extend(["bytes", "mime/multipart"]);
+            setup.push_str("\tvar body bytes.Buffer\n\twriter := multipart.NewWriter(&body)\n");
+            for part in parts {
+                match part {
+                    MultipartPart::Text { name, value } => setup.push_str(&format!(
+                        "\tif err := writer.WriteField({}, {}); err != nil {{\n\t\tpanic(err)\n\t}}\n",
+                        string(name),
+                        string(value)
+                    )),
+                    MultipartPart::File {
+                        name,
+                        path,
+                        content_type,
+                    } => {
+                        imports.push("os");
+                        setup.push_str(&format!(
+                            "\t{{\n\t\tfile, err := os.Open({})\n\t\tif err != nil {{\n\t\t\tpanic(err)\n\t\t}}\n\
+                             \t\tdefer file.Close()\n",
+                            string(&path.display().to_string())
+                        ));
+                        match content_type {
+                            Some(content_type) => {
+                                imports.push("net/textproto");
+                                let disposition = format!(
+                                    "form-data; name=\"{}\"; filename=\"{}\"",
+                                    quote_escape(name),
+                                    quote_escape(&file_name(path))
+                                );
+                                setup.push_str(&format!(
+                                    "\t\theader := make(textproto.MIMEHeader)\n\
+                                     \t\theader.Set(\"Content-Disposition\", {})\n\
+                                     \t\theader.Set(\"Content-Type\", {})\n\
+                                     \t\tpart, err := writer.CreatePart(header)\n",
+                                    string(&disposition),
+                                    string(content_type)
+                                ));
+                            }
+                            None => setup.push_str(&format!(
+                                "\t\tpart, err := writer.CreateFormFile({}, {})\n",
+                                string(name),
+                                string(&file_name(path))
+                            )),
+                        }
+                        setup.push_str(
+                            "\t\tif err != nil {\n\t\t\tpanic(err)\n\t\t}\n\
+                             \t\tif _, err := io.Copy(part, file); err != nil {\n\t\t\tpanic(err)\n\t\t}\n\t}\n",
+                        );
+                    }
+                }
+            }
+            setup.push_str("\tif err := writer.Close(); err != nil {\n\t\tpanic(err)\n\t}\n\n");
+            after.push_str("\treq.Header.Set(\"Content-Type\", writer.FormDataContentType())\n");
+            "&body".to_string()
+        }
+    };
+    imports.sort_unstable();
+    imports.dedup();
+
+    let mut out = String::from("package main\n\nimport (\n");
+    for import in imports {
+        out.push_str(&format!("\t\"{}\"\n", import));
+    }
+    out.push_str(")\n\nfunc main() {\n");
+    out.push_str(&setup);
+    out.push_str(&format!(
+        "\treq, err := http.NewRequest({}, {}, {})\n\tif err != nil {{\n\t\tpanic(err)\n\t}}\n",
+        string(request.method().as_str()),
+        string(&request.full_url()),
+        body
+    ));
+    for (name, value) in request.headers_as_sent().iter() {
+        out.push_str(&format!(
+            "\treq.Header.Add({}, {})\n",
+            string(name),
+            string(value)
+        ));
+    }
+    out.push_str(&after);
+    out.push_str(
+        "\n\tres, err := http.DefaultClient.Do(req)\n\tif err != nil {\n\t\tpanic(err)\n\t}\n\
+         \tdefer res.Body.Close()\n\n\tdata, err := io.ReadAll(res.Body)\n\tif err != nil {\n\t\tpanic(err)\n\t}\n\
+         \tfmt.Println(res.Status)\n\tfmt.Println(string(data))\n}\n",
+    );
+    out
+}
+
+fn rust(request: &Request) -> String {
+    let mut setup = String::new();
+    let method = match request.method() {
+        RequestMethod::Custom(method) => {
+            format!(
+                "reqwest::Method::from_bytes({})?",
+                rust_bytes(method.as_bytes())
+            )
+        }
+        method => format!("reqwest::Method::{}", method.as_str()),
+    };
+    let mut calls = vec![format!(
+        "request({}, {})",
+        method,
+        rust_string(&request.full_url())
+    )];
+    for (name, value) in request.headers_as_sent().iter() {
+        calls.push(format!(
+            "header({}, {})",
+            rust_string(name),
+            rust_string(value)
+        ));
+    }
+    match request.body() {
+        None => {}
+        Some(RequestBody::Json(value)) => calls.push(format!(
+            "json(&serde_json::json!({}))",
+            indent(
+                &serde_json::to_string_pretty(value).unwrap_or_default(),
+                "        "
+            )
+        )),
+        Some(RequestBody::Form(pairs)) => calls.push(format!(
+            "form(&[{}])",
+            pairs
+                .iter()
+                .map(|(name, value)| format!("({}, {})", rust_string(name), rust_string(value)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+        Some(RequestBody::Multipart(parts)) => {
+            setup.push_str("    let form = reqwest::multipart::Form::new()");
+            for part in parts {
+                setup.push_str(&match part {
+                    MultipartPart::Text { name, value } => format!(
+                        "\n        .text({}, {})",
+                        rust_string(name),
+                        rust_string(value)
+                    ),
+                    MultipartPart::File {
+                        name,
+                        path,
+                        content_type,
+                    } => format!(
+                        "\n        .part(\n            {},\n            \
+                         reqwest::multipart::Part::bytes(std::fs::read({})?)\n                \
+                         .file_name({}){},\n        )",
+                        rust_string(name),
+                        rust_string(&path.display().to_string()),
+                        rust_string(&file_name(path)),
+                        content_type
+                            .as_deref()
+                            .map(|content_type| format!(
+                                "\n                .mime_str({})?",
+                                rust_string(content_type)
+                            ))
+                            .unwrap_or_default()
+                    ),
+                });
+            }
+            setup.push_str(";\n\n");
+            calls.push("multipart(form)".to_string());
+        }
+        Some(RequestBody::Binary(BinaryBody::File(path))) => calls.push(format!(
+            "body(std::fs::read({})?)",
+            rust_string(&path.display().to_string())
+        )),
+        Some(RequestBody::Binary(BinaryBody::Bytes(bytes))) => {
+            calls.push(format!("body({}.to_vec())", rust_bytes(bytes)))
+        }
+        Some(RequestBody::Raw { content, .. }) => {
+            calls.push(format!("body({})", rust_string(content)))
+        }
+    }
+    calls.extend(["send()".to_string(), "await?".to_string()]);
+
+    format!(
+        "#[tokio::main]\nasync fn main() -> Result<(), Box<dyn std::error::Error>> {{\n{}\
+         \x20   let response = reqwest::Client::new()\n{};\n\n\
+         \x20   println!(\"{{}}\", response.status());\n\
+         \x20   println!(\"{{}}\", response.text().await?);\n\
+         \x20   Ok(())\n}}\n",
+        setup,
+        calls
+            .iter()
+            .map(|call| format!("        .{}", call))
+            .collect::<Vec<_>>()
+            .join("\n")
+    )
+}
+
+/// Repeated headers joined into one, for targets that take a map
+fn merged(headers: &MultiMap) -> Vec<(String, String)> {
+    let mut merged: Vec<(String, String)> = Vec::new();
+    for (name, value) in headers.iter() {
+        match merged
+            .iter_mut()
+            .find(|(existing, _)| existing.eq_ignore_ascii_case(name))
+        {
+            Some((_, existing)) => {
+                existing.push_str(", ");
+                existing.push_str(value);
+            }
+            None => merged.push((name.to_string(), value.to_string())),
+        }
+    }
+    merged
+}
+
+fn form(pairs: &MultiMap) -> String {
+    url::form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(pairs.iter())
+        .finish()
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Words are joined with backslash continuations, one flag per line
+fn command(stdin: Option<String>, program: &str, args: Vec<String>) -> String {
+    let mut out = match stdin {
+        Some(stdin) => format!("{} | {}", stdin, program),
+        None => program.to_string(),
+    };
+    match args.len() {
+        0 | 1 => {
+            for arg in args {
+                out.push(' ');
+                out.push_str(&arg);
+            }
+        }
+        _ => {
+            let mut args = args.into_iter();
+            out.push(' ');
+            out.push_str(&args.next().unwrap_or_default());
+            for arg in args {
+                out.push_str(" \\\n  ");
+                out.push_str(&arg);
+            }
+        }
+    }
+    out.push('\n');
+    out
+}
+
+/// Single-quoted for POSIX shells unless the word is plain
+fn shell(word: &str) -> String {
+    let plain = !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c));
+    match plain {
+        true => word.to_string(),
+        false => format!("'{}'", word.replace('\'', "'\\''")),
+    }
+}
+
+/// A `printf` writing `bytes` to stdout, with octal escapes as POSIX requires
+fn printf(bytes: &[u8]) -> String {
+    let mut format = String::new();
+    for &byte in bytes {
+        match byte {
+            b'%' => format.push_str("%%"),
+            b'\\' => format.push_str("\\\\"),
+            b' '..=b'~' => format.push(byte as char),
+            _ => format.push_str(&format!("\\{:03o}", byte)),
+        }
+    }
+    format!("printf {}", shell(&format))
+}
+
+/// Escapes separators in an HTTPie request item's name
+fn item_name(name: &str) -> String {
+    let mut escaped = String::new();
+    for c in name.chars() {
+        if matches!(c, '\\' | ':' | '=' | '@' | ';') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// A request item value, with a leading `@` or `=` escaped so that HTTPie does not
+/// read it as part of the separator, e.g. `=@` which reads a file
+fn item_value(value: &str) -> String {
+    match value.starts_with(['@', '=']) {
+        true => format!("\\{}", value),
+        false => value.to_string(),
+    }
+}
+
+/// A double-quoted literal valid in Python, JavaScript and Go
+fn string(s: &str) -> String {
+    serde_json::to_string(s).unwrap_or_default()
+}
+
+/// A raw string when it reads better and stays exact, else a quoted one
+fn go_string(s: &str) -> String {
+    let raw = s.contains('\n')
+        && !s
+            .chars()
+            .any(|c| c == '`' || (c.is_control() && c != '\n' && c != '\t'));
+    match raw {
+        true => format!("`{}`", s),
+        false => string(s),
+    }
+}
+
+fn rust_string(s: &str) -> String {
+    format!("{:?}", s)
+}
+
+fn rust_bytes(bytes: &[u8]) -> String {
+    format!("b\"{}\"", byte_escape(bytes))
+}
+
+/// Content of a byte string literal, `\xNN` escapes are shared by Python, Go and Rust
+fn byte_escape(bytes: &[u8]) -> String {
+    let mut escaped = String::new();
+    for &byte in bytes {
+        match byte {
+            b'"' => escaped.push_str("\\\""),
+            b'\\' => escaped.push_str("\\\\"),
+            b' '..=b'~' => escaped.push(byte as char),
+            _ => escaped.push_str(&format!("\\x{:02x}", byte)),
+        }
+    }
+    escaped
+}
+
+/// For a quoted-string inside a header value
+fn quote_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Indents every line but the first, which continues the current one
+fn indent(text: &str, prefix: &str) -> String {
+    text.lines()
+        .enumerate()
+        .map(|(index, line)| match index {
+            0 => line.to_string(),
+            _ => format!("{}{}", prefix, line),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A Python literal, JSON's `true`, `false` and `null` are spelled differently
+fn python_value(value: &Value, depth: usize) -> String {
+    let pad = "    ".repeat(depth + 1);
+    let close = "    ".repeat(depth);
+    match value {
+        Value::Null => "None".to_string(),
+        Value::Bool(true) => "True".to_string(),
+        Value::Bool(false) => "False".to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => string(s),
+        Value::Array(items) if items.is_empty() => "[]".to_string(),
+        Value::Array(items) => format!(
+            "[\n{}{}]",
+            items
+                .iter()
+                .map(|item| format!("{}{},\n", pad, python_value(item, depth + 1)))
+                .collect::<String>(),
+            close
+        ),
+        Value::Object(map) if map.is_empty() => "{}".to_string(),
+        Value::Object(map) => format!(
+            "{{\n{}{}}}",
+            map.iter()
+                .map(|(key, value)| {
+                    format!(
+                        "{}{}: {},\n",
+                        pad,
+                        string(key),
+                        python_value(value, depth + 1)
+                    )
+                })
+                .collect::<String>(),
+            close
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{shell, Language};
+    use crate::{
+        body::{BinaryBody, MultipartPart, RequestBody},
+        curl,
+        multimap::MultiMap,
+        request::{Request, RequestMethod},
+    };
+    use serde_json::json;
+    use std::path::PathBuf;
+
+    fn request(method: RequestMethod, body: Option<RequestBody>) -> Request {
+        Request::new(
+            body,
+            MultiMap::from([("Authorization", "Bearer it's")]),
+            method,
+            "https://api.example.com/orders".to_string(),
+            MultiMap::from([("q", "a b&c")]),
+        )
+    }
+
+    #[test]
+    fn quote_for_the_shell() {
+        assert_eq!("https://x.io/a", shell("https://x.io/a"));
+        assert_eq!("'a b'", shell("a b"));
+        assert_eq!("'it'\\''s'", shell("it's"));
+        assert_eq!("''", shell(""));
+        assert_eq!("'$HOME'", shell("$HOME"));
+    }
+
+    #[test]
+    fn curl_round_trips_through_the_importer() {
+        let bodies = [
+            None,
+            Some(RequestBody::Raw {
+                content: "line one\nline 'two' $HOME `cmd` \\".to_string(),
+                content_type: "text/plain".to_string(),
+            }),
+            Some(RequestBody::Binary(BinaryBody::File(PathBuf::from(
+                "my dump.bin",
+            )))),
+        ];
+        for body in bodies {
+            let original = request(RequestMethod::PUT, body);
+            let command = Language::Curl.render(&original);
+            let imported = curl::parse(&command).unwrap();
+            assert!(imported.warnings.is_empty(), "{}", command);
+            assert_eq!(&RequestMethod::PUT, imported.request.method());
+            assert_eq!(
+                "https://api.example.com/orders?q=a+b%26c",
+                imported.request.url()
+            );
+            assert_eq!(
+                Some("Bearer it's"),
+                imported.request.headers().get("authorization")
+            );
+            assert_eq!(original.body(), imported.request.body(), "{}", command);
+        }
+
+        let original = request(RequestMethod::GET, Some(RequestBody::Json(json!({"q": 1}))));
+        let command = Language::Curl.render(&original);
+        assert!(command.starts_with("curl -X GET "), "{}", command);
+        let imported = curl::parse(&command).unwrap();
+        assert_eq!(&RequestMethod::GET, imported.request.method());
+        assert_eq!(original.body().is_some(), imported.request.body().is_some());
+    }
+
+    #[test]
+    fn render_every_language() {
+        let multipart = RequestBody::Multipart(vec![
+            MultipartPart::Text {
+                name: "title".to_string(),
+                value: "@not a file".to_string(),
+            },
+            MultipartPart::File {
+                name: "file".to_string(),
+                path: PathBuf::from("out/report.pdf"),
+                content_type: Some("application/pdf".to_string()),
+            },
+        ]);
+        let json = RequestBody::Json(json!({"ok": true, "tags": [null]}));
+        let custom = RequestMethod::Custom("PURGE".to_string());
+
+        let curl = Language::Curl.render(&request(RequestMethod::POST, Some(multipart.clone())));
+        assert!(
+            curl.contains("--form-string 'title=@not a file'"),
+            "{}",
+            curl
+        );
+        assert!(curl.contains("-F 'file=@out/report.pdf;type=application/pdf'"));
+        let httpie =
+            Language::Httpie.render(&request(RequestMethod::POST, Some(multipart.clone())));
+        assert!(httpie.contains("'title=\\@not a file'"), "{}", httpie);
+        assert!(httpie.contains("'file@out/report.pdf;type=application/pdf'"));
+
+        let httpie = Language::Httpie.render(&request(custom.clone(), Some(json.clone())));
+        assert!(httpie.starts_with("http --raw '{\"ok\":true,\"tags\":[null]}' \\\n  PURGE "));
+        assert!(
+            httpie.contains("'authorization:Bearer it'\\''s'"),
+            "{}",
+            httpie
+        );
+
+        let python = Language::Python.render(&request(RequestMethod::POST, Some(json.clone())));
+        assert!(
+            python.contains(
+                "payload = {\n    \"ok\": True,\n    \"tags\": [\n        None,\n    ],\n}"
+            ),
+            "{}",
+            python
+        );
+        assert!(python.contains("requests.request(\"POST\", url, headers=headers, json=payload)"));
+
+        let javascript =
+            Language::JavaScript.render(&request(RequestMethod::POST, Some(multipart.clone())));
+        assert!(javascript.starts_with("import { openAsBlob } from \"node:fs\";"));
+        assert!(javascript.contains(
+            "form.append(\"file\", await openAsBlob(\"out/report.pdf\", { type: \"application/pdf\" }), \"report.pdf\");"
+        ), "{}", javascript);
+        assert!(javascript.contains("fetch(\"https://api.example.com/orders?q=a+b%26c\", {"));
+
+        let go = Language::Go.render(&request(RequestMethod::POST, Some(multipart)));
+        assert!(
+            go.contains("\t\"mime/multipart\"\n\t\"net/http\"\n\t\"net/textproto\"\n\t\"os\"\n"),
+            "{}",
+            go
+        );
+        assert!(go.contains("req.Header.Set(\"Content-Type\", writer.FormDataContentType())"));
+
+        let binary = RequestBody::Binary(BinaryBody::Bytes(vec![0, b'"', 0xff]));
+        let rust = Language::Rust.render(&request(custom, Some(binary)));
+        assert!(rust.contains(".request(reqwest::Method::from_bytes(b\"PURGE\")?, \"https://api.example.com/orders?q=a+b%26c\")"), "{}", rust);
+        assert!(rust.contains(".body(b\"\\x00\\\"\\xff\".to_vec())"));
+        assert!(rust.contains(".header(\"authorization\", \"Bearer it's\")"));
+
+        assert_eq!(Ok(Language::JavaScript), "JS".parse());
+        assert!("cobol".parse::<Language>().is_err());
+    }
+}
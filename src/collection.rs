@@ -0,0 +1,663 @@
+use crate::{
+    assertion::Assertion,
+    extract::Extraction,
+    request::Request,
+    variables::{Environment, VariableMap},
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    error, fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Named requests organized in nested folders, saved as a JSON or TOML file.
+///
+/// Items are addressed by slash-separated paths made of folder and request
+/// names, e.g. `users/create`. The empty path is the collection root.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Collection {
+    info: CollectionInfo,
+    #[serde(default, skip_serializing_if = "VariableMap::is_empty")]
+    variables: VariableMap,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    environments: Vec<Environment>,
+    #[serde(default)]
+    items: Vec<Item>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct CollectionInfo {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Item {
+    Folder(Folder),
+    Request(SavedRequest),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Folder {
+    pub name: String,
+    #[serde(default)]
+    pub items: Vec<Item>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SavedRequest {
+    pub name: String,
+    pub request: Request,
+    #[serde(default, skip_serializing_if = "VariableMap::is_empty")]
+    pub variables: VariableMap, // Request-local, they win over every other scope
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extract: Vec<Extraction>, // Applied to the response, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assertions: Vec<Assertion>,
+}
+
+/// On-disk format, picked from the file extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Toml,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Json(serde_json::Error),
+    TomlDe(toml::de::Error),
+    TomlSer(toml::ser::Error),
+    UnsupportedFormat(PathBuf),
+    NotFound(String),
+    AlreadyExists(String),
+    InvalidName(String),
+    InvalidMove { from: String, to: String },
+}
+
+impl Collection {
+    pub fn new(name: impl Into<String>) -> Collection {
+        Collection {
+            info: CollectionInfo {
+                name: name.into(),
+                ..CollectionInfo::default()
+            },
+            variables: VariableMap::new(),
+            environments: Vec::new(),
+            items: Vec::new(),
+        }
+    }
+
+    pub fn info(&self) -> &CollectionInfo {
+        &self.info
+    }
+
+    pub fn info_mut(&mut self) -> &mut CollectionInfo {
+        &mut self.info
+    }
+
+    pub fn items(&self) -> &[Item] {
+        &self.items
+    }
+
+    /// Collection-scoped variables
+    pub fn variables(&self) -> &VariableMap {
+        &self.variables
+    }
+
+    pub fn variables_mut(&mut self) -> &mut VariableMap {
+        &mut self.variables
+    }
+
+    pub fn environments(&self) -> &[Environment] {
+        &self.environments
+    }
+
+    pub fn environment(&self, name: &str) -> Option<&Environment> {
+        self.environments.iter().find(|env| env.name == name)
+    }
+
+    /// Adds `environment`, replacing the one with the same name
+    pub fn set_environment(&mut self, environment: Environment) {
+        match self
+            .environments
+            .iter_mut()
+            .find(|env| env.name == environment.name)
+        {
+            Some(existing) => *existing = environment,
+            None => self.environments.push(environment),
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Collection, Error> {
+        let path = path.as_ref();
+        let format = Format::from_path(path)?;
+        Collection::parse(&fs::read_to_string(path).map_err(Error::Io)?, format)
+    }
+
+    pub fn parse(content: &str, format: Format) -> Result<Collection, Error> {
+        format.parse(content)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let path = path.as_ref();
+        let content = self.to_string(Format::from_path(path)?)?;
+        fs::write(path, content).map_err(Error::Io)
+    }
+
+    pub fn to_string(&self, format: Format) -> Result<String, Error> {
+        format.to_string(self)
+    }
+
+    pub fn get(&self, path: &str) -> Option<&SavedRequest> {
+        match self.item(path)? {
+            Item::Request(saved) => Some(saved),
+            Item::Folder(_) => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, path: &str) -> Option<&mut SavedRequest> {
+        let (folder, name) = split_path(path);
+        let items = items_mut(&mut self.items, &folder)?;
+        match items.iter_mut().find(|item| item.name() == name)? {
+            Item::Request(saved) => Some(saved),
+            Item::Folder(_) => None,
+        }
+    }
+
+    /// The folder or request at `path`
+    pub fn item(&self, path: &str) -> Option<&Item> {
+        let (folder, name) = split_path(path);
+        items(&self.items, &folder)?
+            .iter()
+            .find(|item| item.name() == name)
+    }
+
+    /// Adds `saved` to `folder`, creating missing folders along the way
+    pub fn add(&mut self, folder: &str, saved: SavedRequest) -> Result<(), Error> {
+        self.insert(folder, Item::Request(saved))
+    }
+
+    /// Creates the folder at `path` and its missing parents
+    pub fn add_folder(&mut self, path: &str) -> Result<(), Error> {
+        let (parent, name) = split_path(path);
+        self.insert(
+            &parent.join("/"),
+            Item::Folder(Folder {
+                name: name.to_string(),
+                items: Vec::new(),
+            }),
+        )
+    }
+
+    /// A valid name close to `name` that no item in `folder` uses yet, e.g. for
+    /// importers: slashes become dashes and repeats get a ` (2)`, ` (3)`... suffix
+    pub fn unique_name(&self, folder: &str, name: &str) -> String {
+        let base = match name.trim() {
+            "" => "untitled".to_string(),
+            name => name.replace('/', "-"),
+        };
+        let taken = |name: &str| {
+            items(&self.items, &segments(folder))
+                .is_some_and(|items| items.iter().any(|item| item.name() == name))
+        };
+        let mut candidate = base.clone();
+        let mut n = 1;
+        while taken(&candidate) {
+            n += 1;
+            candidate = format!("{} ({})", base, n);
+        }
+        candidate
+    }
+
+    pub fn rename(&mut self, path: &str, new_name: &str) -> Result<(), Error> {
+        validate_name(new_name)?;
+        let (folder, name) = split_path(path);
+        let items = items_mut(&mut self.items, &folder).ok_or_else(|| not_found(path))?;
+        if items.iter().any(|item| item.name() == new_name) {
+            return Err(Error::AlreadyExists(join(&folder, new_name)));
+        }
+        let item = items
+            .iter_mut()
+            .find(|item| item.name() == name)
+            .ok_or_else(|| not_found(path))?;
+        item.set_name(new_name.to_string());
+        Ok(())
+    }
+
+    /// Moves the item at `path` into `folder`, keeping its name
+    pub fn move_item(&mut self, path: &str, folder: &str) -> Result<(), Error> {
+        let path = normalize(path);
+        let folder = normalize(folder);
+        if folder == path || folder.starts_with(&format!("{}/", path)) {
+            return Err(Error::InvalidMove {
+                from: path,
+                to: folder,
+            });
+        }
+        let item = self.item(&path).ok_or_else(|| not_found(&path))?;
+        let (_, name) = split_path(&path);
+        if let Some(items) = items(&self.items, &segments(&folder)) {
+            if items.iter().any(|other| other.name() == name) {
+                return Err(Error::AlreadyExists(join(&segments(&folder), name)));
+            }
+        }
+        let item = item.clone();
+        self.remove(&path)?;
+        self.insert(&folder, item)
+    }
+
+    pub fn remove(&mut self, path: &str) -> Result<Item, Error> {
+        let (folder, name) = split_path(path);
+        let items = items_mut(&mut self.items, &folder).ok_or_else(|| not_found(path))?;
+        let index = items
+            .iter()
+            .position(|item| item.name() == name)
+            .ok_or_else(|| not_found(path))?;
+        Ok(items.remove(index))
+    }
+
+    /// Every request with its path, depth first in file order
+    pub fn requests(&self) -> Vec<(String, &SavedRequest)> {
+        let mut requests = Vec::new();
+        collect_requests(&self.items, "", &mut requests);
+        requests
+    }
+
+    /// Every request under the folder at `path`, with paths relative to the root
+    pub fn requests_in(&self, path: &str) -> Result<Vec<(String, &SavedRequest)>, Error> {
+        let path = normalize(path);
+        let items = items(&self.items, &segments(&path)).ok_or_else(|| not_found(&path))?;
+        let mut requests = Vec::new();
+        collect_requests(items, &path, &mut requests);
+        Ok(requests)
+    }
+
+    fn insert(&mut self, folder: &str, item: Item) -> Result<(), Error> {
+        validate_name(item.name())?;
+        let mut items = &mut self.items;
+        for name in segments(folder) {
+            validate_name(name)?;
+            let index = match items.iter().position(|item| item.name() == name) {
+                Some(index) => index,
+                None => {
+                    items.push(Item::Folder(Folder {
+                        name: name.to_string(),
+                        items: Vec::new(),
+                    }));
+                    items.len() - 1
+                }
+            };
+            items = match &mut items[index] {
+                Item::Folder(folder) => &mut folder.items,
+                Item::Request(_) => return Err(Error::AlreadyExists(name.to_string())),
+            };
+        }
+        if items.iter().any(|other| other.name() == item.name()) {
+            return Err(Error::AlreadyExists(join(&segments(folder), item.name())));
+        }
+        items.push(item);
+        Ok(())
+    }
+}
+
+impl Item {
+    pub fn name(&self) -> &str {
+        match self {
+            Item::Folder(folder) => &folder.name,
+            Item::Request(saved) => &saved.name,
+        }
+    }
+
+    fn set_name(&mut self, name: String) {
+        match self {
+            Item::Folder(folder) => folder.name = name,
+            Item::Request(saved) => saved.name = name,
+        }
+    }
+}
+
+impl SavedRequest {
+    pub fn new(name: impl Into<String>, request: Request) -> SavedRequest {
+        SavedRequest {
+            name: name.into(),
+            request,
+            variables: VariableMap::new(),
+            extract: Vec::new(),
+            assertions: Vec::new(),
+        }
+    }
+}
+
+impl Format {
+    pub fn from_path(path: &Path) -> Result<Format, Error> {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => Ok(Format::Json),
+            Some("toml") => Ok(Format::Toml),
+            _ => Err(Error::UnsupportedFormat(path.to_path_buf())),
+        }
+    }
+
+    pub fn parse<T: DeserializeOwned>(self, content: &str) -> Result<T, Error> {
+        match self {
+            Format::Json => serde_json::from_str(content).map_err(Error::Json),
+            Format::Toml => toml::from_str(content).map_err(Error::TomlDe),
+        }
+    }
+
+    pub fn to_string<T: Serialize>(self, value: &T) -> Result<String, Error> {
+        match self {
+            Format::Json => serde_json::to_string_pretty(value).map_err(Error::Json),
+            Format::Toml => toml::to_string_pretty(value).map_err(Error::TomlSer),
+        }
+    }
+}
+
+fn segments(path: &str) -> Vec<&str> {
+    path.split('/').filter(|s| !s.is_empty()).collect()
+}
+
+fn normalize(path: &str) -> String {
+    segments(path).join("/")
+}
+
+fn join(folder: &[&str], name: &str) -> String {
+    folder
+        .iter()
+        .copied()
+        .chain(std::iter::once(name))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Splits a path into its parent folder segments and the item name
+fn split_path(path: &str) -> (Vec<&str>, &str) {
+    let mut segments = segments(path);
+    let name = segments.pop().unwrap_or_default();
+    (segments, name)
+}
+
+fn not_found(path: &str) -> Error {
+    Error::NotFound(normalize(path))
+}
+
+fn validate_name(name: &str) -> Result<(), Error> {
+    if name.trim().is_empty() || name.contains('/') {
+        return Err(Error::InvalidName(name.to_string()));
+    }
+    Ok(())
+}
+
+fn items<'a>(mut items: &'a [Item], folder: &[&str]) -> Option<&'a [Item]> {
+    for name in folder {
+        items = match items.iter().find(|item| item.name() == *name)? {
+            Item::Folder(folder) => &folder.items,
+            Item::Request(_) => return None,
+        };
+    }
+    Some(items)
+}
+
+fn items_mut<'a>(mut items: &'a mut Vec<Item>, folder: &[&str]) -> Option<&'a mut Vec<Item>> {
+    for name in folder {
+        items = match items.iter_mut().find(|item| item.name() == *name)? {
+            Item::Folder(folder) => &mut folder.items,
+            Item::Request(_) => return None,
+        };
+    }
+    Some(items)
+}
+
+fn collect_requests<'a>(
+    items: &'a [Item],
+    prefix: &str,
+    out: &mut Vec<(String, &'a SavedRequest)>,
+) {
+    for item in items {
+        let path = match prefix {
+            "" => item.name().to_string(),
+            prefix => format!("{}/{}", prefix, item.name()),
+        };
+        match item {
+            Item::Folder(folder) => collect_requests(&folder.items, &path, out),
+            Item::Request(saved) => out.push((path, saved)),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(_) => write!(f, "could not access file"),
+            Error::Json(_) | Error::TomlDe(_) => write!(f, "invalid file contents"),
+            Error::TomlSer(_) => write!(f, "value cannot be written as TOML"),
+            Error::UnsupportedFormat(path) => write!(
+                f,
+                "unsupported file format `{}`, expected .json or .toml",
+                path.display()
+            ),
+            Error::NotFound(path) => write!(f, "no item at `{}`", path),
+            Error::AlreadyExists(path) => write!(f, "an item already exists at `{}`", path),
+            Error::InvalidName(name) => write!(f, "invalid item name `{}`", name),
+            Error::InvalidMove { from, to } => {
+                write!(f, "cannot move `{}` into `{}`", from, to)
+            }
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Json(e) => Some(e),
+            Error::TomlDe(e) => Some(e),
+            Error::TomlSer(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Collection, Error, Format, Item, SavedRequest};
+    use crate::{
+        extract::{Extraction, Source, Target},
+        multimap::MultiMap,
+        request::{Request, RequestMethod},
+        variables::Environment,
+    };
+
+    fn saved(name: &str, method: RequestMethod, url: &str) -> SavedRequest {
+        SavedRequest::new(
+            name,
+            Request::new(
+                None,
+                MultiMap::from([("Accept", "application/json")]),
+                method,
+                url.to_string(),
+                MultiMap::new(),
+            ),
+        )
+    }
+
+    fn sample() -> Collection {
+        let mut collection = Collection::new("api");
+        collection
+            .add(
+                "users",
+                saved("list", RequestMethod::GET, "http://localhost/users"),
+            )
+            .unwrap();
+        collection
+            .add(
+                "users/admin",
+                saved("create", RequestMethod::POST, "http://localhost/admins"),
+            )
+            .unwrap();
+        collection
+            .add("", saved("health", RequestMethod::GET, "http://localhost/"))
+            .unwrap();
+        collection
+    }
+
+    fn paths(collection: &Collection) -> Vec<String> {
+        collection
+            .requests()
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect()
+    }
+
+    #[test]
+    fn add_requests_in_nested_folders() {
+        let collection = sample();
+        assert_eq!(
+            vec!["users/list", "users/admin/create", "health"],
+            paths(&collection)
+        );
+        assert_eq!(
+            "http://localhost/admins",
+            collection.get("users/admin/create").unwrap().request.url()
+        );
+        assert!(collection.get("users").is_none());
+        assert!(matches!(collection.item("users"), Some(Item::Folder(_))));
+    }
+
+    #[test]
+    fn reject_duplicate_and_invalid_names() {
+        let mut collection = sample();
+        assert!(matches!(
+            collection.add(
+                "users",
+                saved("list", RequestMethod::GET, "http://localhost/")
+            ),
+            Err(Error::AlreadyExists(path)) if path == "users/list"
+        ));
+        assert!(matches!(
+            collection.add("", saved(" ", RequestMethod::GET, "http://localhost/")),
+            Err(Error::InvalidName(_))
+        ));
+        assert!(matches!(
+            collection.add(
+                "health",
+                saved("nested", RequestMethod::GET, "http://localhost/")
+            ),
+            Err(Error::AlreadyExists(_))
+        ));
+    }
+
+    #[test]
+    fn unique_names() {
+        let collection = sample();
+        assert_eq!("login", collection.unique_name("auth", "login"));
+        assert_eq!(
+            "create (2)",
+            collection.unique_name("users/admin", "create")
+        );
+        assert_eq!("admin (2)", collection.unique_name("users", "admin"));
+        assert_eq!(
+            "GET -users-:id",
+            collection.unique_name("", "GET /users/:id")
+        );
+        assert_eq!("untitled", collection.unique_name("missing", " "));
+    }
+
+    #[test]
+    fn rename_move_and_remove_items() {
+        let mut collection = sample();
+        collection.rename("users/list", "all").unwrap();
+        collection.move_item("users/admin", "").unwrap();
+        collection.add_folder("misc/old").unwrap();
+        collection.move_item("health", "misc/old").unwrap();
+        assert_eq!(
+            vec!["users/all", "admin/create", "misc/old/health"],
+            paths(&collection)
+        );
+
+        assert!(matches!(
+            collection.move_item("misc", "misc/old"),
+            Err(Error::InvalidMove { .. })
+        ));
+        assert!(matches!(
+            collection.remove("misc/old/health"),
+            Ok(Item::Request(saved)) if saved.name == "health"
+        ));
+        assert!(matches!(
+            collection.remove("misc/old/health"),
+            Err(Error::NotFound(_))
+        ));
+        assert_eq!(
+            vec!["admin/create"],
+            collection
+                .requests_in("admin")
+                .unwrap()
+                .into_iter()
+                .map(|(path, _)| path)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn round_trip_json_and_toml() {
+        let collection = sample();
+        for format in [Format::Json, Format::Toml] {
+            let content = collection.to_string(format).unwrap();
+            assert_eq!(collection, Collection::parse(&content, format).unwrap());
+        }
+    }
+
+    #[test]
+    fn round_trip_variables_and_environments() {
+        let mut collection = sample();
+        collection
+            .variables_mut()
+            .insert("version".to_string(), "v1".to_string());
+        let mut dev = Environment::new("dev");
+        dev.variables
+            .insert("host".to_string(), "localhost".to_string());
+        collection.set_environment(dev.clone());
+        dev.variables.insert("port".to_string(), "8080".to_string());
+        collection.set_environment(dev);
+        collection.set_environment(Environment::new("prod"));
+        let list = collection.get_mut("users/list").unwrap();
+        list.variables.insert("page".to_string(), "1".to_string());
+        list.extract.push(
+            Extraction::new("first", Source::JsonPath("$[0].id".to_string()))
+                .with_scope(Target::Environment),
+        );
+
+        assert_eq!(2, collection.environments().len());
+        assert_eq!(
+            Some("8080"),
+            collection
+                .environment("dev")
+                .and_then(|env| env.variables.get("port"))
+                .map(String::as_str)
+        );
+        for format in [Format::Json, Format::Toml] {
+            let content = collection.to_string(format).unwrap();
+            assert_eq!(collection, Collection::parse(&content, format).unwrap());
+        }
+    }
+
+    #[test]
+    fn save_and_load_from_disk() {
+        let collection = sample();
+        let path = std::env::temp_dir().join(format!("asterios-{}.toml", std::process::id()));
+        collection.save(&path).unwrap();
+        let loaded = Collection::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(collection, loaded.unwrap());
+
+        assert!(matches!(
+            collection.save("collection.yaml"),
+            Err(Error::UnsupportedFormat(_))
+        ));
+    }
+}
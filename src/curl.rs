@@ -0,0 +1,856 @@
+use crate::{
+    body::{BinaryBody, MultipartPart, RequestBody},
+    multimap::MultiMap,
+    request::{HeaderCase, Request, RequestMethod},
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use std::{error, fmt, iter::Peekable, path::PathBuf, str::Chars};
+
+/// A request parsed from a curl command line
+#[derive(Debug, Clone, PartialEq)]
+pub struct Import {
+    pub request: Request,
+    pub insecure: bool,        // `-k`, the request itself can't carry it
+    pub warnings: Vec<String>, // Flags that were not carried over, in order
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    NotCurl,
+    UnterminatedQuote,
+    MissingValue(String), // Flag given as the last word
+    MissingUrl,
+    SeveralUrls(Vec<String>), // Often the value of a flag we don't know takes one
+    InvalidMethod(String),
+    ConflictingBodies, // Both `-d` and `-F`, which curl refuses too
+}
+
+/// What a flag does to the request
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Flag {
+    Method,
+    Header,
+    Data(DataKind),
+    Json,
+    Form { literal: bool }, // `--form-string` never reads files
+    User,
+    Bearer,
+    Get,
+    Head,
+    Cookie,
+    UserAgent,
+    Referer,
+    Url,
+    Insecure,
+    Compressed,
+    Ignored { value: bool }, // Doesn't change what is sent, e.g. `-s` or `-o file`
+    Unsupported { value: bool },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DataKind {
+    Ascii,  // `-d`, `@file` reads a file without its line breaks
+    Raw,    // `--data-raw`, `@` is not special
+    Binary, // `--data-binary`, `@file` reads a file as-is
+    Urlencode,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Data {
+    Text(String),
+    File(PathBuf),
+}
+
+/// Parses a curl command as pasted from a shell or from browser devtools'
+/// "Copy as cURL": quotes, `$'...'` strings and backslash line continuations
+/// are understood.
+///
+/// Flags without an equivalent are skipped with a warning rather than failing.
+/// Responses are not decompressed, so `--compressed` is ignored and
+/// `Accept-Encoding` headers are dropped, as devtools copies always add one.
+pub fn parse(command: &str) -> Result<Import, Error> {
+    let words = split(command)?;
+    let mut words = words.into_iter();
+    match words.next() {
+        Some(program) if program == "curl" || program.ends_with("/curl") => {}
+        _ => return Err(Error::NotCurl),
+    }
+
+    let mut parser = Parser::default();
+    while let Some(word) = words.next() {
+        if word == "--" {
+            parser.urls.extend(words.by_ref());
+            break;
+        }
+        if let Some(name) = word.strip_prefix("--").filter(|name| !name.is_empty()) {
+            let flag = long_flag(name);
+            let value = match flag.takes_value() {
+                true => Some(
+                    words
+                        .next()
+                        .ok_or_else(|| Error::MissingValue(word.clone()))?,
+                ),
+                false => None,
+            };
+            parser.apply(flag, &word, value);
+            continue;
+        }
+        match word.strip_prefix('-').filter(|cluster| !cluster.is_empty()) {
+            // Short flags can be grouped like `-sSL`, a value is either the rest
+            // of the group (`-XPOST`) or the next word
+            Some(cluster) => {
+                for (index, c) in cluster.char_indices() {
+                    let flag = short_flag(c);
+                    if !flag.takes_value() {
+                        parser.apply(flag, &format!("-{}", c), None);
+                        continue;
+                    }
+                    let rest = &cluster[index + c.len_utf8()..];
+                    let value = match rest {
+                        "" => words
+                            .next()
+                            .ok_or_else(|| Error::MissingValue(format!("-{}", c)))?,
+                        _ => rest.to_string(),
+                    };
+                    parser.apply(flag, &format!("-{}", c), Some(value));
+                    break;
+                }
+            }
+            None => parser.urls.push(word),
+        }
+    }
+    parser.finish()
+}
+
+fn long_flag(name: &str) -> Flag {
+    match name {
+        "request" => Flag::Method,
+        "header" => Flag::Header,
+        "data" | "data-ascii" => Flag::Data(DataKind::Ascii),
+        "data-raw" => Flag::Data(DataKind::Raw),
+        "data-binary" => Flag::Data(DataKind::Binary),
+        "data-urlencode" => Flag::Data(DataKind::Urlencode),
+        "json" => Flag::Json,
+        "form" => Flag::Form { literal: false },
+        "form-string" => Flag::Form { literal: true },
+        "user" => Flag::User,
+        "oauth2-bearer" => Flag::Bearer,
+        "get" => Flag::Get,
+        "head" => Flag::Head,
+        "cookie" => Flag::Cookie,
+        "user-agent" => Flag::UserAgent,
+        "referer" => Flag::Referer,
+        "url" => Flag::Url,
+        "insecure" => Flag::Insecure,
+        "compressed" => Flag::Compressed,
+        "silent" | "show-error" | "verbose" | "include" | "location" | "fail" | "progress-bar"
+        | "no-progress-meter" | "no-buffer" | "basic" => Flag::Ignored { value: false },
+        "output" | "write-out" | "stderr" | "trace" | "trace-ascii" => {
+            Flag::Ignored { value: true }
+        }
+        "max-time"
+        | "connect-timeout"
+        | "proxy"
+        | "proxy-user"
+        | "cacert"
+        | "capath"
+        | "cert"
+        | "key"
+        | "cert-type"
+        | "key-type"
+        | "pass"
+        | "cookie-jar"
+        | "dump-header"
+        | "upload-file"
+        | "range"
+        | "resolve"
+        | "connect-to"
+        | "retry"
+        | "retry-delay"
+        | "retry-max-time"
+        | "max-redirs"
+        | "limit-rate"
+        | "interface"
+        | "config"
+        | "aws-sigv4"
+        | "unix-socket"
+        | "abstract-unix-socket"
+        | "ciphers"
+        | "tls13-ciphers"
+        | "curves"
+        | "tls-max"
+        | "pinnedpubkey"
+        | "crlfile"
+        | "max-filesize"
+        | "continue-at"
+        | "local-port"
+        | "request-target"
+        | "socks4"
+        | "socks4a"
+        | "socks5"
+        | "socks5-hostname"
+        | "preproxy"
+        | "noproxy"
+        | "proxy-header"
+        | "proxy-cacert"
+        | "proxy-capath"
+        | "proxy-cert"
+        | "proxy-key"
+        | "proxy-pass"
+        | "proxy-service-name"
+        | "netrc-file"
+        | "dns-servers"
+        | "dns-interface"
+        | "dns-ipv4-addr"
+        | "dns-ipv6-addr"
+        | "doh-url"
+        | "hsts"
+        | "alt-svc"
+        | "etag-compare"
+        | "etag-save"
+        | "output-dir"
+        | "time-cond"
+        | "url-query"
+        | "variable"
+        | "expect100-timeout"
+        | "keepalive-time"
+        | "speed-limit"
+        | "speed-time"
+        | "login-options"
+        | "sasl-authzid"
+        | "service-name"
+        | "delegation"
+        | "proto"
+        | "proto-redir"
+        | "proto-default"
+        | "quote"
+        | "telnet-option"
+        | "ftp-port"
+        | "mail-from"
+        | "mail-rcpt"
+        | "mail-auth"
+        | "happy-eyeballs-timeout-ms"
+        | "parallel-max"
+        | "rate" => Flag::Unsupported { value: true },
+        _ => Flag::Unsupported { value: false },
+    }
+}
+
+fn short_flag(c: char) -> Flag {
+    match c {
+        'X' => Flag::Method,
+        'H' => Flag::Header,
+        'd' => Flag::Data(DataKind::Ascii),
+        'F' => Flag::Form { literal: false },
+        'u' => Flag::User,
+        'G' => Flag::Get,
+        'I' => Flag::Head,
+        'b' => Flag::Cookie,
+        'A' => Flag::UserAgent,
+        'e' => Flag::Referer,
+        'k' => Flag::Insecure,
+        's' | 'S' | 'v' | 'i' | 'L' | 'f' | '#' | 'N' => Flag::Ignored { value: false },
+        'o' | 'w' => Flag::Ignored { value: true },
+        'm' | 'x' | 'U' | 'E' | 'c' | 'D' | 'T' | 'r' | 'K' | 'C' | 'y' | 'Y' | 'z' | 'P' | 'Q'
+        | 't' => Flag::Unsupported { value: true },
+        _ => Flag::Unsupported { value: false },
+    }
+}
+
+impl Flag {
+    fn takes_value(self) -> bool {
+        !matches!(
+            self,
+            Flag::Get
+                | Flag::Head
+                | Flag::Insecure
+                | Flag::Compressed
+                | Flag::Ignored { value: false }
+                | Flag::Unsupported { value: false }
+        )
+    }
+}
+
+#[derive(Default)]
+struct Parser {
+    method: Option<String>,
+    headers: MultiMap,
+    data: Vec<Data>,
+    form: Vec<MultipartPart>,
+    get: bool,
+    head: bool,
+    urls: Vec<String>,
+    insecure: bool,
+    warnings: Vec<String>,
+}
+
+impl Parser {
+    fn apply(&mut self, flag: Flag, name: &str, value: Option<String>) {
+        let value = value.unwrap_or_default();
+        match flag {
+            Flag::Method => self.method = Some(value),
+            Flag::Header => self.header(&value),
+            Flag::Data(kind) => self.data(kind, value),
+            Flag::Json => {
+                self.default_header("Content-Type", "application/json");
+                self.default_header("Accept", "application/json");
+                self.data(DataKind::Binary, value);
+            }
+            Flag::Form { literal } => self.form(&value, literal),
+            Flag::User => {
+                if !value.contains(':') {
+                    self.warnings.push(format!(
+                        "`{}` has no password, curl would prompt for it, an empty one is used",
+                        name
+                    ));
+                }
+                let credentials = match value.contains(':') {
+                    true => value,
+                    false => format!("{}:", value),
+                };
+                self.headers.set(
+                    "Authorization",
+                    format!("Basic {}", STANDARD.encode(credentials)),
+                );
+            }
+            Flag::Bearer => self
+                .headers
+                .set("Authorization", format!("Bearer {}", value)),
+            Flag::Get => self.get = true,
+            Flag::Head => self.head = true,
+            Flag::Cookie if value.contains('=') => {
+                let cookie = match self.headers.get_ignore_case("cookie") {
+                    Some(existing) => format!("{}; {}", existing, value),
+                    None => value,
+                };
+                self.headers.remove_ignore_case("cookie");
+                self.headers.append("Cookie", cookie);
+            }
+            Flag::Cookie => self.warnings.push(format!(
+                "`{} {}` reads cookies from a file, skipped",
+                name, value
+            )),
+            Flag::UserAgent => self.headers.set("User-Agent", value),
+            Flag::Referer => self.headers.set("Referer", value),
+            Flag::Url => self.urls.push(value),
+            Flag::Insecure => self.insecure = true,
+            Flag::Compressed | Flag::Ignored { .. } => {}
+            Flag::Unsupported { value: true } => self
+                .warnings
+                .push(format!("unsupported flag `{} {}`, skipped", name, value)),
+            Flag::Unsupported { value: false } => self
+                .warnings
+                .push(format!("unsupported flag `{}`, skipped", name)),
+        }
+    }
+
+    /// `Name: value` adds a header, `Name:` removes one curl would send by
+    /// default and `Name;` sends it empty
+    fn header(&mut self, header: &str) {
+        if header.starts_with('@') {
+            self.warnings.push(format!(
+                "`-H {}` reads headers from a file, skipped",
+                header
+            ));
+            return;
+        }
+        match header.split_once(':') {
+            Some((name, _)) if name.trim().eq_ignore_ascii_case("accept-encoding") => {}
+            Some((name, value)) if value.trim().is_empty() => {
+                self.headers.remove_ignore_case(name.trim());
+            }
+            Some((name, value)) => self.headers.append(name.trim(), value.trim()),
+            None => match header.strip_suffix(';') {
+                Some(name) => self.headers.append(name.trim(), ""),
+                None => self
+                    .warnings
+                    .push(format!("malformed header `{}`, skipped", header)),
+            },
+        }
+    }
+
+    fn default_header(&mut self, name: &str, value: &str) {
+        if self.headers.get_ignore_case(name).is_none() {
+            self.headers.append(name, value);
+        }
+    }
+
+    fn data(&mut self, kind: DataKind, value: String) {
+        let file = value.strip_prefix('@').map(PathBuf::from);
+        let data = match (kind, file) {
+            (DataKind::Ascii, Some(path)) => {
+                self.warnings.push(format!(
+                    "`-d {}` is sent with its line breaks, curl would strip them",
+                    value
+                ));
+                Data::File(path)
+            }
+            (DataKind::Binary, Some(path)) => Data::File(path),
+            (DataKind::Urlencode, _) => match urlencode(&value) {
+                Some(data) => data,
+                None => {
+                    self.warnings.push(format!(
+                        "`--data-urlencode {}` reads a file, skipped",
+                        value
+                    ));
+                    return;
+                }
+            },
+            _ => Data::Text(value),
+        };
+        self.data.push(data);
+    }
+
+    /// `name=value`, `name=@path` attaches a file and `name=<path` inlines one.
+    /// `;type=` sets the part's content type, other attributes are ignored.
+    fn form(&mut self, field: &str, literal: bool) {
+        let Some((name, value)) = field.split_once('=') else {
+            self.warnings
+                .push(format!("malformed form field `{}`, skipped", field));
+            return;
+        };
+        let name = name.to_string();
+        if literal {
+            let value = value.to_string();
+            self.form.push(MultipartPart::Text { name, value });
+            return;
+        }
+        let mut attributes = value.split(';');
+        let value = attributes.next().unwrap_or_default();
+        let content_type = attributes
+            .filter_map(|attribute| attribute.trim().strip_prefix("type="))
+            .map(str::to_string)
+            .next();
+        if let Some(path) = value.strip_prefix('@') {
+            self.form.push(MultipartPart::File {
+                name,
+                path: PathBuf::from(path),
+                content_type,
+            });
+        } else if value.starts_with('<') {
+            self.warnings
+                .push(format!("form field `{}` inlines a file, skipped", field));
+        } else {
+            let value = value.to_string();
+            self.form.push(MultipartPart::Text { name, value });
+        }
+    }
+
+    fn finish(mut self) -> Result<Import, Error> {
+        if !self.data.is_empty() && !self.form.is_empty() {
+            return Err(Error::ConflictingBodies);
+        }
+        let mut url = match self.urls.len() {
+            0 => return Err(Error::MissingUrl),
+            1 => self.urls.remove(0),
+            _ => return Err(Error::SeveralUrls(self.urls)),
+        };
+        if !url.contains("://") {
+            url = format!("http://{}", url);
+        }
+
+        let method = match (&self.method, self.head, self.get) {
+            (Some(method), _, _) => method.clone(),
+            (None, true, _) => "HEAD".to_string(),
+            (None, false, true) => "GET".to_string(),
+            (None, false, false) if !self.data.is_empty() || !self.form.is_empty() => {
+                "POST".to_string()
+            }
+            (None, false, false) => "GET".to_string(),
+        };
+        let method: RequestMethod = method.parse().map_err(|_| Error::InvalidMethod(method))?;
+
+        let mut body = None;
+        let mut files = self.data.iter().filter_map(|data| match data {
+            Data::File(path) => Some(path),
+            Data::Text(_) => None,
+        });
+        if self.get {
+            // `-G` moves the data to the query string
+            if let Some(path) = files.next() {
+                self.warnings.push(format!(
+                    "`@{}` can't be read into the query string, skipped",
+                    path.display()
+                ));
+            }
+            let query = join(&self.data);
+            if !query.is_empty() {
+                let separator = if url.contains('?') { '&' } else { '?' };
+                url = format!("{}{}{}", url, separator, query);
+            }
+        } else if let [Data::File(path)] = self.data.as_slice() {
+            body = Some(RequestBody::Binary(BinaryBody::File(path.clone())));
+            // The header overrides the body's octet-stream type, as curl sends a form
+            self.default_header("Content-Type", "application/x-www-form-urlencoded");
+        } else if !self.data.is_empty() {
+            if let Some(path) = files.next() {
+                self.warnings.push(format!(
+                    "`@{}` can't be combined with other data, skipped",
+                    path.display()
+                ));
+            }
+            let content_type = self
+                .headers
+                .get_ignore_case("content-type")
+                .unwrap_or("application/x-www-form-urlencoded")
+                .to_string();
+            self.headers.remove_ignore_case("content-type");
+            body = Some(RequestBody::Raw {
+                content: join(&self.data),
+                content_type,
+            });
+        } else if !self.form.is_empty() {
+            body = Some(RequestBody::Multipart(self.form));
+        }
+
+        Ok(Import {
+            request: Request::new(body, self.headers, method, url, MultiMap::new())
+                .with_header_case(HeaderCase::Verbatim),
+            insecure: self.insecure,
+            warnings: self.warnings,
+        })
+    }
+}
+
+/// Text pieces joined with `&` as curl does
+fn join(data: &[Data]) -> String {
+    data.iter()
+        .filter_map(|data| match data {
+            Data::Text(text) => Some(text.as_str()),
+            Data::File(_) => None,
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// `content`, `=content` and `name=content` encode the content,
+/// `@file` and `name@file` read a file and are not supported
+fn urlencode(value: &str) -> Option<Data> {
+    let encode = |content: &str| {
+        url::form_urlencoded::byte_serialize(content.as_bytes())
+            .collect::<String>()
+            .replace('+', "%20")
+    };
+    let at = value.find('@');
+    match value.find('=') {
+        Some(eq) if at.is_none_or(|at| eq < at) => Some(Data::Text(match &value[..eq] {
+            "" => encode(&value[eq + 1..]),
+            name => format!("{}={}", name, encode(&value[eq + 1..])),
+        })),
+        _ if at.is_some() => None,
+        _ => Some(Data::Text(encode(value))),
+    }
+}
+
+/// Splits a shell command into words, the way bash would
+fn split(command: &str) -> Result<Vec<String>, Error> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = command.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => words.extend(word.take()),
+            '\\' => match chars.next() {
+                Some('\n') => {}
+                Some('\r') if chars.peek() == Some(&'\n') => {
+                    chars.next();
+                }
+                Some(escaped) => word.get_or_insert_with(String::new).push(escaped),
+                None => {}
+            },
+            '\'' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next().ok_or(Error::UnterminatedQuote)? {
+                        '\'' => break,
+                        c => word.push(c),
+                    }
+                }
+            }
+            '$' if chars.peek() == Some(&'\'') => {
+                chars.next();
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next().ok_or(Error::UnterminatedQuote)? {
+                        '\'' => break,
+                        '\\' => ansi_c_escape(&mut chars, word)?,
+                        c => word.push(c),
+                    }
+                }
+            }
+            '"' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next().ok_or(Error::UnterminatedQuote)? {
+                        '"' => break,
+                        '\\' => match chars.next().ok_or(Error::UnterminatedQuote)? {
+                            '\n' => {}
+                            c @ ('"' | '\\' | '$' | '`') => word.push(c),
+                            c => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                        },
+                        c => word.push(c),
+                    }
+                }
+            }
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// Decodes the escape following a `\` inside `$'...'`
+fn ansi_c_escape(chars: &mut Peekable<Chars<'_>>, word: &mut String) -> Result<(), Error> {
+    let escaped = match chars.next().ok_or(Error::UnterminatedQuote)? {
+        'n' => '\n',
+        't' => '\t',
+        'r' => '\r',
+        '0' => '\0',
+        'a' => '\x07',
+        'b' => '\x08',
+        'e' | 'E' => '\x1b',
+        'f' => '\x0c',
+        'v' => '\x0b',
+        c @ ('x' | 'u' | 'U') => {
+            let max = match c {
+                'x' => 2,
+                'u' => 4,
+                _ => 8,
+            };
+            match hex(chars, max) {
+                Some(decoded) => decoded,
+                None => {
+                    word.push('\\');
+                    c
+                }
+            }
+        }
+        c @ ('\\' | '\'' | '"' | '?') => c,
+        c => {
+            word.push('\\');
+            c
+        }
+    };
+    word.push(escaped);
+    Ok(())
+}
+
+/// Up to `max` hex digits as a character
+fn hex(chars: &mut Peekable<Chars<'_>>, max: usize) -> Option<char> {
+    let mut digits = String::new();
+    while digits.len() < max {
+        match chars.next_if(char::is_ascii_hexdigit) {
+            Some(c) => digits.push(c),
+            None => break,
+        }
+    }
+    u32::from_str_radix(&digits, 16)
+        .ok()
+        .and_then(char::from_u32)
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotCurl => write!(f, "not a curl command"),
+            Error::UnterminatedQuote => write!(f, "unterminated quote in curl command"),
+            Error::MissingValue(flag) => write!(f, "missing value for `{}`", flag),
+            Error::MissingUrl => write!(f, "no url in curl command"),
+            Error::SeveralUrls(urls) => write!(
+                f,
+                "several urls in curl command: `{}`, an unsupported flag may have taken a value",
+                urls.join("`, `")
+            ),
+            Error::InvalidMethod(method) => write!(f, "invalid method `{}`", method),
+            Error::ConflictingBodies => write!(f, "cannot combine `-d` and `-F` data"),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, split, Error};
+    use crate::{
+        body::{BinaryBody, MultipartPart, RequestBody},
+        multimap::MultiMap,
+        request::RequestMethod,
+    };
+    use std::path::PathBuf;
+
+    #[test]
+    fn split_like_a_shell() {
+        let words = split(
+            "curl 'https://x.io/a b' \\\n  -H \"X-Q: \\\"q\\\" \\n\" \\\r\n --data-raw $'{\"a\":\"\\u00e9\\n\\'\"}' plain\\ word",
+        )
+        .unwrap();
+        assert_eq!(
+            vec![
+                "curl",
+                "https://x.io/a b",
+                "-H",
+                "X-Q: \"q\" \\n",
+                "--data-raw",
+                "{\"a\":\"é\n'\"}",
+                "plain word"
+            ],
+            words
+        );
+        assert_eq!(Err(Error::UnterminatedQuote), split("curl 'x"));
+        assert_eq!(Err(Error::UnterminatedQuote), split("curl $'x\\'"));
+    }
+
+    #[test]
+    fn parse_browser_copy() {
+        let import = parse(
+            r#"curl 'https://api.example.com/orders?page=2' \
+  -H 'accept: application/json' \
+  -H 'content-type: application/json' \
+  -b 'sid=abc; theme=dark' \
+  -H 'sec-ch-ua-mobile: ?0' \
+  -H 'Accept-Encoding: gzip, deflate, br' \
+  --data-raw $'{"note":"it\'s"}' \
+  --compressed"#,
+        )
+        .unwrap();
+        let request = import.request;
+        assert_eq!(&RequestMethod::POST, request.method());
+        assert_eq!("https://api.example.com/orders?page=2", request.url());
+        assert_eq!(
+            &MultiMap::from([
+                ("accept", "application/json"),
+                ("Cookie", "sid=abc; theme=dark"),
+                ("sec-ch-ua-mobile", "?0"),
+            ]),
+            request.headers()
+        );
+        assert_eq!(
+            Some(&RequestBody::Raw {
+                content: r#"{"note":"it's"}"#.to_string(),
+                content_type: "application/json".to_string(),
+            }),
+            request.body()
+        );
+        assert!(import.warnings.is_empty());
+        assert!(!import.insecure);
+    }
+
+    #[test]
+    fn parse_data_flags() {
+        let import = parse(
+            "curl -sSk -XPUT example.com/f -d a=1 --data b=2 --data-urlencode 'q=a b&c' \
+             --data-urlencode '=x+y' -u john:secret",
+        )
+        .unwrap();
+        assert_eq!(&RequestMethod::PUT, import.request.method());
+        assert_eq!("http://example.com/f", import.request.url());
+        assert_eq!(
+            Some(&RequestBody::Raw {
+                content: "a=1&b=2&q=a%20b%26c&x%2By".to_string(),
+                content_type: "application/x-www-form-urlencoded".to_string(),
+            }),
+            import.request.body()
+        );
+        assert_eq!(
+            Some("Basic am9objpzZWNyZXQ="),
+            import.request.headers().get("Authorization")
+        );
+        assert!(import.insecure);
+
+        let get = parse("curl -G https://x.io/search?lang=en -d q=rust -d page=2").unwrap();
+        assert_eq!(&RequestMethod::GET, get.request.method());
+        assert_eq!(
+            "https://x.io/search?lang=en&q=rust&page=2",
+            get.request.url()
+        );
+        assert_eq!(None, get.request.body());
+
+        let file = parse("curl https://x.io --data-binary @dump.bin").unwrap();
+        assert_eq!(
+            Some(&RequestBody::Binary(BinaryBody::File(PathBuf::from(
+                "dump.bin"
+            )))),
+            file.request.body()
+        );
+        assert_eq!(
+            &MultiMap::from([("Content-Type", "application/x-www-form-urlencoded")]),
+            file.request.headers()
+        );
+        let typed = parse("curl https://x.io -H 'content-type: image/png' -d @cat.png").unwrap();
+        assert_eq!(
+            &MultiMap::from([("content-type", "image/png")]),
+            typed.request.headers()
+        );
+    }
+
+    #[test]
+    fn parse_multipart() {
+        let import = parse(
+            "curl https://x.io/upload -F name=report -F 'file=@out/report.pdf;type=application/pdf'",
+        )
+        .unwrap();
+        assert_eq!(&RequestMethod::POST, import.request.method());
+        assert_eq!(
+            Some(&RequestBody::Multipart(vec![
+                MultipartPart::Text {
+                    name: "name".to_string(),
+                    value: "report".to_string(),
+                },
+                MultipartPart::File {
+                    name: "file".to_string(),
+                    path: PathBuf::from("out/report.pdf"),
+                    content_type: Some("application/pdf".to_string()),
+                },
+            ])),
+            import.request.body()
+        );
+        assert_eq!(
+            Err(Error::ConflictingBodies),
+            parse("curl x.io -F a=1 -d b=2")
+        );
+    }
+
+    #[test]
+    fn warn_about_unsupported_flags() {
+        let import = parse(
+            "curl --http2 -m 5 -o out.json --proxy http://p:3128 --socks5 h:1080 --noproxy '*' \
+             -b cookies.txt -L https://x.io --oauth2-bearer t0k",
+        )
+        .unwrap();
+        assert_eq!(&RequestMethod::GET, import.request.method());
+        assert_eq!("https://x.io", import.request.url());
+        assert_eq!(
+            Some("Bearer t0k"),
+            import.request.headers().get("Authorization")
+        );
+        assert_eq!(
+            vec![
+                "unsupported flag `--http2`, skipped",
+                "unsupported flag `-m 5`, skipped",
+                "unsupported flag `--proxy http://p:3128`, skipped",
+                "unsupported flag `--socks5 h:1080`, skipped",
+                "unsupported flag `--noproxy *`, skipped",
+                "`-b cookies.txt` reads cookies from a file, skipped",
+            ],
+            import.warnings
+        );
+
+        assert_eq!(Err(Error::NotCurl), parse("wget https://x.io"));
+        assert_eq!(Err(Error::MissingUrl), parse("curl -s"));
+        assert_eq!(
+            Err(Error::SeveralUrls(vec![
+                "https://x.io".to_string(),
+                "https://y.io".to_string()
+            ])),
+            parse("curl https://x.io https://y.io")
+        );
+        assert_eq!(
+            Err(Error::MissingValue("-H".to_string())),
+            parse("curl x.io -H")
+        );
+    }
+}
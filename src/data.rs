@@ -0,0 +1,133 @@
+use crate::variables::VariableMap;
+use serde_json::Value;
+use std::{
+    error, fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Rows of a data file, each one drives an iteration of a collection run
+/// with its columns exposed as variables
+pub type Rows = Vec<VariableMap>;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Csv(csv::Error),
+    Json(serde_json::Error),
+    NotARow(usize), // A JSON array element that isn't an object
+    NotAnArray,
+    UnsupportedFormat(PathBuf),
+}
+
+/// Reads a .csv file with a header line, or a .json array of objects
+pub fn load(path: impl AsRef<Path>) -> Result<Rows, Error> {
+    let path = path.as_ref();
+    let content = fs::read_to_string(path).map_err(Error::Io)?;
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("csv") => parse_csv(&content),
+        Some("json") => parse_json(&content),
+        _ => Err(Error::UnsupportedFormat(path.to_path_buf())),
+    }
+}
+
+/// The header line names the variables, every following line is a row
+pub fn parse_csv(content: &str) -> Result<Rows, Error> {
+    let mut reader = csv::Reader::from_reader(content.as_bytes());
+    let headers = reader.headers().map_err(Error::Csv)?.clone();
+    reader
+        .records()
+        .map(|record| {
+            let record = record.map_err(Error::Csv)?;
+            Ok(headers
+                .iter()
+                .zip(record.iter())
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect())
+        })
+        .collect()
+}
+
+/// An array of objects, strings are taken as-is and other values as JSON text
+pub fn parse_json(content: &str) -> Result<Rows, Error> {
+    let value: Value = serde_json::from_str(content).map_err(Error::Json)?;
+    let Value::Array(items) = value else {
+        return Err(Error::NotAnArray);
+    };
+    items
+        .into_iter()
+        .enumerate()
+        .map(|(index, item)| match item {
+            Value::Object(map) => Ok(map
+                .into_iter()
+                .map(|(name, value)| match value {
+                    Value::String(s) => (name, s),
+                    other => (name, other.to_string()),
+                })
+                .collect()),
+            _ => Err(Error::NotARow(index)),
+        })
+        .collect()
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(_) => write!(f, "could not read data file"),
+            Error::Csv(_) | Error::Json(_) => write!(f, "invalid data file"),
+            Error::NotARow(index) => write!(f, "data row {} is not an object", index),
+            Error::NotAnArray => write!(f, "JSON data file must be an array of objects"),
+            Error::UnsupportedFormat(path) => write!(
+                f,
+                "unsupported data format `{}`, expected .csv or .json",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Csv(e) => Some(e),
+            Error::Json(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_csv, parse_json, Error};
+
+    #[test]
+    fn parse_csv_rows() {
+        let rows = parse_csv("name,email\njohn,john@example.com\n\"Doe, Jane\",jane@example.com\n")
+            .unwrap();
+        assert_eq!(2, rows.len());
+        assert_eq!("john", rows[0]["name"]);
+        assert_eq!("Doe, Jane", rows[1]["name"]);
+        assert_eq!("jane@example.com", rows[1]["email"]);
+
+        assert!(matches!(
+            parse_csv("name,email\njohn\n"),
+            Err(Error::Csv(_))
+        ));
+    }
+
+    #[test]
+    fn parse_json_rows() {
+        let rows = parse_json(r#"[{"name": "john", "age": 42, "admin": true}, {"name": "jane"}]"#)
+            .unwrap();
+        assert_eq!("john", rows[0]["name"]);
+        assert_eq!("42", rows[0]["age"]);
+        assert_eq!("true", rows[0]["admin"]);
+        assert_eq!(1, rows[1].len());
+
+        assert!(matches!(
+            parse_json(r#"[{"a": 1}, 2]"#),
+            Err(Error::NotARow(1))
+        ));
+        assert!(matches!(parse_json(r#"{"a": 1}"#), Err(Error::NotAnArray)));
+    }
+}
@@ -0,0 +1,223 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, SecondsFormat, Utc};
+use rand::{distributions::Alphanumeric, rngs::StdRng, Rng, SeedableRng};
+use std::{error, fmt, time::SystemTime};
+use uuid::Builder;
+
+const DEFAULT_INT_RANGE: (i64, i64) = (0, 1000);
+const DEFAULT_STRING_LEN: usize = 10;
+const MAX_STRING_LEN: usize = 1024 * 1024;
+
+/// Produces the values of built-in `{{$name}}` variables.
+///
+/// Every reference gets a fresh value. Seeding makes the random ones
+/// reproducible, pinning the clock does the same for timestamps.
+#[derive(Debug, Clone)]
+pub struct Generator {
+    rng: StdRng,
+    clock: Option<SystemTime>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    Unknown(String),
+    InvalidArguments { name: String, reason: String },
+}
+
+impl Generator {
+    pub fn new() -> Generator {
+        Generator {
+            rng: StdRng::from_entropy(),
+            clock: None,
+        }
+    }
+
+    /// Same seed, same sequence of values
+    pub fn seeded(seed: u64) -> Generator {
+        Generator {
+            rng: StdRng::seed_from_u64(seed),
+            clock: None,
+        }
+    }
+
+    /// Pins `$timestamp` and `$isoTimestamp` to `time`
+    pub fn with_clock(mut self, time: SystemTime) -> Generator {
+        self.clock = Some(time);
+        self
+    }
+
+    /// Value of `expr`, the part after `$`, e.g. `uuid` or `randomInt(1,6)`
+    pub fn generate(&mut self, expr: &str) -> Result<String, Error> {
+        let (name, args) = match expr.split_once('(') {
+            Some((name, rest)) => match rest.strip_suffix(')') {
+                Some(args) => (name.trim(), Some(args)),
+                None => return Err(invalid(name.trim(), "missing closing parenthesis")),
+            },
+            None => (expr.trim(), None),
+        };
+
+        match (name, args) {
+            ("uuid", None) => Ok(Builder::from_random_bytes(self.rng.gen())
+                .into_uuid()
+                .to_string()),
+            ("timestamp", None) => Ok(self.now().timestamp().to_string()),
+            ("isoTimestamp", None) => Ok(self.now().to_rfc3339_opts(SecondsFormat::Millis, true)),
+            ("randomInt", args) => {
+                let (min, max) = match args {
+                    Some(args) => int_range(args).map_err(|reason| invalid(name, reason))?,
+                    None => DEFAULT_INT_RANGE,
+                };
+                Ok(self.rng.gen_range(min..=max).to_string())
+            }
+            ("randomEmail", None) => Ok(format!(
+                "{}@example.com",
+                self.alphanumeric(10).to_ascii_lowercase()
+            )),
+            ("randomString", args) => {
+                let len = match args {
+                    Some(args) => args
+                        .trim()
+                        .parse()
+                        .map_err(|_| invalid(name, "expected a length"))?,
+                    None => DEFAULT_STRING_LEN,
+                };
+                if len > MAX_STRING_LEN {
+                    let reason = format!("length is limited to {}", MAX_STRING_LEN);
+                    return Err(invalid(name, &reason));
+                }
+                Ok(self.alphanumeric(len))
+            }
+            ("base64", Some(text)) => Ok(STANDARD.encode(text)),
+            ("base64", None) => Err(invalid(name, "expected the text to encode")),
+            ("uuid" | "timestamp" | "isoTimestamp" | "randomEmail", Some(_)) => {
+                Err(invalid(name, "takes no arguments"))
+            }
+            _ => Err(Error::Unknown(name.to_string())),
+        }
+    }
+
+    fn now(&self) -> DateTime<Utc> {
+        self.clock.unwrap_or_else(SystemTime::now).into()
+    }
+
+    fn alphanumeric(&mut self, len: usize) -> String {
+        (&mut self.rng)
+            .sample_iter(Alphanumeric)
+            .take(len)
+            .map(char::from)
+            .collect()
+    }
+}
+
+impl Default for Generator {
+    fn default() -> Generator {
+        Generator::new()
+    }
+}
+
+/// `min,max`, both inclusive
+fn int_range(args: &str) -> Result<(i64, i64), &'static str> {
+    let (min, max) = args.split_once(',').ok_or("expected `min,max`")?;
+    let min: i64 = min.trim().parse().map_err(|_| "expected `min,max`")?;
+    let max: i64 = max.trim().parse().map_err(|_| "expected `min,max`")?;
+    if min > max {
+        return Err("min is greater than max");
+    }
+    Ok((min, max))
+}
+
+fn invalid(name: &str, reason: &str) -> Error {
+    Error::InvalidArguments {
+        name: name.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Unknown(name) => write!(f, "unknown dynamic variable `${}`", name),
+            Error::InvalidArguments { name, reason } => {
+                write!(f, "invalid arguments to `${}`: {}", name, reason)
+            }
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, Generator};
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn same_seed_same_values() {
+        let mut a = Generator::seeded(7);
+        let mut b = Generator::seeded(7);
+        for expr in ["uuid", "randomInt(1,6)", "randomEmail", "randomString(16)"] {
+            assert_eq!(a.generate(expr), b.generate(expr));
+        }
+        assert_ne!(a.generate("uuid"), a.generate("uuid"));
+    }
+
+    #[test]
+    fn generate_well_formed_values() {
+        let mut gen = Generator::seeded(1);
+        let uuid = gen.generate("uuid").unwrap();
+        assert_eq!(36, uuid.len());
+        assert_eq!(Some('4'), uuid.chars().nth(14));
+
+        for _ in 0..100 {
+            let n: i64 = gen.generate("randomInt( -2, 2 )").unwrap().parse().unwrap();
+            assert!((-2..=2).contains(&n));
+        }
+        let n: i64 = gen.generate("randomInt").unwrap().parse().unwrap();
+        assert!((0..=1000).contains(&n));
+
+        let s = gen.generate("randomString(24)").unwrap();
+        assert_eq!(24, s.len());
+        assert!(s.chars().all(|c| c.is_ascii_alphanumeric()));
+        assert!(gen
+            .generate("randomEmail")
+            .unwrap()
+            .ends_with("@example.com"));
+        assert_eq!("dXNlcjpwYXNz", gen.generate("base64(user:pass)").unwrap());
+    }
+
+    #[test]
+    fn pinned_clock() {
+        let time = SystemTime::UNIX_EPOCH + Duration::from_millis(1_700_000_000_123);
+        let mut gen = Generator::new().with_clock(time);
+        assert_eq!("1700000000", gen.generate("timestamp").unwrap());
+        assert_eq!(
+            "2023-11-14T22:13:20.123Z",
+            gen.generate("isoTimestamp").unwrap()
+        );
+    }
+
+    #[test]
+    fn reject_unknown_names_and_bad_arguments() {
+        let mut gen = Generator::seeded(1);
+        assert_eq!(
+            Err(Error::Unknown("guid".to_string())),
+            gen.generate("guid")
+        );
+        assert!(matches!(
+            gen.generate("randomInt(5,1)"),
+            Err(Error::InvalidArguments { .. })
+        ));
+        assert!(matches!(
+            gen.generate("randomString(ten)"),
+            Err(Error::InvalidArguments { .. })
+        ));
+        assert!(matches!(
+            gen.generate("randomString(100000000000)"),
+            Err(Error::InvalidArguments { .. })
+        ));
+        assert!(matches!(
+            gen.generate("uuid(4)"),
+            Err(Error::InvalidArguments { .. })
+        ));
+    }
+}
@@ -0,0 +1,428 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use hyper::{
+    header::{self, HeaderValue},
+    service::{make_service_fn, service_fn},
+    Body, Request, Response, Server, StatusCode,
+};
+use serde_json::{json, Map, Value};
+use std::{convert::Infallible, net::SocketAddr, time::Duration};
+use tokio::sync::oneshot;
+
+const MAX_DELAY_SECS: f64 = 10.0;
+const MAX_BYTES: usize = 100 * 1024 * 1024;
+
+/// A local HTTP server reflecting requests as JSON, in the shape postman-echo uses.
+///
+/// Besides echoing on any other path, it serves:
+/// - `/status/{code}` answers with that status
+/// - `/delay/{seconds}` echoes after waiting, at most 10 seconds
+/// - `/redirect/{n}` redirects `n` times before landing on `/get`
+/// - `/redirect-to?url=...&status_code=...` redirects once to `url`
+/// - `/bytes/{n}` answers with `n` bytes of binary data
+/// - `/html` answers with a small HTML page
+/// - `/response-headers?name=value` sets the query pairs as response headers
+/// - `/cookies` lists the request cookies, `/cookies/set?name=value` sets them
+///
+/// The server stops when dropped.
+#[derive(Debug)]
+pub struct EchoServer {
+    addr: SocketAddr,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl EchoServer {
+    /// Starts a server on a free local port, must be called within a tokio runtime
+    pub fn start() -> Result<EchoServer, hyper::Error> {
+        EchoServer::bind(SocketAddr::from(([127, 0, 0, 1], 0)))
+    }
+
+    pub fn bind(addr: SocketAddr) -> Result<EchoServer, hyper::Error> {
+        let make_service = make_service_fn(|_| async {
+            Ok::<_, Infallible>(service_fn(|req| async {
+                Ok::<_, Infallible>(handle(req).await)
+            }))
+        });
+        let server = Server::try_bind(&addr)?.serve(make_service);
+        let addr = server.local_addr();
+        let (shutdown, stopped) = oneshot::channel();
+        tokio::spawn(server.with_graceful_shutdown(async {
+            stopped.await.ok();
+        }));
+
+        Ok(EchoServer {
+            addr,
+            shutdown: Some(shutdown),
+        })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Absolute url of `path` on this server, e.g. `url("/get")`
+    pub fn url(&self, path: &str) -> String {
+        format!("http://{}{}", self.addr, path)
+    }
+}
+
+impl Drop for EchoServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            shutdown.send(()).ok();
+        }
+    }
+}
+
+async fn handle(req: Request<Body>) -> Response<Body> {
+    let path = req.uri().path().to_string();
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let args = query_pairs(&req);
+
+    match segments.as_slice() {
+        ["status", code] => match code
+            .parse::<u16>()
+            .ok()
+            .and_then(|c| StatusCode::from_u16(c).ok())
+        {
+            // Statuses without a payload must not get one
+            Some(status)
+                if status == StatusCode::NO_CONTENT || status == StatusCode::NOT_MODIFIED =>
+            {
+                with_status(status, Body::empty())
+            }
+            Some(status) => json_response(status, json!({ "status": status.as_u16() })),
+            None => bad_request("invalid status code"),
+        },
+        ["delay", seconds] => match seconds.parse::<f64>() {
+            Ok(seconds) if seconds >= 0.0 => {
+                tokio::time::sleep(Duration::from_secs_f64(seconds.min(MAX_DELAY_SECS))).await;
+                json_response(StatusCode::OK, echo(req, args).await)
+            }
+            _ => bad_request("invalid delay"),
+        },
+        ["redirect", n] => match n.parse::<u32>() {
+            Ok(0) | Ok(1) => redirect(StatusCode::FOUND, "/get"),
+            Ok(n) => redirect(StatusCode::FOUND, &format!("/redirect/{}", n - 1)),
+            Err(_) => bad_request("invalid redirect count"),
+        },
+        ["redirect-to"] => {
+            let status = args
+                .iter()
+                .find(|(k, _)| k == "status_code")
+                .and_then(|(_, v)| v.parse::<u16>().ok())
+                .and_then(|c| StatusCode::from_u16(c).ok())
+                .filter(StatusCode::is_redirection)
+                .unwrap_or(StatusCode::FOUND);
+            match args.iter().find(|(k, _)| k == "url") {
+                Some((_, url)) => redirect(status, url),
+                None => bad_request("missing url"),
+            }
+        }
+        ["bytes", n] => match n.parse::<usize>() {
+            Ok(n) if n <= MAX_BYTES => {
+                let bytes: Vec<u8> = (0..n).map(|i| (i % 256) as u8).collect();
+                let mut response = with_status(StatusCode::OK, Body::from(bytes));
+                response.headers_mut().insert(
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_static("application/octet-stream"),
+                );
+                response
+            }
+            _ => bad_request("invalid byte count"),
+        },
+        ["html"] => {
+            let mut response = with_status(
+                StatusCode::OK,
+                Body::from("<!DOCTYPE html><html><body><h1>asterios</h1></body></html>"),
+            );
+            response.headers_mut().insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("text/html; charset=utf-8"),
+            );
+            response
+        }
+        ["response-headers"] => {
+            let mut response = json_response(StatusCode::OK, multi_value(&args));
+            for (name, value) in &args {
+                if let (Ok(name), Ok(value)) = (
+                    header::HeaderName::from_bytes(name.as_bytes()),
+                    HeaderValue::from_str(value),
+                ) {
+                    response.headers_mut().append(name, value);
+                }
+            }
+            response
+        }
+        ["cookies"] => json_response(
+            StatusCode::OK,
+            json!({ "cookies": cookie_map(req.headers()) }),
+        ),
+        ["cookies", "set"] => {
+            let mut response = redirect(StatusCode::FOUND, "/cookies");
+            for (name, value) in &args {
+                if let Ok(cookie) = HeaderValue::from_str(&format!("{}={}; Path=/", name, value)) {
+                    response.headers_mut().append(header::SET_COOKIE, cookie);
+                }
+            }
+            response
+        }
+        _ => json_response(StatusCode::OK, echo(req, args).await),
+    }
+}
+
+/// The postman-echo payload: args, data, files, form, headers, json and url,
+/// plus the method, path and cookies
+async fn echo(req: Request<Body>, args: Vec<(String, String)>) -> Value {
+    let (parts, body) = req.into_parts();
+    let body = hyper::body::to_bytes(body).await.unwrap_or_default();
+    let content_type = parts
+        .headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+
+    let mut data = Value::String(String::new());
+    let mut form = Vec::new();
+    let mut files = Map::new();
+    let mut json = Value::Null;
+    if mime == "application/x-www-form-urlencoded" {
+        form = url::form_urlencoded::parse(&body).into_owned().collect();
+    } else if mime == "multipart/form-data" {
+        let boundary = content_type
+            .split(';')
+            .filter_map(|param| param.trim().strip_prefix("boundary="))
+            .next()
+            .unwrap_or_default()
+            .trim_matches('"');
+        for part in multipart_parts(&body, boundary) {
+            match part.filename {
+                Some(filename) => {
+                    files.insert(
+                        filename,
+                        Value::String(data_uri(&part.content_type, part.body)),
+                    );
+                }
+                None => form.push((part.name, String::from_utf8_lossy(part.body).into_owned())),
+            }
+        }
+    } else if !body.is_empty() {
+        json = serde_json::from_slice(&body).unwrap_or(Value::Null);
+        data = match (&json, std::str::from_utf8(&body)) {
+            (Value::Null, Ok(text)) => Value::String(text.to_string()),
+            (Value::Null, Err(_)) => Value::String(data_uri(&mime, &body)),
+            (json, _) => json.clone(),
+        };
+    }
+
+    let headers: Vec<(String, String)> = parts
+        .headers
+        .iter()
+        .map(|(k, v)| {
+            (
+                k.to_string(),
+                String::from_utf8_lossy(v.as_bytes()).into_owned(),
+            )
+        })
+        .collect();
+    let host = parts
+        .headers
+        .get(header::HOST)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("localhost");
+
+    json!({
+        "args": multi_value(&args),
+        "data": data,
+        "files": files,
+        "form": multi_value(&form),
+        "headers": multi_value(&headers),
+        "json": json,
+        "url": format!("http://{}{}", host, parts.uri),
+        "method": parts.method.as_str(),
+        "path": parts.uri.path(),
+        "cookies": cookie_map(&parts.headers),
+    })
+}
+
+fn query_pairs(req: &Request<Body>) -> Vec<(String, String)> {
+    url::form_urlencoded::parse(req.uri().query().unwrap_or_default().as_bytes())
+        .into_owned()
+        .collect()
+}
+
+/// A key seen once maps to a string, a repeated key to an array of strings
+fn multi_value(pairs: &[(String, String)]) -> Value {
+    let mut map = Map::new();
+    for (key, value) in pairs {
+        match map.get_mut(key) {
+            Some(Value::Array(values)) => values.push(Value::String(value.clone())),
+            Some(existing) => {
+                let first = existing.take();
+                *existing = Value::Array(vec![first, Value::String(value.clone())]);
+            }
+            None => {
+                map.insert(key.clone(), Value::String(value.clone()));
+            }
+        }
+    }
+    Value::Object(map)
+}
+
+fn cookie_map(headers: &hyper::HeaderMap) -> Value {
+    let pairs: Vec<(String, String)> = headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .filter_map(|cookie| {
+            let (name, value) = cookie.trim().split_once('=')?;
+            Some((name.to_string(), value.to_string()))
+        })
+        .collect();
+    multi_value(&pairs)
+}
+
+fn data_uri(content_type: &str, bytes: &[u8]) -> String {
+    let content_type = match content_type {
+        "" => "application/octet-stream",
+        content_type => content_type,
+    };
+    format!("data:{};base64,{}", content_type, STANDARD.encode(bytes))
+}
+
+struct MultipartPart<'a> {
+    name: String,
+    filename: Option<String>,
+    content_type: String,
+    body: &'a [u8],
+}
+
+/// Minimal multipart/form-data parser, good enough for echoing test payloads
+fn multipart_parts<'a>(body: &'a [u8], boundary: &str) -> Vec<MultipartPart<'a>> {
+    if boundary.is_empty() {
+        return Vec::new();
+    }
+    let delimiter = format!("--{}", boundary);
+    split_bytes(body, delimiter.as_bytes())
+        .into_iter()
+        .skip(1) // Preamble
+        .filter(|part| !part.starts_with(b"--")) // Closing delimiter
+        .filter_map(|part| {
+            let part = part.strip_prefix(b"\r\n").unwrap_or(part);
+            let part = part.strip_suffix(b"\r\n").unwrap_or(part);
+            let split = part.windows(4).position(|w| w == b"\r\n\r\n")?;
+            let (head, body) = (&part[..split], &part[split + 4..]);
+            let head = String::from_utf8_lossy(head);
+            let mut name = None;
+            let mut filename = None;
+            let mut content_type = String::new();
+            for line in head.lines() {
+                let (key, value) = match line.split_once(':') {
+                    Some(header) => header,
+                    None => continue,
+                };
+                if key.trim().eq_ignore_ascii_case("content-disposition") {
+                    for param in value.split(';').skip(1) {
+                        match param.trim().split_once('=') {
+                            Some(("name", v)) => name = Some(v.trim_matches('"').to_string()),
+                            Some(("filename", v)) => {
+                                filename = Some(v.trim_matches('"').to_string())
+                            }
+                            _ => {}
+                        }
+                    }
+                } else if key.trim().eq_ignore_ascii_case("content-type") {
+                    content_type = value.trim().to_string();
+                }
+            }
+            Some(MultipartPart {
+                name: name?,
+                filename,
+                content_type,
+                body,
+            })
+        })
+        .collect()
+}
+
+fn split_bytes<'a>(haystack: &'a [u8], needle: &[u8]) -> Vec<&'a [u8]> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+    while i + needle.len() <= haystack.len() {
+        if &haystack[i..i + needle.len()] == needle {
+            parts.push(&haystack[start..i]);
+            i += needle.len();
+            start = i;
+        } else {
+            i += 1;
+        }
+    }
+    parts.push(&haystack[start..]);
+    parts
+}
+
+fn with_status(status: StatusCode, body: Body) -> Response<Body> {
+    let mut response = Response::new(body);
+    *response.status_mut() = status;
+    response
+}
+
+fn json_response(status: StatusCode, value: Value) -> Response<Body> {
+    let mut response = with_status(status, Body::from(value.to_string()));
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json; charset=utf-8"),
+    );
+    response
+}
+
+fn redirect(status: StatusCode, location: &str) -> Response<Body> {
+    let mut response = with_status(status, Body::empty());
+    if let Ok(location) = HeaderValue::from_str(location) {
+        response.headers_mut().insert(header::LOCATION, location);
+    }
+    response
+}
+
+fn bad_request(message: &str) -> Response<Body> {
+    json_response(StatusCode::BAD_REQUEST, json!({ "error": message }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{multipart_parts, split_bytes, EchoServer};
+
+    #[test]
+    fn split_on_delimiter() {
+        assert_eq!(vec![&b"a"[..], b"b", b""], split_bytes(b"a--b--", b"--"));
+    }
+
+    #[test]
+    fn parse_multipart_parts() {
+        let body = b"--xyz\r\nContent-Disposition: form-data; name=\"name\"\r\n\r\njohn\r\n\
+--xyz\r\nContent-Disposition: form-data; name=\"file\"; filename=\"a.txt\"\r\n\
+Content-Type: text/plain\r\n\r\nhello\r\n--xyz--\r\n";
+        let parts = multipart_parts(body, "xyz");
+        assert_eq!(2, parts.len());
+        assert_eq!("name", parts[0].name);
+        assert_eq!(b"john", parts[0].body);
+        assert_eq!(Some("a.txt".to_string()), parts[1].filename);
+        assert_eq!("text/plain", parts[1].content_type);
+        assert_eq!(b"hello", parts[1].body);
+    }
+
+    #[tokio::test]
+    async fn serve_on_a_free_port() {
+        let server = EchoServer::start().unwrap();
+        assert_ne!(0, server.addr().port());
+        assert_eq!(format!("http://{}/get", server.addr()), server.url("/get"));
+    }
+}
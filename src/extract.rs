@@ -0,0 +1,249 @@
+use crate::{
+    jsonpath::{self, JsonPath},
+    request::Response,
+    variables::Variables,
+};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{error, fmt};
+
+/// A rule saving part of a response into a variable, so later requests can use it,
+/// e.g. `{ variable = "token", from = { json_path = "$.token" } }`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Extraction {
+    pub variable: String,
+    pub from: Source,
+    #[serde(default)]
+    pub scope: Target,
+}
+
+/// Where the value comes from
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Source {
+    JsonPath(String),
+    JsonPointer(String),
+    Header(String),
+    /// Over the body text, the first capture group if there is one, else the whole match
+    Regex(String),
+    Cookie(String),
+}
+
+/// Where the value is saved
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Target {
+    /// Lives until the end of the run
+    #[default]
+    Runtime,
+    /// The active environment, or the runtime variables without one
+    Environment,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    NoMatch { variable: String, source: Source },
+    InvalidJsonPath(jsonpath::Error),
+    InvalidRegex(regex::Error),
+}
+
+impl Extraction {
+    pub fn new(variable: impl Into<String>, from: Source) -> Extraction {
+        Extraction {
+            variable: variable.into(),
+            from,
+            scope: Target::Runtime,
+        }
+    }
+
+    pub fn with_scope(mut self, scope: Target) -> Extraction {
+        self.scope = scope;
+        self
+    }
+
+    /// Value picked from `res`. JSON strings are taken as-is, other JSON values as JSON text.
+    pub fn extract(&self, res: &Response) -> Result<String, Error> {
+        let value = match &self.from {
+            Source::JsonPath(path) => {
+                let path = JsonPath::parse(path).map_err(Error::InvalidJsonPath)?;
+                res.body()
+                    .json()
+                    .and_then(|json| path.first(json))
+                    .map(text)
+            }
+            Source::JsonPointer(pointer) => res
+                .body()
+                .json()
+                .and_then(|json| json.pointer(pointer))
+                .map(text),
+            Source::Header(name) => res.headers().get_ignore_case(name).map(str::to_string),
+            Source::Regex(pattern) => {
+                let regex = Regex::new(pattern).map_err(Error::InvalidRegex)?;
+                let body = res.body().text();
+                regex.captures(&body).and_then(|captures| {
+                    captures
+                        .get(1)
+                        .or_else(|| captures.get(0))
+                        .map(|m| m.as_str().to_string())
+                })
+            }
+            Source::Cookie(name) => cookie(res, name),
+        };
+        value.ok_or_else(|| Error::NoMatch {
+            variable: self.variable.clone(),
+            source: self.from.clone(),
+        })
+    }
+}
+
+/// Runs every rule against `res` and saves the values, stopping at the first failure
+pub fn apply(rules: &[Extraction], res: &Response, variables: &mut Variables) -> Result<(), Error> {
+    for rule in rules {
+        let value = rule.extract(res)?;
+        let target = match (rule.scope, &mut variables.environment) {
+            (Target::Environment, Some(environment)) => &mut environment.variables,
+            _ => &mut variables.runtime,
+        };
+        target.insert(rule.variable.clone(), value);
+    }
+    Ok(())
+}
+
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Value of cookie `name` from the `Set-Cookie` headers
+fn cookie(res: &Response, name: &str) -> Option<String> {
+    res.headers()
+        .iter()
+        .filter(|(header, _)| header.eq_ignore_ascii_case("set-cookie"))
+        .filter_map(|(_, value)| value.split(';').next()?.split_once('='))
+        .find(|(cookie, _)| cookie.trim() == name)
+        .map(|(_, value)| value.trim().trim_matches('"').to_string())
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::JsonPath(path) => write!(f, "JSONPath `{}`", path),
+            Source::JsonPointer(pointer) => write!(f, "JSON pointer `{}`", pointer),
+            Source::Header(name) => write!(f, "header `{}`", name),
+            Source::Regex(pattern) => write!(f, "regex `{}`", pattern),
+            Source::Cookie(name) => write!(f, "cookie `{}`", name),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NoMatch { variable, source } => {
+                write!(f, "nothing to extract into `{}` from {}", variable, source)
+            }
+            Error::InvalidJsonPath(e) => e.fmt(f),
+            Error::InvalidRegex(_) => write!(f, "invalid extraction regex"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::InvalidRegex(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply, Error, Extraction, Source, Target};
+    use crate::{
+        echo::EchoServer,
+        multimap::MultiMap,
+        request::{Request, RequestMethod, Response},
+        variables::{Environment, Variables},
+    };
+
+    async fn login(server: &EchoServer) -> Response {
+        Request::new(
+            None,
+            MultiMap::new(),
+            RequestMethod::GET,
+            server.url("/response-headers"),
+            MultiMap::from([
+                ("token", "abc123"),
+                ("X-Order-Id", "42"),
+                ("Set-Cookie", "session=s3cr3t; Path=/; HttpOnly"),
+            ]),
+        )
+        .send_request()
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn extract_from_every_source() {
+        let server = EchoServer::start().unwrap();
+        let res = login(&server).await;
+
+        let extract = |from| Extraction::new("v", from).extract(&res).unwrap();
+        assert_eq!("abc123", extract(Source::JsonPath("$.token".to_string())));
+        assert_eq!("abc123", extract(Source::JsonPointer("/token".to_string())));
+        assert_eq!("42", extract(Source::Header("x-order-id".to_string())));
+        assert_eq!(
+            "abc",
+            extract(Source::Regex(r#""token":"([a-z]+)"#.to_string()))
+        );
+        assert_eq!("s3cr3t", extract(Source::Cookie("session".to_string())));
+
+        assert!(matches!(
+            Extraction::new("v", Source::Header("missing".to_string())).extract(&res),
+            Err(Error::NoMatch { .. })
+        ));
+        assert!(matches!(
+            Extraction::new("v", Source::Regex("(".to_string())).extract(&res),
+            Err(Error::InvalidRegex(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn save_into_runtime_or_environment() {
+        let server = EchoServer::start().unwrap();
+        let res = login(&server).await;
+        let rules = [
+            Extraction::new("token", Source::JsonPath("$.token".to_string()))
+                .with_scope(Target::Environment),
+            Extraction::new("order", Source::Header("X-Order-Id".to_string())),
+        ];
+
+        let mut variables = Variables::new();
+        apply(&rules, &res, &mut variables).unwrap();
+        assert_eq!(2, variables.runtime.len());
+
+        let mut variables = Variables::new();
+        variables.environment = Some(Environment::new("dev"));
+        apply(&rules, &res, &mut variables).unwrap();
+        let environment = variables.environment.as_ref().unwrap();
+        assert_eq!(
+            Some("abc123"),
+            environment.variables.get("token").map(String::as_str)
+        );
+        assert_eq!(
+            Some("42"),
+            variables.runtime.get("order").map(String::as_str)
+        );
+        assert_eq!(
+            "Bearer abc123 42",
+            variables
+                .scope(&Default::default())
+                .interpolate("Bearer {{token}} {{order}}")
+                .unwrap()
+        );
+    }
+}
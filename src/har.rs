@@ -0,0 +1,770 @@
+use crate::{
+    body::{MultipartPart, RequestBody},
+    collection::{self, Collection, SavedRequest},
+    multimap::MultiMap,
+    report::sensitive_header,
+    request::{HeaderCase, Request, RequestMethod},
+    runner::{Exchange, Outcome, RequestResult, RunSummary},
+};
+use reqwest::{StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use std::{error, fmt, path::PathBuf, time::Duration};
+
+/// Headers not carried over: pseudo-headers are HTTP/2 framing, the others are
+/// computed when sending, and bodies would come back compressed
+const SKIPPED_HEADERS: [&str; 3] = ["content-length", "host", "accept-encoding"];
+
+/// Written instead of credentials, HAR files get shared and attached to CI runs
+const REDACTED: &str = "[redacted]";
+
+/// A collection converted from a HAR capture, with what could not be carried over
+#[derive(Debug, Clone, PartialEq)]
+pub struct Import {
+    pub collection: Collection,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Json(serde_json::Error),
+    Collection(collection::Error),
+}
+
+// HAR 1.2 as described at http://www.softwareishard.com/blog/har-12-spec/.
+// Every field has a default so that captures from any browser are accepted.
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct Har {
+    log: Log,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct Log {
+    version: String,
+    creator: Creator,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pages: Vec<Page>,
+    entries: Vec<Entry>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct Creator {
+    name: String,
+    version: String,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct Page {
+    title: String,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct Entry {
+    #[serde(rename = "startedDateTime")]
+    started: String,
+    time: f64, // Milliseconds, the sum of the non-negative timings
+    request: HarRequest,
+    response: HarResponse,
+    cache: Cache,
+    timings: Timings,
+    #[serde(rename = "serverIPAddress", skip_serializing_if = "Option::is_none")]
+    server_ip_address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    comment: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct HarRequest {
+    method: String,
+    url: String,
+    #[serde(rename = "httpVersion")]
+    http_version: String,
+    cookies: Vec<Cookie>,
+    headers: Vec<Pair>,
+    #[serde(rename = "queryString")]
+    query_string: Vec<Pair>,
+    #[serde(rename = "postData", skip_serializing_if = "Option::is_none")]
+    post_data: Option<PostData>,
+    #[serde(rename = "headersSize")]
+    headers_size: i64, // -1 when unknown
+    #[serde(rename = "bodySize")]
+    body_size: i64,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct HarResponse {
+    status: u16,
+    #[serde(rename = "statusText")]
+    status_text: String,
+    #[serde(rename = "httpVersion")]
+    http_version: String,
+    cookies: Vec<Cookie>,
+    headers: Vec<Pair>,
+    content: Content,
+    #[serde(rename = "redirectURL")]
+    redirect_url: String,
+    #[serde(rename = "headersSize")]
+    headers_size: i64,
+    #[serde(rename = "bodySize")]
+    body_size: i64,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq)]
+#[serde(default)]
+struct Cookie {
+    name: String,
+    value: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    domain: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expires: Option<String>,
+    #[serde(rename = "httpOnly", skip_serializing_if = "Option::is_none")]
+    http_only: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    secure: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct Pair {
+    name: String,
+    value: String,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct PostData {
+    #[serde(rename = "mimeType")]
+    mime_type: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    params: Vec<Param>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct Param {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<String>,
+    #[serde(rename = "fileName", skip_serializing_if = "Option::is_none")]
+    file_name: Option<String>,
+    #[serde(rename = "contentType", skip_serializing_if = "Option::is_none")]
+    content_type: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct Content {
+    size: i64,
+    #[serde(rename = "mimeType")]
+    mime_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    encoding: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct Cache {}
+
+/// Milliseconds, -1 when the phase does not apply or was not measured
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct Timings {
+    blocked: f64,
+    dns: f64,
+    connect: f64,
+    ssl: f64,
+    send: f64,
+    wait: f64,
+    receive: f64,
+}
+
+/// Converts a HAR capture, e.g. saved from the network tab of browser devtools.
+/// Requests are grouped in one folder per host and named after their method and path.
+pub fn parse(content: &str) -> Result<Import, Error> {
+    let har: Har = serde_json::from_str(content).map_err(Error::Json)?;
+    let name = match har.log.pages.first() {
+        Some(page) if !page.title.trim().is_empty() => page.title.clone(),
+        _ => "imported".to_string(),
+    };
+    let mut collection = Collection::new(name);
+    let mut warnings = Vec::new();
+
+    for (index, entry) in har.log.entries.iter().enumerate() {
+        let request = &entry.request;
+        let url = match Url::parse(&request.url) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => url,
+            _ => {
+                let mut shown = request.url.clone();
+                if let Some((end, _)) = shown.char_indices().nth(60) {
+                    shown.truncate(end);
+                    shown.push_str("...");
+                }
+                warnings.push(format!(
+                    "entry {}: `{}` is not an HTTP url, skipped",
+                    index + 1,
+                    shown
+                ));
+                continue;
+            }
+        };
+        let Ok(method) = request.method.parse::<RequestMethod>() else {
+            warnings.push(format!(
+                "entry {}: invalid method `{}`, skipped",
+                index + 1,
+                request.method
+            ));
+            continue;
+        };
+
+        let folder = match url.port() {
+            Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+            None => url.host_str().unwrap_or_default().to_string(),
+        };
+        let name = format!("{} {}", method, url.path().trim_matches('/'));
+        let name = collection.unique_name(&folder, &name);
+        let path = format!("{}/{}", folder, name);
+
+        let mut headers = MultiMap::new();
+        for header in &request.headers {
+            let lower = header.name.to_ascii_lowercase();
+            if !header.name.starts_with(':') && !SKIPPED_HEADERS.contains(&lower.as_str()) {
+                headers.append(header.name.as_str(), header.value.as_str());
+            }
+        }
+        if headers.get_ignore_case("cookie").is_none() && !request.cookies.is_empty() {
+            let cookies: Vec<_> = request
+                .cookies
+                .iter()
+                .map(|cookie| format!("{}={}", cookie.name, cookie.value))
+                .collect();
+            headers.append("Cookie", cookies.join("; "));
+        }
+        let body = request
+            .post_data
+            .as_ref()
+            .and_then(|data| body(&path, data, &mut headers, &mut warnings));
+
+        let params: MultiMap = url.query_pairs().collect();
+        let mut base = url.clone();
+        base.set_query(None);
+        base.set_fragment(None);
+        let request = Request::new(body, headers, method, base.to_string(), params)
+            .with_header_case(HeaderCase::Verbatim);
+        collection
+            .add(&folder, SavedRequest::new(name, request))
+            .map_err(Error::Collection)?;
+    }
+    Ok(Import {
+        collection,
+        warnings,
+    })
+}
+
+/// Text becomes a raw body, parameters a form, multipart when files are involved.
+/// The Content-Type header is replaced by the body's own.
+fn body(
+    path: &str,
+    data: &PostData,
+    headers: &mut MultiMap,
+    warnings: &mut Vec<String>,
+) -> Option<RequestBody> {
+    let header = headers.get_ignore_case("content-type").map(str::to_string);
+    headers.remove_ignore_case("content-type");
+    let content_type = match data.mime_type.as_str() {
+        "" => header.unwrap_or_default(),
+        mime_type => mime_type.to_string(),
+    };
+    if data.params.is_empty() || data.text.as_ref().is_some_and(|text| !text.is_empty()) {
+        let content = data.text.clone().unwrap_or_default();
+        if content.is_empty() {
+            return None;
+        }
+        return Some(RequestBody::Raw {
+            content,
+            content_type,
+        });
+    }
+
+    if !content_type.starts_with("multipart/") {
+        let form = data
+            .params
+            .iter()
+            .map(|param| {
+                (
+                    param.name.as_str(),
+                    param.value.as_deref().unwrap_or_default(),
+                )
+            })
+            .collect();
+        return Some(RequestBody::Form(form));
+    }
+    let mut parts = Vec::new();
+    for param in &data.params {
+        match &param.file_name {
+            Some(file_name) => {
+                warnings.push(format!(
+                    "`{}`: file `{}` of field `{}` is read from the working directory",
+                    path, file_name, param.name
+                ));
+                parts.push(MultipartPart::File {
+                    name: param.name.clone(),
+                    path: PathBuf::from(file_name),
+                    content_type: param.content_type.clone(),
+                });
+            }
+            None => parts.push(MultipartPart::Text {
+                name: param.name.clone(),
+                value: param.value.clone().unwrap_or_default(),
+            }),
+        }
+    }
+    Some(RequestBody::Multipart(parts))
+}
+
+/// The exchanges of a run as a HAR log, e.g. to inspect them in browser devtools.
+/// Requests that got no response are left out, each entry's comment is its path.
+/// Cookies and credential headers are redacted, as in HTML reports.
+/// `dns` is measured when the request opened a connection to a host name, and
+/// is -1 otherwise. The client does not expose connecting, TLS handshakes or
+/// sending, so `blocked`, `connect` and `ssl` are -1 and `send` is 0, their time
+/// being counted in `wait`.
+pub fn export(summary: &RunSummary) -> String {
+    let har = Har {
+        log: Log {
+            version: "1.2".to_string(),
+            creator: Creator {
+                name: "asterios".to_string(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+            },
+            pages: Vec::new(),
+            entries: summary
+                .results
+                .iter()
+                .filter_map(|result| Some(entry(result, result.exchange.as_ref()?)))
+                .collect(),
+        },
+    };
+    let mut out = serde_json::to_string_pretty(&har).expect("HAR logs serialize to JSON");
+    out.push('\n');
+    out
+}
+
+fn entry(result: &RequestResult, exchange: &Exchange) -> Entry {
+    let (elapsed, size) = match result.outcome {
+        Outcome::Response { elapsed, size, .. } => (elapsed, size),
+        Outcome::Error(_) => (exchange.waiting, exchange.response_body.len()),
+    };
+    let request_size = exchange.request_body.as_ref().map_or(0, String::len);
+    let query_string = match Url::parse(&exchange.url) {
+        Ok(url) => url
+            .query_pairs()
+            .map(|(name, value)| Pair {
+                name: name.into_owned(),
+                value: value.into_owned(),
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+
+    Entry {
+        started: exchange.started.clone(),
+        time: millis(elapsed),
+        request: HarRequest {
+            method: result.method.clone(),
+            url: exchange.url.clone(),
+            http_version: exchange.http_version.clone(),
+            cookies: exchange
+                .request_headers
+                .iter()
+                .filter(|(name, _)| name.eq_ignore_ascii_case("cookie"))
+                .flat_map(|(_, value)| value.split(';'))
+                .filter_map(request_cookie)
+                .map(redact)
+                .collect(),
+            headers: pairs(&exchange.request_headers),
+            query_string,
+            post_data: exchange.request_body.as_ref().map(|text| PostData {
+                mime_type: header(&exchange.request_headers, "content-type"),
+                params: Vec::new(),
+                text: Some(text.clone()),
+            }),
+            headers_size: -1,
+            body_size: request_size as i64,
+        },
+        response: HarResponse {
+            status: exchange.status,
+            status_text: StatusCode::from_u16(exchange.status)
+                .ok()
+                .and_then(|status| status.canonical_reason())
+                .unwrap_or_default()
+                .to_string(),
+            http_version: exchange.http_version.clone(),
+            cookies: exchange
+                .response_headers
+                .iter()
+                .filter(|(name, _)| name.eq_ignore_ascii_case("set-cookie"))
+                .filter_map(|(_, value)| response_cookie(value))
+                .map(redact)
+                .collect(),
+            headers: pairs(&exchange.response_headers),
+            content: Content {
+                size: size as i64,
+                mime_type: header(&exchange.response_headers, "content-type"),
+                text: Some(exchange.response_body.clone()),
+                encoding: None,
+            },
+            redirect_url: header(&exchange.response_headers, "location"),
+            headers_size: -1,
+            body_size: size as i64,
+        },
+        cache: Cache {},
+        timings: Timings {
+            blocked: -1.0,
+            dns: exchange.dns.map_or(-1.0, millis),
+            connect: -1.0,
+            ssl: -1.0,
+            send: 0.0,
+            wait: millis(
+                exchange
+                    .waiting
+                    .saturating_sub(exchange.dns.unwrap_or_default()),
+            ),
+            receive: millis(elapsed.saturating_sub(exchange.waiting)),
+        },
+        server_ip_address: None,
+        comment: Some(result.path.clone()),
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_micros() as f64 / 1000.0
+}
+
+fn pairs(headers: &MultiMap) -> Vec<Pair> {
+    headers
+        .iter()
+        .map(|(name, value)| Pair {
+            name: name.to_string(),
+            value: match sensitive_header(name) {
+                true => REDACTED.to_string(),
+                false => value.to_string(),
+            },
+        })
+        .collect()
+}
+
+fn redact(cookie: Cookie) -> Cookie {
+    Cookie {
+        value: REDACTED.to_string(),
+        ..cookie
+    }
+}
+
+fn header(headers: &MultiMap, name: &str) -> String {
+    headers
+        .get_ignore_case(name)
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// One `name=value` pair of a Cookie header
+fn request_cookie(pair: &str) -> Option<Cookie> {
+    let (name, value) = pair.split_once('=')?;
+    Some(Cookie {
+        name: name.trim().to_string(),
+        value: value.trim().to_string(),
+        ..Cookie::default()
+    })
+}
+
+/// A Set-Cookie header: `name=value` then attributes separated by `;`
+fn response_cookie(header: &str) -> Option<Cookie> {
+    let mut attributes = header.split(';');
+    let mut cookie = request_cookie(attributes.next()?)?;
+    for attribute in attributes {
+        let (key, value) = match attribute.split_once('=') {
+            Some((key, value)) => (key.trim(), Some(value.trim().to_string())),
+            None => (attribute.trim(), None),
+        };
+        match key.to_ascii_lowercase().as_str() {
+            "path" => cookie.path = value,
+            "domain" => cookie.domain = value,
+            "expires" => cookie.expires = value,
+            "httponly" => cookie.http_only = Some(true),
+            "secure" => cookie.secure = Some(true),
+            _ => {}
+        }
+    }
+    Some(cookie)
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Json(_) => write!(f, "invalid HAR file"),
+            Error::Collection(_) => write!(f, "could not build the collection"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Json(e) => Some(e),
+            Error::Collection(e) => Some(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{export, parse, response_cookie, Cookie};
+    use crate::{
+        body::{MultipartPart, RequestBody},
+        multimap::MultiMap,
+        request::RequestMethod,
+        runner::{Exchange, Outcome, RequestResult, RunSummary},
+    };
+    use serde_json::{json, Value};
+    use std::{path::PathBuf, time::Duration};
+
+    const CAPTURE: &str = r#"{
+        "log": {
+            "version": "1.2",
+            "creator": {"name": "WebInspector", "version": "537.36"},
+            "pages": [{"id": "page_1", "title": "Shop"}],
+            "entries": [
+                {
+                    "startedDateTime": "2024-05-01T10:00:00.000Z",
+                    "request": {
+                        "method": "GET",
+                        "url": "https://shop.test/api/orders?page=2&sort=date#top",
+                        "httpVersion": "h2",
+                        "headers": [
+                            {"name": ":authority", "value": "shop.test"},
+                            {"name": "accept", "value": "application/json"},
+                            {"name": "accept-encoding", "value": "gzip, br"}
+                        ],
+                        "cookies": [
+                            {"name": "session", "value": "abc"},
+                            {"name": "theme", "value": "dark"}
+                        ],
+                        "queryString": [{"name": "page", "value": "2"}]
+                    }
+                },
+                {
+                    "request": {
+                        "method": "POST",
+                        "url": "https://shop.test/api/orders",
+                        "headers": [
+                            {"name": "Content-Type", "value": "application/json"},
+                            {"name": "Content-Length", "value": "12"}
+                        ],
+                        "postData": {"mimeType": "application/json", "text": "{\"qty\": 1}"}
+                    }
+                },
+                {
+                    "request": {
+                        "method": "GET",
+                        "url": "https://shop.test/api/orders"
+                    }
+                },
+                {
+                    "request": {
+                        "method": "POST",
+                        "url": "http://localhost:8080/upload",
+                        "postData": {
+                            "mimeType": "multipart/form-data; boundary=xyz",
+                            "params": [
+                                {"name": "title", "value": "cat"},
+                                {"name": "photo", "fileName": "cat.png", "contentType": "image/png"}
+                            ]
+                        }
+                    }
+                },
+                {"request": {"method": "GET", "url": "data:image/png;base64,iVBORw0KGgo="}}
+            ]
+        }
+    }"#;
+
+    #[test]
+    fn import_a_browser_capture() {
+        let import = parse(CAPTURE).unwrap();
+        let collection = import.collection;
+        assert_eq!("Shop", collection.info().name);
+        let requests = collection.requests();
+        let paths: Vec<_> = requests.iter().map(|(path, _)| path.as_str()).collect();
+        assert_eq!(
+            vec![
+                "shop.test/GET api-orders",
+                "shop.test/POST api-orders",
+                "shop.test/GET api-orders (2)",
+                "localhost:8080/POST upload",
+            ],
+            paths
+        );
+
+        let get = &requests[0].1.request;
+        assert_eq!("https://shop.test/api/orders", get.url());
+        assert_eq!(
+            &MultiMap::from([("page", "2"), ("sort", "date")]),
+            get.params()
+        );
+        assert_eq!(
+            &MultiMap::from([
+                ("accept", "application/json"),
+                ("Cookie", "session=abc; theme=dark")
+            ]),
+            get.headers()
+        );
+
+        let post = &requests[1].1.request;
+        assert_eq!(&RequestMethod::POST, post.method());
+        assert!(post.headers().is_empty());
+        assert_eq!(
+            Some(&RequestBody::Raw {
+                content: "{\"qty\": 1}".to_string(),
+                content_type: "application/json".to_string(),
+            }),
+            post.body()
+        );
+
+        let upload = &requests[3].1.request;
+        assert_eq!(
+            Some(&RequestBody::Multipart(vec![
+                MultipartPart::Text {
+                    name: "title".to_string(),
+                    value: "cat".to_string(),
+                },
+                MultipartPart::File {
+                    name: "photo".to_string(),
+                    path: PathBuf::from("cat.png"),
+                    content_type: Some("image/png".to_string()),
+                },
+            ])),
+            upload.body()
+        );
+
+        assert_eq!(2, import.warnings.len());
+        assert!(import.warnings[1].contains("data:image/png"));
+    }
+
+    fn summary() -> RunSummary {
+        let result = RequestResult {
+            iteration: 0,
+            path: "orders/create".to_string(),
+            method: "POST".to_string(),
+            url: "http://localhost/orders".to_string(),
+            outcome: Outcome::Response {
+                status: 201,
+                elapsed: Duration::from_millis(50),
+                size: 11,
+            },
+            assertions: Vec::new(),
+            snippet: None,
+            exchange: Some(Exchange {
+                started: "2024-05-01T10:00:00.000Z".to_string(),
+                url: "http://localhost/orders?dry_run=true&tag=a%20b".to_string(),
+                http_version: "HTTP/1.1".to_string(),
+                status: 201,
+                waiting: Duration::from_millis(30),
+                dns: Some(Duration::from_millis(4)),
+                request_headers: MultiMap::from([
+                    ("content-type", "application/json"),
+                    ("cookie", "session=abc; theme=dark"),
+                    ("X-Api-Key", "k3y"),
+                ]),
+                request_body: Some("{\"qty\": 1}".to_string()),
+                response_headers: MultiMap::from([
+                    ("content-type", "application/json"),
+                    ("location", "/orders/7"),
+                    ("set-cookie", "seen=1; Path=/; HttpOnly"),
+                ]),
+                response_body: "{\"id\": 7}\n".to_string(),
+            }),
+        };
+        let mut unsent = result.clone();
+        unsent.outcome = Outcome::Error("connection refused".to_string());
+        unsent.exchange = None;
+        RunSummary {
+            collection: "shop".to_string(),
+            results: vec![result, unsent],
+            elapsed: Duration::from_millis(60),
+            bailed: false,
+        }
+    }
+
+    #[test]
+    fn export_executed_requests() {
+        let har: Value = serde_json::from_str(&export(&summary())).unwrap();
+        let entries = har["log"]["entries"].as_array().unwrap();
+        assert_eq!(1, entries.len());
+        let entry = &entries[0];
+        assert_eq!("orders/create", entry["comment"]);
+        assert_eq!(50.0, entry["time"]);
+        assert_eq!(4.0, entry["timings"]["dns"]);
+        assert_eq!(26.0, entry["timings"]["wait"]);
+        assert_eq!(20.0, entry["timings"]["receive"]);
+        assert_eq!(-1.0, entry["timings"]["connect"]);
+
+        let request = &entry["request"];
+        assert_eq!("a b", request["queryString"][1]["value"]);
+        assert_eq!("theme", request["cookies"][1]["name"]);
+        assert_eq!("[redacted]", request["cookies"][1]["value"]);
+        assert_eq!(
+            json!([
+                {"name": "content-type", "value": "application/json"},
+                {"name": "cookie", "value": "[redacted]"},
+                {"name": "X-Api-Key", "value": "[redacted]"},
+            ]),
+            request["headers"]
+        );
+        assert_eq!("application/json", request["postData"]["mimeType"]);
+        assert_eq!("{\"qty\": 1}", request["postData"]["text"]);
+
+        let response = &entry["response"];
+        assert_eq!("Created", response["statusText"]);
+        assert_eq!("/orders/7", response["redirectURL"]);
+        assert_eq!(true, response["cookies"][0]["httpOnly"]);
+        assert_eq!("[redacted]", response["cookies"][0]["value"]);
+        assert!(!export(&summary()).contains("abc"));
+        assert_eq!(11, response["content"]["size"]);
+
+        // What was exported imports back as the same request
+        let import = parse(&export(&summary())).unwrap();
+        let (path, saved) = &import.collection.requests()[0];
+        assert_eq!("localhost/POST orders", path);
+        assert_eq!(
+            &MultiMap::from([("dry_run", "true"), ("tag", "a b")]),
+            saved.request.params()
+        );
+    }
+
+    #[test]
+    fn parse_set_cookie_attributes() {
+        assert_eq!(
+            Some(Cookie {
+                name: "id".to_string(),
+                value: "a=b".to_string(),
+                path: Some("/".to_string()),
+                domain: Some("shop.test".to_string()),
+                secure: Some(true),
+                ..Cookie::default()
+            }),
+            response_cookie("id=a=b; Path=/; Domain=shop.test; Secure; SameSite=Lax")
+        );
+    }
+}
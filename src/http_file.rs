@@ -0,0 +1,805 @@
+use crate::{
+    body::{BinaryBody, MultipartPart, RequestBody},
+    collection::{self, Collection, SavedRequest},
+    dynamic::Generator,
+    multimap::MultiMap,
+    request::{HeaderCase, Request, RequestMethod},
+    variables::VariableMap,
+};
+use regex::{Captures, Regex};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error, fmt,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+
+/// Boundary of the multipart bodies written by `export`
+const BOUNDARY: &str = "AsteriosFormBoundary";
+
+/// The requests of a `.http` file, as used by the REST Client extension of
+/// VS Code and the HTTP client of JetBrains IDEs
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpFile {
+    pub variables: VariableMap, // `@name = value` definitions, visible to every request
+    pub requests: Vec<HttpRequest>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpRequest {
+    pub name: String, // As written in `### name` or `# @name name`, or built from the URL
+    pub folder: String, // From a `### auth/login` title, the request itself is named `login`
+    pub lines: RangeInclusive<usize>, // 1-based, from its `###` separator to the next one
+    pub saved: SavedRequest,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    InvalidHeader(usize), // Line number
+    InvalidMethod { line: usize, method: String },
+    ConflictingVariable(String), // Exported with different values, the file can only hold one
+}
+
+impl HttpFile {
+    /// The request named `selector`, or the one whose lines include it when it is a number
+    pub fn find(&self, selector: &str) -> Option<&HttpRequest> {
+        match selector.parse::<usize>() {
+            Ok(line) => self.requests.iter().find(|r| r.lines.contains(&line)),
+            Err(_) => self.requests.iter().find(|r| r.name == selector),
+        }
+    }
+
+    /// The file as a collection: variables become collection variables and
+    /// requests go in the folders of their `###` titles
+    pub fn to_collection(&self, name: &str) -> Result<Collection, collection::Error> {
+        let mut collection = Collection::new(name);
+        *collection.variables_mut() = self.variables.clone();
+        for request in &self.requests {
+            let mut saved = request.saved.clone();
+            saved.name = collection.unique_name(&request.folder, &saved.name);
+            collection.add(&request.folder, saved)?;
+        }
+        Ok(collection)
+    }
+}
+
+/// Parses a `.http` file: requests separated by `###` lines, each one a request
+/// line, headers, a blank line then the body. `@name = value` lines define
+/// variables, `#` and `//` lines are comments. Relative paths of files to send
+/// are relative to `dir`, the directory of the file, as both clients read them.
+pub fn parse(content: &str, dir: &Path) -> Result<HttpFile, Error> {
+    let mut parser = Parser {
+        dir: dir.to_path_buf(),
+        file: HttpFile {
+            variables: VariableMap::new(),
+            requests: Vec::new(),
+            warnings: Vec::new(),
+        },
+        dynamic: Regex::new(r"\{\{\s*\$([^{}]*?)\s*\}\}").expect("valid regex"),
+        request_variable: Regex::new(r"\{\{\s*([\w-]+)\.(response|request)\.")
+            .expect("valid regex"),
+        unknown: BTreeSet::new(),
+    };
+    let content = parser.dynamic(content);
+    let lines: Vec<&str> = content.lines().collect();
+
+    // Blocks as (index of their first line, title after `###`)
+    let mut blocks = vec![(0, None)];
+    for (index, line) in lines.iter().enumerate() {
+        if let Some(title) = line.trim_start().strip_prefix("###") {
+            let title = title.trim();
+            blocks.push((index, (!title.is_empty()).then(|| title.to_string())));
+        }
+    }
+    for (i, (start, title)) in blocks.iter().enumerate() {
+        let end = blocks.get(i + 1).map_or(lines.len(), |(next, _)| *next);
+        let body_start = if i == 0 { *start } else { start + 1 };
+        if body_start <= end {
+            let block = &lines[body_start..end];
+            parser.block(
+                block,
+                body_start + 1,
+                *start + 1..=end.max(start + 1),
+                title.clone(),
+            )?;
+        }
+    }
+
+    Ok(parser.finish())
+}
+
+struct Parser {
+    dir: PathBuf,
+    file: HttpFile,
+    dynamic: Regex,
+    request_variable: Regex,
+    unknown: BTreeSet<String>, // Dynamic variables without an equivalent
+}
+
+impl Parser {
+    fn finish(mut self) -> HttpFile {
+        for name in &self.unknown {
+            self.file.warnings.push(format!(
+                "`{{{{${}}}}}` has no built-in equivalent, define it as a variable",
+                name
+            ));
+        }
+        self.file
+    }
+
+    fn warn(&mut self, warning: String) {
+        if !self.file.warnings.contains(&warning) {
+            self.file.warnings.push(warning);
+        }
+    }
+
+    /// Rewrites the dynamic variables of both clients into ours, e.g. `{{$guid}}`
+    /// into `{{$uuid}}` and `{{$randomInt 1 7}}` into `{{$randomInt(1,6)}}`
+    fn dynamic(&mut self, content: &str) -> String {
+        let unknown = &mut self.unknown;
+        self.dynamic
+            .replace_all(content, |captures: &Captures| {
+                let words: Vec<&str> = captures[1].split_whitespace().collect();
+                let converted = match words.as_slice() {
+                    ["guid" | "uuid" | "random.uuid"] => Some("uuid".to_string()),
+                    ["datetime", "iso8601"] | ["isoTimestamp"] => Some("isoTimestamp".to_string()),
+                    // REST Client excludes the maximum, we include it
+                    ["randomInt", min, max] => match (min.parse::<i64>(), max.parse::<i64>()) {
+                        (Ok(min), Ok(max)) if min < max => {
+                            Some(format!("randomInt({},{})", min, max - 1))
+                        }
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                match converted {
+                    Some(expr) => format!("{{{{${}}}}}", expr),
+                    None => {
+                        if Generator::seeded(0).generate(&captures[1]).is_err() {
+                            unknown.insert(captures[1].to_string());
+                        }
+                        captures[0].to_string()
+                    }
+                }
+            })
+            .into_owned()
+    }
+
+    /// One `###` block: comments and definitions, then at most one request.
+    /// `first` is the line number of `block[0]`.
+    fn block(
+        &mut self,
+        block: &[&str],
+        first: usize,
+        lines: RangeInclusive<usize>,
+        title: Option<String>,
+    ) -> Result<(), Error> {
+        // Only titles name folders, slashes elsewhere are part of the name
+        let mut titled = title.is_some();
+        let mut name = title;
+        let mut i = 0;
+        while let Some(line) = block.get(i).map(|line| line.trim()) {
+            if let Some(comment) = line.strip_prefix('#').or_else(|| line.strip_prefix("//")) {
+                if let Some(value) = comment.trim().strip_prefix("@name") {
+                    let value = value.trim().trim_start_matches('=').trim();
+                    if !value.is_empty() {
+                        name = Some(value.to_string());
+                        titled = false;
+                    }
+                }
+            } else if let Some((key, value)) =
+                line.strip_prefix('@').and_then(|d| d.split_once('='))
+            {
+                self.file
+                    .variables
+                    .insert(key.trim().to_string(), value.trim().to_string());
+            } else if !line.is_empty() {
+                break;
+            }
+            i += 1;
+        }
+        let Some(request_line) = block.get(i) else {
+            return Ok(());
+        };
+
+        let mut words: Vec<&str> = request_line.split_whitespace().collect();
+        if words.len() > 1 && words[words.len() - 1].starts_with("HTTP/") {
+            words.pop();
+        }
+        let (method, mut url) = match words.as_slice() {
+            [url] => (RequestMethod::GET, url.to_string()),
+            [method, url @ ..] => {
+                let method = method
+                    .parse::<RequestMethod>()
+                    .map_err(|_| Error::InvalidMethod {
+                        line: first + i,
+                        method: method.to_string(),
+                    })?;
+                (method, url.join(" "))
+            }
+            [] => unreachable!("the request line is not blank"),
+        };
+        i += 1;
+        // Long query strings may continue on the next lines
+        while let Some(line) = block.get(i).map(|line| line.trim()) {
+            if !line.starts_with('?') && !line.starts_with('&') {
+                break;
+            }
+            url.push_str(line);
+            i += 1;
+        }
+
+        let mut headers = MultiMap::new();
+        while let Some(line) = block.get(i).map(|line| line.trim()) {
+            i += 1;
+            if line.is_empty() {
+                break;
+            }
+            if line.starts_with('#') || line.starts_with("//") {
+                continue;
+            }
+            let (key, value) = line
+                .split_once(':')
+                .ok_or(Error::InvalidHeader(first + i - 1))?;
+            headers.append(key.trim(), value.trim());
+        }
+
+        let name = name.unwrap_or_else(|| default_name(&method, &url));
+        let body = self.body(&name, block.get(i..).unwrap_or_default(), &mut headers);
+        let request = Request::new(body, headers, method, url, MultiMap::new())
+            .with_header_case(HeaderCase::Verbatim);
+        let mut found = Vec::new();
+        request.map_strings(&mut |text| {
+            found.extend(
+                self.request_variable
+                    .captures_iter(text)
+                    .map(|c| c[1].to_string()),
+            );
+            text.to_string()
+        });
+        for variable in found {
+            self.warn(format!(
+                "`{}`: `{{{{{}.response...}}}}` is not supported, extract the value instead",
+                name, variable
+            ));
+        }
+
+        let (folder, leaf) = match name.rsplit_once('/') {
+            Some((folder, leaf)) if titled => (folder, leaf),
+            _ => ("", name.as_str()),
+        };
+        self.file.requests.push(HttpRequest {
+            folder: folder.to_string(),
+            saved: SavedRequest::new(leaf, request),
+            name,
+            lines,
+        });
+        Ok(())
+    }
+
+    /// `< path` sends a file, multipart bodies are split into parts, anything
+    /// else is sent as written with the Content-Type header as its type
+    fn body(&mut self, name: &str, lines: &[&str], headers: &mut MultiMap) -> Option<RequestBody> {
+        let mut kept = Vec::new();
+        let mut in_handler = false;
+        for line in lines {
+            if in_handler {
+                in_handler = !line.contains("%}");
+            } else if line.starts_with("> {%") {
+                self.warn(format!("`{}`: response handler skipped", name));
+                in_handler = !line.contains("%}");
+            } else if line.starts_with("> ") {
+                self.warn(format!("`{}`: response handler skipped", name));
+            } else if !line.starts_with("<> ") {
+                kept.push(*line);
+            }
+        }
+        while kept.last().is_some_and(|line| line.trim().is_empty()) {
+            kept.pop();
+        }
+        if kept.is_empty() {
+            return None;
+        }
+        let content = kept.join("\n");
+
+        if let [line] = kept.as_slice() {
+            let path = line.strip_prefix("<@ ").or_else(|| line.strip_prefix("< "));
+            if let Some(path) = path {
+                if line.starts_with("<@") {
+                    self.warn(format!(
+                        "`{}`: variables in `{}` are not substituted",
+                        name,
+                        path.trim()
+                    ));
+                }
+                return Some(RequestBody::Binary(BinaryBody::File(self.path(path))));
+            }
+        }
+        let content_type = headers
+            .get_ignore_case("content-type")
+            .unwrap_or("text/plain")
+            .to_string();
+        headers.remove_ignore_case("content-type");
+        let parts = boundary(&content_type).and_then(|boundary| multipart(&content, &boundary));
+        if let Some(mut parts) = parts {
+            for part in &mut parts {
+                if let MultipartPart::File { path, .. } = part {
+                    *path = self.path(&path.to_string_lossy());
+                }
+            }
+            return Some(RequestBody::Multipart(parts));
+        }
+        Some(RequestBody::Raw {
+            content,
+            content_type,
+        })
+    }
+
+    /// `path` from the directory of the file, unless it is absolute or starts
+    /// with a variable
+    fn path(&self, path: &str) -> PathBuf {
+        let path = path.trim();
+        if path.starts_with("{{") || Path::new(path).is_absolute() {
+            return PathBuf::from(path);
+        }
+        self.dir.join(path.strip_prefix("./").unwrap_or(path))
+    }
+}
+
+/// `GET users` for `GET {{host}}/users?page=1`
+fn default_name(method: &RequestMethod, url: &str) -> String {
+    let mut path = url;
+    if let Some((_, rest)) = path.split_once("://") {
+        path = rest.find('/').map_or("", |slash| &rest[slash..]);
+    } else if path.starts_with("{{") {
+        path = path.find("}}").map_or(path, |end| &path[end + 2..]);
+    }
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+    format!("{} {}", method, path.trim_matches('/'))
+        .trim()
+        .to_string()
+}
+
+fn boundary(content_type: &str) -> Option<String> {
+    if !content_type.starts_with("multipart/form-data") {
+        return None;
+    }
+    content_type.split(';').find_map(|param| {
+        let value = param.trim().strip_prefix("boundary=")?;
+        Some(value.trim_matches('"').to_string())
+    })
+}
+
+/// The parts of a multipart body, `None` when it is not well formed
+fn multipart(content: &str, boundary: &str) -> Option<Vec<MultipartPart>> {
+    let delimiter = format!("--{}", boundary);
+    let mut parts = Vec::new();
+    let mut sections = content.split(delimiter.as_str());
+    if !sections.next()?.trim().is_empty() {
+        return None;
+    }
+    for section in sections {
+        if section.starts_with("--") {
+            return Some(parts);
+        }
+        let section = section.strip_prefix('\n')?;
+        let (head, value) = section.split_once("\n\n")?;
+        let value = value.strip_suffix('\n').unwrap_or(value);
+        let mut name = None;
+        let mut content_type = None;
+        for line in head.lines() {
+            let (key, header) = line.split_once(':')?;
+            if key.trim().eq_ignore_ascii_case("content-type") {
+                content_type = Some(header.trim().to_string());
+            } else if key.trim().eq_ignore_ascii_case("content-disposition") {
+                name = header.split(';').find_map(|param| {
+                    let value = param.trim().strip_prefix("name=")?;
+                    Some(value.trim_matches('"').to_string())
+                });
+            }
+        }
+        let name = name?;
+        match value.trim().strip_prefix("< ") {
+            Some(path) => parts.push(MultipartPart::File {
+                name,
+                path: PathBuf::from(path.trim()),
+                content_type,
+            }),
+            None => parts.push(MultipartPart::Text {
+                name,
+                value: value.to_string(),
+            }),
+        }
+    }
+    None
+}
+
+/// Writes `collection` as a `.http` file: its variables as definitions, then
+/// each request under a `###` separator named after its path. Variables of a
+/// request are written in its block, where both clients make them file-wide,
+/// so a variable may not have different values in several places.
+pub fn export(collection: &Collection) -> Result<String, Error> {
+    let mut defined: BTreeMap<&str, &str> = BTreeMap::new();
+    let requests = collection.requests();
+    let variables = collection.variables().iter().chain(
+        requests
+            .iter()
+            .flat_map(|(_, saved)| saved.variables.iter()),
+    );
+    for (name, value) in variables {
+        if defined
+            .insert(name, value)
+            .is_some_and(|other| other != value)
+        {
+            return Err(Error::ConflictingVariable(name.clone()));
+        }
+    }
+
+    let mut out = String::new();
+    for (name, value) in collection.variables() {
+        out.push_str(&format!("@{} = {}\n", name, value));
+    }
+    for (path, saved) in requests {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&format!("### {}\n", path));
+        for (name, value) in &saved.variables {
+            out.push_str(&format!("@{} = {}\n", name, value));
+        }
+
+        let request = &saved.request;
+        let mut url = request.url().to_string();
+        if !request.params().is_empty() {
+            url.push(if url.contains('?') { '&' } else { '?' });
+            url.push_str(&query(request.params()));
+        }
+        out.push_str(&format!("{} {}\n", request.method(), url));
+        let mut headers = request.headers_as_sent();
+        if let Some(RequestBody::Multipart(_)) = request.body() {
+            let content_type = format!("multipart/form-data; boundary={}", BOUNDARY);
+            headers.append("Content-Type", content_type);
+        }
+        for (name, value) in headers.iter() {
+            out.push_str(&format!("{}: {}\n", name, value));
+        }
+        if let Some(body) = request.body() {
+            out.push('\n');
+            out.push_str(&body_text(body));
+            if !out.ends_with('\n') {
+                out.push('\n');
+            }
+        }
+    }
+    Ok(out)
+}
+
+fn body_text(body: &RequestBody) -> String {
+    match body {
+        RequestBody::Json(value) => serde_json::to_string_pretty(value).unwrap_or_default(),
+        RequestBody::Raw { content, .. } => content.clone(),
+        RequestBody::Form(pairs) => query(pairs),
+        RequestBody::Binary(BinaryBody::File(path)) => format!("< {}", path.display()),
+        RequestBody::Binary(BinaryBody::Bytes(bytes)) => {
+            String::from_utf8_lossy(bytes).into_owned()
+        }
+        RequestBody::Multipart(parts) => {
+            let mut out = String::new();
+            for part in parts {
+                out.push_str(&format!("--{}\n", BOUNDARY));
+                match part {
+                    MultipartPart::Text { name, value } => out.push_str(&format!(
+                        "Content-Disposition: form-data; name=\"{}\"\n\n{}\n",
+                        name, value
+                    )),
+                    MultipartPart::File {
+                        name,
+                        path,
+                        content_type,
+                    } => {
+                        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+                        out.push_str(&format!(
+                            "Content-Disposition: form-data; name=\"{}\"; filename=\"{}\"\n",
+                            name, file_name
+                        ));
+                        if let Some(content_type) = content_type {
+                            out.push_str(&format!("Content-Type: {}\n", content_type));
+                        }
+                        out.push_str(&format!("\n< {}\n", path.display()));
+                    }
+                }
+            }
+            out.push_str(&format!("--{}--\n", BOUNDARY));
+            out
+        }
+    }
+}
+
+/// `key=value` pairs joined with `&`, percent-encoded except for `{{variable}}` references
+fn query(pairs: &MultiMap) -> String {
+    let encode = |text: &str| {
+        let mut out = String::new();
+        for byte in text.bytes() {
+            match byte {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' => out.push(byte as char),
+                b'-' | b'.' | b'_' | b'~' | b'{' | b'}' | b'$' | b'(' | b')' | b',' | b':'
+                | b'/' => out.push(byte as char),
+                _ => out.push_str(&format!("%{:02X}", byte)),
+            }
+        }
+        out
+    };
+    pairs
+        .iter()
+        .map(|(key, value)| format!("{}={}", encode(key), encode(value)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidHeader(line) => {
+                write!(f, "line {}: expected a header as `name: value`", line)
+            }
+            Error::InvalidMethod { line, method } => {
+                write!(f, "line {}: invalid method `{}`", line, method)
+            }
+            Error::ConflictingVariable(name) => write!(
+                f,
+                "variable `{}` has different values in several places, a .http file defines it once",
+                name
+            ),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::{export, parse, Error};
+    use crate::{
+        body::{BinaryBody, MultipartPart, RequestBody},
+        collection::{Collection, SavedRequest},
+        echo::EchoServer,
+        multimap::MultiMap,
+        request::{Request, RequestMethod},
+        variables::VariableMap,
+    };
+    use serde_json::json;
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+    };
+
+    const FILE: &str = "\
+@host = https://shop.test
+@token = abc
+
+### auth/login
+POST {{host}}/login HTTP/1.1
+Content-Type: application/json
+
+{\"user\": \"{{$guid}}\"}
+
+> {%
+    client.global.set(\"token\", response.body.token);
+%}
+
+###
+# @name list
+GET {{host}}/orders
+    ?page=2
+    &n={{$randomInt 1 7}}
+Authorization: Bearer {{token}}
+// A comment between headers
+
+###
+https://shop.test/health
+
+### upload
+PUT {{host}}/files
+Content-Type: image/png
+
+< ./cat.png
+";
+
+    #[test]
+    fn parse_requests_and_variables() {
+        let file = parse(FILE, Path::new("api")).unwrap();
+        assert_eq!(
+            VariableMap::from([
+                ("host".to_string(), "https://shop.test".to_string()),
+                ("token".to_string(), "abc".to_string()),
+            ]),
+            file.variables
+        );
+        let names: Vec<_> = file.requests.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(vec!["auth/login", "list", "GET health", "upload"], names);
+
+        let login = &file.requests[0].saved.request;
+        assert_eq!(&RequestMethod::POST, login.method());
+        assert_eq!("{{host}}/login", login.url());
+        assert!(login.headers().is_empty());
+        assert_eq!(
+            Some(&RequestBody::Raw {
+                content: "{\"user\": \"{{$uuid}}\"}".to_string(),
+                content_type: "application/json".to_string(),
+            }),
+            login.body()
+        );
+
+        let list = &file.requests[1].saved.request;
+        assert_eq!("{{host}}/orders?page=2&n={{$randomInt(1,6)}}", list.url());
+        assert_eq!(
+            &MultiMap::from([("Authorization", "Bearer {{token}}")]),
+            list.headers()
+        );
+        assert_eq!(None, list.body());
+
+        assert_eq!(
+            Some(&RequestBody::Binary(BinaryBody::File(PathBuf::from(
+                "api/cat.png"
+            )))),
+            file.requests[3].saved.request.body()
+        );
+        assert_eq!(1, file.warnings.len());
+
+        assert_eq!(Some("list"), file.find("list").map(|r| r.name.as_str()));
+        assert_eq!(Some("list"), file.find("15").map(|r| r.name.as_str()));
+        assert_eq!(Some("auth/login"), file.find("4").map(|r| r.name.as_str()));
+        assert_eq!(None, file.find("2"));
+        assert_eq!(None, file.find("missing"));
+
+        let collection = file.to_collection("shop").unwrap();
+        assert!(collection.get("auth/login").is_some());
+        assert_eq!("abc", collection.variables()["token"]);
+    }
+
+    #[test]
+    fn only_titles_name_folders() {
+        let file = parse(
+            "GET http://x/api\n\
+             ###\nGET http://x/api/orders\n\
+             ### auth/login\nPOST http://x/login\n\
+             ###\n# @name reports/daily\nGET http://x/reports\n",
+            Path::new(""),
+        )
+        .unwrap();
+        assert_eq!(
+            Some("GET api/orders"),
+            file.find("3").map(|r| r.name.as_str())
+        );
+
+        let collection = file.to_collection("x").unwrap();
+        let paths: Vec<_> = collection
+            .requests()
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect();
+        assert_eq!(
+            vec!["GET api", "GET api-orders", "auth/login", "reports-daily"],
+            paths
+        );
+    }
+
+    #[test]
+    fn export_then_parse_back() {
+        let mut collection = Collection::new("shop");
+        collection
+            .variables_mut()
+            .insert("host".to_string(), "http://localhost".to_string());
+        let request = |method, url: &str, params, body| {
+            Request::new(body, MultiMap::new(), method, url.to_string(), params)
+        };
+        let mut create = SavedRequest::new(
+            "create",
+            request(
+                RequestMethod::POST,
+                "{{host}}/orders",
+                MultiMap::from([("dry run", "{{dry}}")]),
+                Some(RequestBody::Json(json!({"qty": 1}))),
+            ),
+        );
+        create
+            .variables
+            .insert("dry".to_string(), "true".to_string());
+        collection.add("orders", create).unwrap();
+        let parts = vec![
+            MultipartPart::Text {
+                name: "title".to_string(),
+                value: "cat".to_string(),
+            },
+            MultipartPart::File {
+                name: "photo".to_string(),
+                path: PathBuf::from("img/cat.png"),
+                content_type: Some("image/png".to_string()),
+            },
+        ];
+        collection
+            .add(
+                "",
+                SavedRequest::new(
+                    "upload",
+                    request(
+                        RequestMethod::PUT,
+                        "{{host}}/files",
+                        MultiMap::new(),
+                        Some(RequestBody::Multipart(parts.clone())),
+                    ),
+                ),
+            )
+            .unwrap();
+
+        let text = export(&collection).unwrap();
+        assert!(text.starts_with(
+            "@host = http://localhost\n\n### orders/create\n@dry = true\n\
+             POST {{host}}/orders?dry%20run={{dry}}\ncontent-type: application/json\n\n{\n  \"qty\": 1\n}\n"
+        ));
+
+        let file = parse(&text, Path::new("")).unwrap();
+        assert!(file.warnings.is_empty());
+        assert_eq!("true", file.variables["dry"]);
+        let create = &file.requests[0];
+        assert_eq!("orders/create", create.name);
+        assert_eq!(
+            Some(&RequestBody::Raw {
+                content: "{\n  \"qty\": 1\n}".to_string(),
+                content_type: "application/json".to_string(),
+            }),
+            create.saved.request.body()
+        );
+        assert_eq!(
+            Some(&RequestBody::Multipart(parts)),
+            file.requests[1].saved.request.body()
+        );
+
+        let mut retry = file.requests[0].saved.clone();
+        retry.name = "retry".to_string();
+        retry
+            .variables
+            .insert("dry".to_string(), "false".to_string());
+        collection.add("orders", retry).unwrap();
+        assert_eq!(
+            Err(Error::ConflictingVariable("dry".to_string())),
+            export(&collection)
+        );
+    }
+
+    #[tokio::test]
+    async fn read_files_from_the_directory_of_the_file() {
+        let dir = std::env::temp_dir().join(format!("asterios-http-{}", std::process::id()));
+        fs::create_dir_all(dir.join("fixtures")).unwrap();
+        fs::write(dir.join("fixtures/note.txt"), "hello").unwrap();
+        let server = EchoServer::start().unwrap();
+        let content = format!(
+            "POST {}\nContent-Type: text/plain\n\n< ./fixtures/note.txt\n",
+            server.url("/post")
+        );
+
+        let file = parse(&content, &dir).unwrap();
+        let res = file.requests[0].saved.request.send_request().await;
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!("hello", res.ok().unwrap().body()["data"]);
+    }
+
+    #[test]
+    fn report_the_line_of_errors() {
+        assert_eq!(
+            Err(Error::InvalidHeader(4)),
+            parse(
+                "###\nGET http://x\nAccept: */*\nnot a header\n",
+                Path::new("")
+            )
+        );
+        assert_eq!(
+            Err(Error::InvalidMethod {
+                line: 2,
+                method: "G(T".to_string()
+            }),
+            parse("# comment\nG(T http://x\n", Path::new(""))
+        );
+    }
+}
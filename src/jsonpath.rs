@@ -0,0 +1,228 @@
+use serde_json::Value;
+use std::{error, fmt};
+
+/// A parsed JSONPath expression.
+///
+/// Supports the common subset: the `$` root, `.name` and `['name']` children,
+/// `[n]` indexes (negative ones count from the end), `*` wildcards and `..`
+/// recursive descent. Filters and slices are not supported.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonPath {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Segment {
+    recursive: bool,
+    selector: Selector,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Selector {
+    Key(String),
+    Index(i64),
+    Wildcard,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error {
+    path: String,
+    position: usize,
+    reason: &'static str,
+}
+
+impl JsonPath {
+    pub fn parse(path: &str) -> Result<JsonPath, Error> {
+        let fail = |position, reason| Error {
+            path: path.to_string(),
+            position,
+            reason,
+        };
+        let chars: Vec<char> = path.trim().chars().collect();
+        if chars.first() != Some(&'$') {
+            return Err(fail(0, "expected `$` at the start"));
+        }
+
+        let mut segments = Vec::new();
+        let mut i = 1;
+        while i < chars.len() {
+            let recursive = chars[i..].starts_with(&['.', '.']);
+            match chars[i] {
+                '.' => {
+                    i += if recursive { 2 } else { 1 };
+                    if chars.get(i) == Some(&'[') {
+                        continue_bracket(&chars, &mut i, recursive, &mut segments)
+                            .map_err(|reason| fail(i, reason))?;
+                        continue;
+                    }
+                    let start = i;
+                    while i < chars.len() && !matches!(chars[i], '.' | '[') {
+                        i += 1;
+                    }
+                    let name: String = chars[start..i].iter().collect();
+                    let selector = match name.as_str() {
+                        "" => return Err(fail(start, "expected a name after `.`")),
+                        "*" => Selector::Wildcard,
+                        _ => Selector::Key(name),
+                    };
+                    segments.push(Segment {
+                        recursive,
+                        selector,
+                    });
+                }
+                '[' => continue_bracket(&chars, &mut i, false, &mut segments)
+                    .map_err(|reason| fail(i, reason))?,
+                _ => return Err(fail(i, "expected `.` or `[`")),
+            }
+        }
+        Ok(JsonPath { segments })
+    }
+
+    /// Every value matched by the path, in document order
+    pub fn query<'a>(&self, root: &'a Value) -> Vec<&'a Value> {
+        let mut nodes = vec![root];
+        for segment in &self.segments {
+            let mut next = Vec::new();
+            for node in nodes {
+                if segment.recursive {
+                    let mut descendants = Vec::new();
+                    collect_descendants(node, &mut descendants);
+                    for descendant in descendants {
+                        segment.selector.select(descendant, &mut next);
+                    }
+                } else {
+                    segment.selector.select(node, &mut next);
+                }
+            }
+            nodes = next;
+        }
+        nodes
+    }
+
+    /// First match of the path
+    pub fn first<'a>(&self, root: &'a Value) -> Option<&'a Value> {
+        self.query(root).into_iter().next()
+    }
+}
+
+/// Parses a `[...]` selector starting at `chars[*i]`, leaving `i` after the `]`
+fn continue_bracket(
+    chars: &[char],
+    i: &mut usize,
+    recursive: bool,
+    segments: &mut Vec<Segment>,
+) -> Result<(), &'static str> {
+    let close = chars[*i..]
+        .iter()
+        .position(|&c| c == ']')
+        .ok_or("missing `]`")?
+        + *i;
+    let inner: String = chars[*i + 1..close].iter().collect();
+    let inner = inner.trim();
+    let selector = if inner == "*" {
+        Selector::Wildcard
+    } else if let Some(key) = quoted(inner, '\'').or_else(|| quoted(inner, '"')) {
+        Selector::Key(key.to_string())
+    } else {
+        Selector::Index(
+            inner
+                .parse()
+                .map_err(|_| "expected an index, `*` or a quoted name")?,
+        )
+    };
+    segments.push(Segment {
+        recursive,
+        selector,
+    });
+    *i = close + 1;
+    Ok(())
+}
+
+fn quoted(s: &str, quote: char) -> Option<&str> {
+    s.strip_prefix(quote)?.strip_suffix(quote)
+}
+
+/// `value` itself then every value nested in it, depth-first
+fn collect_descendants<'a>(value: &'a Value, out: &mut Vec<&'a Value>) {
+    out.push(value);
+    match value {
+        Value::Array(items) => items.iter().for_each(|v| collect_descendants(v, out)),
+        Value::Object(map) => map.values().for_each(|v| collect_descendants(v, out)),
+        _ => {}
+    }
+}
+
+impl Selector {
+    fn select<'a>(&self, value: &'a Value, out: &mut Vec<&'a Value>) {
+        match (self, value) {
+            (Selector::Key(key), Value::Object(map)) => out.extend(map.get(key)),
+            (Selector::Index(index), Value::Array(items)) => {
+                let index = if *index < 0 {
+                    items.len() as i64 + index
+                } else {
+                    *index
+                };
+                out.extend(usize::try_from(index).ok().and_then(|i| items.get(i)));
+            }
+            (Selector::Wildcard, Value::Array(items)) => out.extend(items),
+            (Selector::Wildcard, Value::Object(map)) => out.extend(map.values()),
+            _ => {}
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid JSONPath `{}` at {}: {}",
+            self.path, self.position, self.reason
+        )
+    }
+}
+
+impl error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::JsonPath;
+    use serde_json::{json, Value};
+
+    fn query(path: &str, value: &Value) -> Vec<Value> {
+        JsonPath::parse(path)
+            .unwrap()
+            .query(value)
+            .into_iter()
+            .cloned()
+            .collect()
+    }
+
+    #[test]
+    fn select_children_and_indexes() {
+        let doc = json!({"data": {"items": [{"id": 1}, {"id": 2}, {"id": 3}], "a.b": true}});
+        assert_eq!(vec![doc.clone()], query("$", &doc));
+        assert_eq!(vec![json!(2)], query("$.data.items[1].id", &doc));
+        assert_eq!(vec![json!(3)], query("$.data.items[-1].id", &doc));
+        assert_eq!(vec![json!(true)], query("$['data'][\"a.b\"]", &doc));
+        assert_eq!(
+            vec![json!(1), json!(2), json!(3)],
+            query("$.data.items[*].id", &doc)
+        );
+        assert!(query("$.data.missing", &doc).is_empty());
+        assert!(query("$.data.items[7]", &doc).is_empty());
+    }
+
+    #[test]
+    fn descend_recursively() {
+        let doc = json!({"id": 0, "user": {"id": 1, "friends": [{"id": 2}]}});
+        assert_eq!(vec![json!(0), json!(1), json!(2)], query("$..id", &doc));
+        assert_eq!(vec![json!(2)], query("$..friends[0].id", &doc));
+    }
+
+    #[test]
+    fn reject_malformed_paths() {
+        for path in ["data.id", "$.", "$[1", "$[x]", "$id"] {
+            assert!(JsonPath::parse(path).is_err(), "{}", path);
+        }
+    }
+}
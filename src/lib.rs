@@ -0,0 +1,20 @@
+pub mod assertion;
+pub mod body;
+pub mod codegen;
+pub mod collection;
+pub mod curl;
+pub mod data;
+pub mod dynamic;
+pub mod echo;
+pub mod extract;
+pub mod har;
+pub mod http_file;
+pub mod jsonpath;
+pub mod multimap;
+pub mod openapi;
+pub mod postman;
+pub mod report;
+pub mod request;
+pub mod runner;
+pub mod session;
+pub mod variables;
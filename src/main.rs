@@ -1,5 +1,1219 @@
-mod request;
+use std::{
+    fs,
+    io::{self, Read, Write},
+    net::{IpAddr, SocketAddr},
+    path::{Path, PathBuf},
+    process::ExitCode,
+    time::Duration,
+};
 
-fn main() {
-    println!("Hello, world!");
+use asterios::{
+    assertion::{self, AssertionResult},
+    body::{BinaryBody, MultipartPart, RequestBody},
+    codegen::Language,
+    collection::{self, Collection, Format, SavedRequest},
+    curl,
+    data::{self, Rows},
+    dynamic::Generator,
+    echo::EchoServer,
+    extract, har, http_file, openapi, postman,
+    report::Reporter,
+    request::{Error, HeaderCase, Request, RequestMethod, Response},
+    runner::{self, Outcome, RequestResult, RunOptions, RunSummary},
+    session::Session,
+    variables::{Environment, VariableMap, Variables},
+};
+use clap::{Args, Parser, Subcommand};
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "asterios",
+    version,
+    about = "A command-line API client",
+    after_help = EXIT_CODES
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Send a single request and print the response
+    Send(Box<SendArgs>),
+    /// Run a local server that reflects requests as JSON, like postman-echo
+    EchoServer(EchoServerArgs),
+    /// Send saved requests from a collection and print the responses
+    Run(RunArgs),
+    /// List the requests saved in a collection
+    List(ListArgs),
+    /// Run every request of a collection in order, with a pass/fail summary
+    RunCollection(RunCollectionArgs),
+    /// Run the requests of a .http file, or one of them by name or line number
+    RunHttp(RunHttpArgs),
+    /// Bring requests from other tools into a collection
+    #[command(subcommand)]
+    Import(ImportCommand),
+    /// Write a collection out in another tool's format
+    #[command(subcommand)]
+    Export(ExportCommand),
+    /// Print a saved request as code that sends it, with variables resolved
+    Codegen(CodegenArgs),
+}
+
+#[derive(Args, Debug)]
+struct CodegenArgs {
+    /// Collection file, .json or .toml
+    collection: PathBuf,
+    /// Slash-separated path of the request, e.g. `users/create`
+    request: String,
+    /// Target: curl, httpie, python, javascript, go or rust
+    #[arg(long, default_value = "curl")]
+    lang: Language,
+    #[command(flatten)]
+    variables: VariableArgs,
+}
+
+#[derive(Subcommand, Debug)]
+enum ImportCommand {
+    /// A curl command line, e.g. from the browser's "Copy as cURL"
+    Curl(ImportCurlArgs),
+    /// A Postman v2.1 collection export, with its environments
+    Postman(ImportPostmanArgs),
+    /// A HAR capture, e.g. saved from the network tab of browser devtools
+    Har(ImportHarArgs),
+    /// An OpenAPI 3.0/3.1 or Swagger 2.0 document, with its servers as environments
+    Openapi(ImportOpenapiArgs),
+    /// A .http file of the VS Code REST Client or JetBrains HTTP client
+    Http(ImportHttpArgs),
+}
+
+#[derive(Subcommand, Debug)]
+enum ExportCommand {
+    /// A .http file for the VS Code REST Client or JetBrains HTTP client
+    Http(ExportHttpArgs),
+}
+
+#[derive(Args, Debug)]
+struct ImportHttpArgs {
+    /// Requests file, .http or .rest
+    file: PathBuf,
+    /// Collection file to write, .json or .toml
+    #[arg(short, long, value_name = "FILE")]
+    output: PathBuf,
+}
+
+#[derive(Args, Debug)]
+struct ExportHttpArgs {
+    /// Collection file, .json or .toml
+    collection: PathBuf,
+    /// File to write, stdout when omitted
+    #[arg(short, long, value_name = "FILE")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct ImportPostmanArgs {
+    /// Postman collection export, .json
+    file: PathBuf,
+    /// Collection file to write, .json or .toml
+    #[arg(short, long, value_name = "FILE")]
+    output: PathBuf,
+    /// Postman environment export to add to the collection, can be repeated
+    #[arg(long = "env", value_name = "FILE")]
+    environments: Vec<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct ImportHarArgs {
+    /// HAR 1.2 file, .har or .json
+    file: PathBuf,
+    /// Collection file to write, .json or .toml
+    #[arg(short, long, value_name = "FILE")]
+    output: PathBuf,
+}
+
+#[derive(Args, Debug)]
+struct ImportOpenapiArgs {
+    /// OpenAPI or Swagger document, .yaml, .yml or .json
+    file: PathBuf,
+    /// Collection file to write, .json or .toml
+    #[arg(short, long, value_name = "FILE")]
+    output: PathBuf,
+}
+
+#[derive(Args, Debug)]
+struct ImportCurlArgs {
+    /// The curl command, read from stdin when omitted or `-`
+    command: Option<String>,
+    /// Collection to save the request into, created when missing.
+    /// Without one, the request is printed as JSON.
+    #[arg(long, value_name = "FILE")]
+    collection: Option<PathBuf>,
+    /// Name of the saved request
+    #[arg(long, default_value = "imported")]
+    name: String,
+    /// Folder of the saved request, created when missing
+    #[arg(long, default_value = "")]
+    folder: String,
+}
+
+#[derive(Args, Debug)]
+struct RunArgs {
+    /// Collection file, .json or .toml
+    collection: PathBuf,
+    /// Slash-separated paths of the requests, e.g. `auth/login orders/create`.
+    /// They run in order, values extracted by one are visible to the next.
+    #[arg(required = true)]
+    requests: Vec<String>,
+    /// Write values extracted into the environment back to where it was loaded from
+    #[arg(long)]
+    save_env: bool,
+    #[command(flatten)]
+    variables: VariableArgs,
+    #[command(flatten)]
+    session: SessionArgs,
+}
+
+#[derive(Args, Debug)]
+struct RunCollectionArgs {
+    /// Collection file, .json or .toml
+    collection: PathBuf,
+    /// Only run the requests under this folder
+    #[arg(long)]
+    folder: Option<String>,
+    /// Stop at the first failed request
+    #[arg(long)]
+    bail: bool,
+    /// Pause between two requests
+    #[arg(long, value_name = "MS", default_value_t = 0)]
+    delay_ms: u64,
+    /// Run the whole selection this many times, defaults to one per data row
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    iterations: Option<u64>,
+    /// Data file, .csv with a header line or .json array of objects.
+    /// Each row drives one iteration, its columns are exposed as variables.
+    #[arg(long, value_name = "FILE")]
+    data: Option<PathBuf>,
+    /// Report as `junit`, `tap`, `json`, `html` or `har`, written to stdout or to a file with
+    /// `format:path` like `junit:out.xml`, can be repeated
+    #[arg(long = "reporter", value_name = "FORMAT[:PATH]")]
+    reporters: Vec<Reporter>,
+    #[command(flatten)]
+    variables: VariableArgs,
+    #[command(flatten)]
+    session: SessionArgs,
+}
+
+#[derive(Args, Debug)]
+struct RunHttpArgs {
+    /// Requests file, .http or .rest
+    file: PathBuf,
+    /// Name or line number of the request to send and print.
+    /// Without one, every request runs in order with a pass/fail summary.
+    request: Option<String>,
+    /// Stop at the first failed request
+    #[arg(long)]
+    bail: bool,
+    /// Report as `junit`, `tap`, `json`, `html` or `har` when running every request,
+    /// written to stdout or to a file with `format:path`, can be repeated
+    #[arg(long = "reporter", value_name = "FORMAT[:PATH]")]
+    reporters: Vec<Reporter>,
+    #[command(flatten)]
+    variables: VariableArgs,
+    #[command(flatten)]
+    session: SessionArgs,
+}
+
+#[derive(Args, Debug)]
+struct ListArgs {
+    /// Collection file, .json or .toml
+    collection: PathBuf,
+    /// Only list requests under this folder
+    #[arg(long)]
+    folder: Option<String>,
+}
+
+#[derive(Args, Debug)]
+struct SendArgs {
+    /// HTTP method, e.g. GET, POST or a custom verb like PURGE
+    method: RequestMethod,
+    url: String,
+    /// Header as `name:value`, can be repeated
+    #[arg(short = 'H', long = "header", value_parser = parse_header)]
+    headers: Vec<(String, String)>,
+    /// Query parameter as `key=value`, can be repeated
+    #[arg(short = 'q', long = "query", value_parser = parse_param)]
+    params: Vec<(String, String)>,
+    /// How header names are rewritten: verbatim, lowercase, kebab or title
+    #[arg(long, default_value = "kebab")]
+    header_case: HeaderCase,
+    #[command(flatten)]
+    body: BodyArgs,
+    #[command(flatten)]
+    variables: VariableArgs,
+    #[command(flatten)]
+    session: SessionArgs,
+}
+
+#[derive(Args, Debug)]
+struct EchoServerArgs {
+    #[arg(long, default_value = "127.0.0.1")]
+    host: IpAddr,
+    /// Port to listen on, 0 picks a free one
+    #[arg(short, long, default_value_t = 8080)]
+    port: u16,
+}
+
+#[derive(Args, Debug)]
+struct SessionArgs {
+    /// Give up on a request after this many seconds
+    #[arg(long, value_name = "SECONDS")]
+    timeout: Option<f64>,
+    /// Proxy for every request, e.g. `http://localhost:8080`
+    #[arg(long)]
+    proxy: Option<String>,
+    /// Accept invalid TLS certificates
+    #[arg(short = 'k', long)]
+    insecure: bool,
+    /// Trust an extra PEM encoded root certificate
+    #[arg(long, value_name = "FILE")]
+    cacert: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct VariableArgs {
+    /// Environment to use: a name defined in the collection, or a .json/.toml file
+    #[arg(short = 'e', long = "env")]
+    env: Option<String>,
+    /// Global variables file, a .json or .toml map of names to values
+    #[arg(long, value_name = "FILE")]
+    globals: Option<PathBuf>,
+    /// Variable as `key=value`, overrides every other scope, can be repeated
+    #[arg(long = "var", value_parser = parse_param)]
+    vars: Vec<(String, String)>,
+    /// Seed for `{{$random...}}` and `{{$uuid}}` values, for reproducible runs
+    #[arg(long)]
+    seed: Option<u64>,
+}
+
+#[derive(Args, Debug)]
+#[group(multiple = false)]
+struct BodyArgs {
+    /// Raw text body: inline text, `@file` to read a file or `-` to read stdin
+    #[arg(short = 'd', long = "data")]
+    data: Option<String>,
+    /// JSON body: inline JSON, `@file` to read a file or `-` to read stdin
+    #[arg(long)]
+    json: Option<String>,
+    /// Url-encoded form field as `key=value`, can be repeated
+    #[arg(long = "form", value_parser = parse_param)]
+    form: Vec<(String, String)>,
+    /// Multipart field as `name=value`, or `name=@path` for a file, can be repeated
+    #[arg(short = 'F', long = "multipart", value_parser = parse_param)]
+    multipart: Vec<(String, String)>,
+    /// Binary body read from a file path
+    #[arg(long = "data-binary")]
+    binary: Option<PathBuf>,
+}
+
+fn parse_header(s: &str) -> Result<(String, String), String> {
+    let (name, value) = s
+        .split_once(':')
+        .ok_or_else(|| format!("expected `name:value`, got `{}`", s))?;
+    Ok((name.trim().to_string(), value.trim().to_string()))
+}
+
+fn parse_param(s: &str) -> Result<(String, String), String> {
+    let (key, value) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected `key=value`, got `{}`", s))?;
+    Ok((key.to_string(), value.to_string()))
+}
+
+fn read_text(body: &str) -> io::Result<String> {
+    if body == "-" {
+        let mut buf = String::new();
+        io::stdin().read_to_string(&mut buf)?;
+        Ok(buf)
+    } else if let Some(path) = body.strip_prefix('@') {
+        fs::read_to_string(path)
+    } else {
+        Ok(body.to_string())
+    }
+}
+
+impl BodyArgs {
+    fn into_body(self) -> io::Result<Option<RequestBody>> {
+        if let Some(data) = self.data {
+            return Ok(Some(RequestBody::Raw {
+                content: read_text(&data)?,
+                content_type: "text/plain".to_string(),
+            }));
+        }
+        if let Some(json) = self.json {
+            let value = serde_json::from_str(&read_text(&json)?)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            return Ok(Some(RequestBody::Json(value)));
+        }
+        if !self.form.is_empty() {
+            return Ok(Some(RequestBody::Form(self.form.into_iter().collect())));
+        }
+        if !self.multipart.is_empty() {
+            let parts = self
+                .multipart
+                .into_iter()
+                .map(|(name, value)| match value.strip_prefix('@') {
+                    Some(path) => MultipartPart::File {
+                        name,
+                        path: PathBuf::from(path),
+                        content_type: None,
+                    },
+                    None => MultipartPart::Text { name, value },
+                })
+                .collect();
+            return Ok(Some(RequestBody::Multipart(parts)));
+        }
+        Ok(self
+            .binary
+            .map(|path| RequestBody::Binary(BinaryBody::File(path))))
+    }
+}
+
+/// Shown by `--help`, keep in sync with `exit_code`
+const EXIT_CODES: &str = "\
+Exit codes:
+  0   success
+  1   any other failure, e.g. a collection, variable or import error, or a failed run
+  2   invalid command line arguments
+  3   invalid url
+  6   could not resolve the host
+  7   connection refused
+  26  could not read a file to send
+  28  timed out
+  35  TLS failure
+  43  invalid header
+  47  too many redirects
+  56  any other transport failure
+  61  could not decode the response body
+  64  invalid method, or a body on a method that does not allow one";
+
+/// Exit codes follow curl's where a category exists, so scripts can share handling
+fn exit_code(error: &Error) -> u8 {
+    match error {
+        // curl has no equivalent, and 2 is what clap exits with on a bad flag
+        Error::InvalidMethod(_) | Error::BodyNotAllowed(_) => 64,
+        Error::InvalidUrl { .. } => 3,
+        Error::Dns(_) => 6,
+        Error::ConnectionRefused(_) => 7,
+        Error::Io(_) => 26,
+        Error::Timeout(_) => 28,
+        Error::Tls(_) => 35,
+        Error::InvalidHeader { .. } => 43,
+        Error::RedirectLoop(_) => 47,
+        Error::BodyDecode(_) => 61,
+        Error::Http(_) => 56,
+    }
+}
+
+/// Prints the error followed by its source chain
+fn report(error: &dyn std::error::Error) {
+    eprintln!("error: {}", error);
+    let mut source = error.source();
+    while let Some(cause) = source {
+        eprintln!("  caused by: {}", cause);
+        source = cause.source();
+    }
+}
+
+impl VariableArgs {
+    /// Every layer but the collection's, which `run` adds
+    fn load(&self, collection: Option<&Collection>) -> Result<Variables, collection::Error> {
+        let mut variables = match self.seed {
+            Some(seed) => Variables::new().with_generator(Generator::seeded(seed)),
+            None => Variables::new(),
+        };
+        if let Some(path) = &self.globals {
+            let content = fs::read_to_string(path).map_err(collection::Error::Io)?;
+            variables.globals = Format::from_path(path)?.parse(&content)?;
+        }
+        if let Some(env) = &self.env {
+            variables.environment = match collection.and_then(|c| c.environment(env)) {
+                Some(environment) => Some(environment.clone()),
+                None => Some(Environment::load(env)?),
+            };
+        }
+        variables.overrides = self.vars.iter().cloned().collect();
+        Ok(variables)
+    }
+}
+
+impl SessionArgs {
+    fn into_session(self) -> Result<Session, Error> {
+        let mut builder = Session::builder().accept_invalid_certs(self.insecure);
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(Duration::from_secs_f64(timeout));
+        }
+        if let Some(proxy) = self.proxy {
+            builder = builder.proxy(proxy);
+        }
+        if let Some(cacert) = self.cacert {
+            builder = builder.root_certificate_file(cacert)?;
+        }
+        builder.build()
+    }
+}
+
+/// Prints the status line, headers and body, JSON is pretty-printed
+fn print_response(res: &Response) -> io::Result<()> {
+    let mut stdout = io::stdout().lock();
+    writeln!(stdout, "{}", res.status())?;
+    for (name, value) in res.headers() {
+        writeln!(stdout, "{}: {}", name, value)?;
+    }
+    writeln!(stdout)?;
+    let body = res.body();
+    match body.json() {
+        Some(json) => writeln!(stdout, "{}", serde_json::to_string_pretty(json)?),
+        None if body.is_text() => writeln!(stdout, "{}", body.text()),
+        // Binary payloads are written untouched, like curl does
+        None => stdout.write_all(body.bytes()),
+    }
+}
+
+/// Assertion outcomes go to stderr, keeping stdout for the response
+fn print_results(results: &[AssertionResult]) {
+    for result in results {
+        if result.passed {
+            eprintln!("  pass  {}", result.name);
+        } else {
+            eprintln!(
+                "  FAIL  {}: expected {}, got {}",
+                result.name, result.expected, result.actual
+            );
+        }
+    }
+}
+
+async fn send(args: SendArgs) -> ExitCode {
+    let session = match args.session.into_session() {
+        Ok(session) => session,
+        Err(e) => {
+            report(&e);
+            return ExitCode::from(exit_code(&e));
+        }
+    };
+    let body = match args.body.into_body() {
+        Ok(body) => body,
+        Err(e) => {
+            eprintln!("error: could not read body: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let req = Request::new(
+        body,
+        args.headers.into_iter().collect(),
+        args.method,
+        args.url,
+        args.params.into_iter().collect(),
+    )
+    .with_header_case(args.header_case);
+
+    let variables = match args.variables.load(None) {
+        Ok(variables) => variables,
+        Err(e) => {
+            report(&e);
+            return ExitCode::FAILURE;
+        }
+    };
+    match variables.scope(&VariableMap::new()).resolve(&req) {
+        Ok(req) => send_and_print(&session, &req).await,
+        Err(e) => {
+            report(&e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn send_and_print(session: &Session, req: &Request) -> ExitCode {
+    match session.send(req).await {
+        Ok(res) => match print_response(&res) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("error: could not write response: {}", e);
+                ExitCode::FAILURE
+            }
+        },
+        Err(e) => {
+            report(&e);
+            ExitCode::from(exit_code(&e))
+        }
+    }
+}
+
+async fn run(args: RunArgs) -> ExitCode {
+    let mut collection = match Collection::load(&args.collection) {
+        Ok(collection) => collection,
+        Err(e) => {
+            report(&e);
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Some(path) = args
+        .requests
+        .iter()
+        .find(|path| collection.get(path).is_none())
+    {
+        eprintln!("error: no request at `{}`", path);
+        return ExitCode::FAILURE;
+    }
+    let session = match args.session.into_session() {
+        Ok(session) => session,
+        Err(e) => {
+            report(&e);
+            return ExitCode::from(exit_code(&e));
+        }
+    };
+    let mut variables = match args.variables.load(Some(&collection)) {
+        Ok(variables) => variables,
+        Err(e) => {
+            report(&e);
+            return ExitCode::FAILURE;
+        }
+    };
+    variables.collection = collection.variables().clone();
+
+    // Requests run in the given order, each one seeing what the previous ones extracted
+    let mut failed = false;
+    for path in &args.requests {
+        let saved = collection.get(path).expect("paths were checked above");
+        let req = match variables.scope(&saved.variables).resolve(&saved.request) {
+            Ok(req) => req,
+            Err(e) => {
+                report(&e);
+                return ExitCode::FAILURE;
+            }
+        };
+        let res = match session.send(&req).await {
+            Ok(res) => res,
+            Err(e) => {
+                report(&e);
+                return ExitCode::from(exit_code(&e));
+            }
+        };
+        if let Err(e) = print_response(&res) {
+            eprintln!("error: could not write response: {}", e);
+            return ExitCode::FAILURE;
+        }
+        let results = assertion::check_all(&saved.assertions, &res);
+        print_results(&results);
+        failed |= results.iter().any(|r| !r.passed);
+        if let Err(e) = extract::apply(&saved.extract, &res, &mut variables) {
+            report(&e);
+            return ExitCode::FAILURE;
+        }
+    }
+
+    if args.save_env {
+        let source = args.variables.env.as_deref();
+        if let Err(e) = save_environment(&args.collection, &mut collection, source, variables) {
+            report(&e);
+            return ExitCode::FAILURE;
+        }
+    }
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Writes the environment back where it was loaded from, the collection or its own file
+fn save_environment(
+    path: &Path,
+    collection: &mut Collection,
+    source: Option<&str>,
+    variables: Variables,
+) -> Result<(), collection::Error> {
+    let (Some(environment), Some(source)) = (variables.environment, source) else {
+        return Ok(());
+    };
+    if collection.environment(source).is_some() {
+        collection.set_environment(environment);
+        collection.save(path)
+    } else {
+        environment.save(source)
+    }
+}
+
+async fn run_collection(args: RunCollectionArgs) -> ExitCode {
+    let collection = match Collection::load(&args.collection) {
+        Ok(collection) => collection,
+        Err(e) => {
+            report(&e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let session = match args.session.into_session() {
+        Ok(session) => session,
+        Err(e) => {
+            report(&e);
+            return ExitCode::from(exit_code(&e));
+        }
+    };
+    let mut variables = match args.variables.load(Some(&collection)) {
+        Ok(variables) => variables,
+        Err(e) => {
+            report(&e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let data = match &args.data {
+        Some(path) => match data::load(path) {
+            Ok(rows) => rows,
+            Err(e) => {
+                report(&e);
+                return ExitCode::FAILURE;
+            }
+        },
+        None => Rows::new(),
+    };
+    let options = RunOptions {
+        folder: args.folder,
+        bail: args.bail,
+        delay: Duration::from_millis(args.delay_ms),
+        iterations: args
+            .iterations
+            .map_or(data.len().max(1), |iterations| iterations as usize),
+        data,
+    };
+    run_and_report(
+        &collection,
+        &session,
+        &mut variables,
+        &options,
+        &args.reporters,
+    )
+    .await
+}
+
+/// Runs `collection`, printing progress and a summary, then writes the reports
+async fn run_and_report(
+    collection: &Collection,
+    session: &Session,
+    variables: &mut Variables,
+    options: &RunOptions,
+    reporters: &[Reporter],
+) -> ExitCode {
+    // A report on stdout must stay parseable, so progress moves to stderr
+    let mut console: Box<dyn Write> = if reporters.iter().any(|r| r.path.is_none()) {
+        Box::new(io::stderr())
+    } else {
+        Box::new(io::stdout())
+    };
+    let show_iteration = options.iterations > 1;
+    let summary = match runner::run(collection, session, variables, options, |result| {
+        print_request_result(&mut console, result, show_iteration)
+    })
+    .await
+    {
+        Ok(summary) => summary,
+        Err(e) => {
+            report(&e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    print_summary(&mut console, &summary);
+    for reporter in reporters {
+        if let Err(e) = reporter.write(&summary) {
+            eprintln!("error: could not write report: {}", e);
+            return ExitCode::FAILURE;
+        }
+    }
+    if summary.passed() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+async fn run_http(args: RunHttpArgs) -> ExitCode {
+    let dir = args.file.parent().unwrap_or(Path::new(""));
+    let Some(file) = import_file(&args.file, |content| http_file::parse(content, dir)) else {
+        return ExitCode::FAILURE;
+    };
+    for warning in &file.warnings {
+        eprintln!("warning: {}", warning);
+    }
+    let session = match args.session.into_session() {
+        Ok(session) => session,
+        Err(e) => {
+            report(&e);
+            return ExitCode::from(exit_code(&e));
+        }
+    };
+    // A .http file has no environments
+    let mut variables = match args.variables.load(None) {
+        Ok(variables) => variables,
+        Err(e) => {
+            report(&e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let Some(selector) = &args.request else {
+        let name = args.file.file_stem().unwrap_or_default().to_string_lossy();
+        let collection = match file.to_collection(&name) {
+            Ok(collection) => collection,
+            Err(e) => {
+                report(&e);
+                return ExitCode::FAILURE;
+            }
+        };
+        let options = RunOptions {
+            bail: args.bail,
+            ..RunOptions::default()
+        };
+        return run_and_report(
+            &collection,
+            &session,
+            &mut variables,
+            &options,
+            &args.reporters,
+        )
+        .await;
+    };
+    let Some(request) = file.find(selector) else {
+        eprintln!(
+            "error: no request named or at line `{}` in {}",
+            selector,
+            args.file.display()
+        );
+        return ExitCode::FAILURE;
+    };
+    variables.collection = file.variables.clone();
+    let req = match variables
+        .scope(&request.saved.variables)
+        .resolve(&request.saved.request)
+    {
+        Ok(req) => req,
+        Err(e) => {
+            report(&e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let res = match session.send(&req).await {
+        Ok(res) => res,
+        Err(e) => {
+            report(&e);
+            return ExitCode::from(exit_code(&e));
+        }
+    };
+    if let Err(e) = print_response(&res) {
+        eprintln!("error: could not write response: {}", e);
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
+
+/// One line per request, followed by its failed assertions.
+/// Console write errors are ignored, they must not abort the run.
+fn print_request_result(out: &mut dyn Write, result: &RequestResult, show_iteration: bool) {
+    let label = if result.passed() { "PASS" } else { "FAIL" };
+    let iteration = if show_iteration {
+        format!("[{}] ", result.iteration + 1)
+    } else {
+        String::new()
+    };
+    let _ = match &result.outcome {
+        Outcome::Response {
+            status, elapsed, ..
+        } => {
+            let passed = result.assertions.iter().filter(|a| a.passed).count();
+            writeln!(
+                out,
+                "{}  {}{}  {} {}  {}  {}ms  {}/{} assertions",
+                label,
+                iteration,
+                result.path,
+                result.method,
+                result.url,
+                status,
+                elapsed.as_millis(),
+                passed,
+                result.assertions.len()
+            )
+        }
+        Outcome::Error(error) => writeln!(
+            out,
+            "{}  {}{}  {} {}  error: {}",
+            label, iteration, result.path, result.method, result.url, error
+        ),
+    };
+    for assertion in result.assertions.iter().filter(|a| !a.passed) {
+        let _ = writeln!(
+            out,
+            "      {}: expected {}, got {}",
+            assertion.name, assertion.expected, assertion.actual
+        );
+    }
+}
+
+fn print_summary(out: &mut dyn Write, summary: &RunSummary) {
+    let assertions = summary.assertions().count();
+    let failed_assertions = summary.assertions().filter(|a| !a.passed).count();
+    let _ = writeln!(out);
+    if summary.bailed {
+        let _ = writeln!(out, "Stopped at the first failure (--bail)");
+    }
+    let _ = writeln!(
+        out,
+        "{} requests, {} failed; {} assertions, {} failed; {}ms",
+        summary.results.len(),
+        summary.failed_requests(),
+        assertions,
+        failed_assertions,
+        summary.elapsed.as_millis()
+    );
+}
+
+fn list(args: ListArgs) -> ExitCode {
+    let collection = match Collection::load(&args.collection) {
+        Ok(collection) => collection,
+        Err(e) => {
+            report(&e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let requests = match &args.folder {
+        Some(folder) => match collection.requests_in(folder) {
+            Ok(requests) => requests,
+            Err(e) => {
+                report(&e);
+                return ExitCode::FAILURE;
+            }
+        },
+        None => collection.requests(),
+    };
+
+    let width = requests
+        .iter()
+        .map(|(path, _)| path.len())
+        .max()
+        .unwrap_or(0);
+    for (path, saved) in requests {
+        println!(
+            "{:width$}  {:7} {}",
+            path,
+            saved.request.method().as_str(),
+            saved.request.url(),
+            width = width
+        );
+    }
+    ExitCode::SUCCESS
+}
+
+fn import_curl(args: ImportCurlArgs) -> ExitCode {
+    let command = match args.command.as_deref().unwrap_or("-") {
+        "-" => read_text("-"),
+        command => Ok(command.to_string()),
+    };
+    let command = match command {
+        Ok(command) => command,
+        Err(e) => {
+            report(&e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let import = match curl::parse(&command) {
+        Ok(import) => import,
+        Err(e) => {
+            report(&e);
+            return ExitCode::FAILURE;
+        }
+    };
+    for warning in &import.warnings {
+        eprintln!("warning: {}", warning);
+    }
+    if import.insecure {
+        eprintln!("warning: `-k` is not saved with the request, pass `--insecure` when sending it");
+    }
+
+    let Some(path) = args.collection else {
+        return match serde_json::to_string_pretty(&import.request) {
+            Ok(json) => {
+                println!("{}", json);
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                report(&e);
+                ExitCode::FAILURE
+            }
+        };
+    };
+    let collection = match path.exists() {
+        true => Collection::load(&path),
+        false => Ok(Collection::new(
+            path.file_stem().unwrap_or_default().to_string_lossy(),
+        )),
+    };
+    let saved = SavedRequest::new(args.name, import.request);
+    let result = collection.and_then(|mut collection| {
+        collection.add(&args.folder, saved)?;
+        collection.save(&path)
+    });
+    if let Err(e) = result {
+        report(&e);
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
+
+fn codegen(args: CodegenArgs) -> ExitCode {
+    let collection = match Collection::load(&args.collection) {
+        Ok(collection) => collection,
+        Err(e) => {
+            report(&e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let Some(saved) = collection.get(&args.request) else {
+        eprintln!("error: no request at `{}`", args.request);
+        return ExitCode::FAILURE;
+    };
+    let mut variables = match args.variables.load(Some(&collection)) {
+        Ok(variables) => variables,
+        Err(e) => {
+            report(&e);
+            return ExitCode::FAILURE;
+        }
+    };
+    variables.collection = collection.variables().clone();
+    match variables.scope(&saved.variables).resolve(&saved.request) {
+        Ok(req) => {
+            print!("{}", args.lang.render(&req));
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            report(&e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Reads `path` and converts it with `parse`, reporting any failure
+fn import_file<T, E: std::error::Error>(
+    path: &Path,
+    parse: impl Fn(&str) -> Result<T, E>,
+) -> Option<T> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => {
+            report(&e);
+            return None;
+        }
+    };
+    match parse(&content) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            report(&e);
+            None
+        }
+    }
+}
+
+fn import_postman(args: ImportPostmanArgs) -> ExitCode {
+    let Some(postman::Import {
+        mut collection,
+        mut warnings,
+    }) = import_file(&args.file, postman::parse_collection)
+    else {
+        return ExitCode::FAILURE;
+    };
+    for path in &args.environments {
+        let Some(import) = import_file(path, postman::parse_environment) else {
+            return ExitCode::FAILURE;
+        };
+        warnings.extend(import.warnings);
+        collection.set_environment(import.environment);
+    }
+
+    for warning in &warnings {
+        eprintln!("warning: {}", warning);
+    }
+    if let Err(e) = collection.save(&args.output) {
+        report(&e);
+        return ExitCode::FAILURE;
+    }
+    eprintln!(
+        "Imported {} requests and {} environments into {}",
+        collection.requests().len(),
+        collection.environments().len(),
+        args.output.display()
+    );
+    ExitCode::SUCCESS
+}
+
+fn import_har(args: ImportHarArgs) -> ExitCode {
+    let Some(har::Import {
+        collection,
+        warnings,
+    }) = import_file(&args.file, har::parse)
+    else {
+        return ExitCode::FAILURE;
+    };
+    for warning in &warnings {
+        eprintln!("warning: {}", warning);
+    }
+    if let Err(e) = collection.save(&args.output) {
+        report(&e);
+        return ExitCode::FAILURE;
+    }
+    eprintln!(
+        "Imported {} requests into {}",
+        collection.requests().len(),
+        args.output.display()
+    );
+    ExitCode::SUCCESS
+}
+
+fn import_openapi(args: ImportOpenapiArgs) -> ExitCode {
+    let Some(openapi::Import {
+        collection,
+        warnings,
+    }) = import_file(&args.file, openapi::parse)
+    else {
+        return ExitCode::FAILURE;
+    };
+    for warning in &warnings {
+        eprintln!("warning: {}", warning);
+    }
+    if let Err(e) = collection.save(&args.output) {
+        report(&e);
+        return ExitCode::FAILURE;
+    }
+    eprintln!(
+        "Imported {} requests and {} environments into {}",
+        collection.requests().len(),
+        collection.environments().len(),
+        args.output.display()
+    );
+    ExitCode::SUCCESS
+}
+
+fn import_http(args: ImportHttpArgs) -> ExitCode {
+    let dir = args.file.parent().unwrap_or(Path::new(""));
+    let Some(file) = import_file(&args.file, |content| http_file::parse(content, dir)) else {
+        return ExitCode::FAILURE;
+    };
+    for warning in &file.warnings {
+        eprintln!("warning: {}", warning);
+    }
+    let name = args.file.file_stem().unwrap_or_default().to_string_lossy();
+    let result = file
+        .to_collection(&name)
+        .and_then(|collection| collection.save(&args.output).map(|()| collection));
+    match result {
+        Ok(collection) => {
+            eprintln!(
+                "Imported {} requests into {}",
+                collection.requests().len(),
+                args.output.display()
+            );
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            report(&e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn export_http(args: ExportHttpArgs) -> ExitCode {
+    let collection = match Collection::load(&args.collection) {
+        Ok(collection) => collection,
+        Err(e) => {
+            report(&e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let content = match http_file::export(&collection) {
+        Ok(content) => content,
+        Err(e) => {
+            report(&e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let written = match &args.output {
+        Some(path) => fs::write(path, content),
+        None => io::stdout().write_all(content.as_bytes()),
+    };
+    match written {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            report(&e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn echo_server(args: EchoServerArgs) -> ExitCode {
+    let server = match EchoServer::bind(SocketAddr::new(args.host, args.port)) {
+        Ok(server) => server,
+        Err(e) => {
+            report(&e);
+            return ExitCode::FAILURE;
+        }
+    };
+    println!("Echo server listening on {}", server.url("/"));
+    if let Err(e) = tokio::signal::ctrl_c().await {
+        report(&e);
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    match cli.command {
+        Command::Send(args) => send(*args).await,
+        Command::EchoServer(args) => echo_server(args).await,
+        Command::Run(args) => run(args).await,
+        Command::List(args) => list(args),
+        Command::RunCollection(args) => run_collection(args).await,
+        Command::RunHttp(args) => run_http(args).await,
+        Command::Import(ImportCommand::Curl(args)) => import_curl(args),
+        Command::Import(ImportCommand::Postman(args)) => import_postman(args),
+        Command::Import(ImportCommand::Har(args)) => import_har(args),
+        Command::Import(ImportCommand::Openapi(args)) => import_openapi(args),
+        Command::Import(ImportCommand::Http(args)) => import_http(args),
+        Command::Export(ExportCommand::Http(args)) => export_http(args),
+        Command::Codegen(args) => codegen(args),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_header, parse_param};
+
+    #[test]
+    fn parse_header_splits_on_first_colon() {
+        assert_eq!(
+            ("Authorization".to_string(), "Bearer a:b".to_string()),
+            parse_header("Authorization: Bearer a:b").unwrap()
+        );
+        assert!(parse_header("no-colon").is_err());
+    }
+
+    #[test]
+    fn parse_param_splits_on_first_equals() {
+        assert_eq!(
+            ("q".to_string(), "a=b".to_string()),
+            parse_param("q=a=b").unwrap()
+        );
+        assert!(parse_param("novalue").is_err());
+    }
 }
@@ -0,0 +1,245 @@
+use serde::{
+    de::{MapAccess, SeqAccess, Visitor},
+    ser::{SerializeMap, SerializeSeq},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use std::{collections::HashSet, fmt};
+
+/// An insertion-ordered map that keeps repeated keys, used for headers,
+/// query params and form fields.
+///
+/// Serializes as a plain map when every key is unique, and as a list of
+/// `[key, value]` pairs otherwise. Both forms are accepted when deserializing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MultiMap(Vec<(String, String)>);
+
+impl MultiMap {
+    pub fn new() -> MultiMap {
+        MultiMap(Vec::new())
+    }
+
+    /// Adds a value after the existing ones, even if the key is already present
+    pub fn append(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.0.push((key.into(), value.into()));
+    }
+
+    /// Replaces every value of `key` with a single one, keeping the position of the first
+    pub fn set(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        let key = key.into();
+        match self.0.iter().position(|(k, _)| *k == key) {
+            Some(index) => {
+                self.0[index].1 = value.into();
+                let mut seen = false;
+                self.0.retain(|(k, _)| {
+                    let keep = *k != key || !seen;
+                    seen |= *k == key;
+                    keep
+                });
+            }
+            None => self.0.push((key, value.into())),
+        }
+    }
+
+    /// Removes every value of `key`, returning how many were dropped
+    pub fn remove(&mut self, key: &str) -> usize {
+        let len = self.0.len();
+        self.0.retain(|(k, _)| k != key);
+        len - self.0.len()
+    }
+
+    /// First value of `key`
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn get_all<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a str> {
+        self.0
+            .iter()
+            .filter(move |(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Header lookup, names are compared without regard to ASCII case
+    pub fn get_ignore_case(&self, key: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Removes every value of `key` whatever the ASCII case of its name, returning
+    /// how many were dropped
+    pub fn remove_ignore_case(&mut self, key: &str) -> usize {
+        let len = self.0.len();
+        self.0.retain(|(k, _)| !k.eq_ignore_ascii_case(key));
+        len - self.0.len()
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.0.iter().any(|(k, _)| k == key)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    pub fn as_slice(&self) -> &[(String, String)] {
+        &self.0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<K: Into<String>, V: Into<String>> FromIterator<(K, V)> for MultiMap {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> MultiMap {
+        MultiMap(
+            iter.into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+        )
+    }
+}
+
+impl<K: Into<String>, V: Into<String>, const N: usize> From<[(K, V); N]> for MultiMap {
+    fn from(pairs: [(K, V); N]) -> MultiMap {
+        pairs.into_iter().collect()
+    }
+}
+
+impl From<Vec<(String, String)>> for MultiMap {
+    fn from(pairs: Vec<(String, String)>) -> MultiMap {
+        MultiMap(pairs)
+    }
+}
+
+impl IntoIterator for MultiMap {
+    type Item = (String, String);
+    type IntoIter = std::vec::IntoIter<(String, String)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a MultiMap {
+    type Item = (&'a str, &'a str);
+    type IntoIter = std::iter::Map<
+        std::slice::Iter<'a, (String, String)>,
+        fn(&'a (String, String)) -> (&'a str, &'a str),
+    >;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+}
+
+impl Serialize for MultiMap {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut keys = HashSet::new();
+        if self.0.iter().all(|(k, _)| keys.insert(k)) {
+            let mut map = serializer.serialize_map(Some(self.0.len()))?;
+            for (k, v) in &self.0 {
+                map.serialize_entry(k, v)?;
+            }
+            map.end()
+        } else {
+            let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
+            for pair in &self.0 {
+                seq.serialize_element(pair)?;
+            }
+            seq.end()
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for MultiMap {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<MultiMap, D::Error> {
+        struct MultiMapVisitor;
+
+        impl<'de> Visitor<'de> for MultiMapVisitor {
+            type Value = MultiMap;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a map of strings or a list of [key, value] pairs")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<MultiMap, A::Error> {
+                let mut pairs = Vec::with_capacity(access.size_hint().unwrap_or(0));
+                while let Some(pair) = access.next_entry()? {
+                    pairs.push(pair);
+                }
+                Ok(MultiMap(pairs))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut access: A) -> Result<MultiMap, A::Error> {
+                let mut pairs = Vec::with_capacity(access.size_hint().unwrap_or(0));
+                while let Some(pair) = access.next_element()? {
+                    pairs.push(pair);
+                }
+                Ok(MultiMap(pairs))
+            }
+        }
+
+        deserializer.deserialize_any(MultiMapVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MultiMap;
+    use serde_json::json;
+
+    #[test]
+    fn keep_repeated_keys_in_order() {
+        let mut map = MultiMap::from([("tag", "a"), ("vary", "accept")]);
+        map.append("tag", "b");
+        assert_eq!(Some("a"), map.get("tag"));
+        assert_eq!(vec!["a", "b"], map.get_all("tag").collect::<Vec<_>>());
+        assert_eq!(
+            vec![("tag", "a"), ("vary", "accept"), ("tag", "b")],
+            map.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(Some("accept"), map.get_ignore_case("Vary"));
+
+        map.set("tag", "c");
+        assert_eq!(
+            vec![("tag", "c"), ("vary", "accept")],
+            map.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(1, map.remove("vary"));
+    }
+
+    #[test]
+    fn remove_ignoring_case() {
+        let mut map = MultiMap::from([("Content-Type", "text/plain"), ("accept", "*/*")]);
+        map.append("content-type", "application/json");
+        assert_eq!(0, map.remove("CONTENT-TYPE"));
+        assert_eq!(2, map.remove_ignore_case("CONTENT-TYPE"));
+        assert_eq!(vec![("accept", "*/*")], map.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn round_trip_map_form() {
+        let value = json!({"name": "john", "age": "42"});
+        let map: MultiMap = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(Some("john"), map.get("name"));
+        assert_eq!(value, serde_json::to_value(&map).unwrap());
+    }
+
+    #[test]
+    fn round_trip_pairs_form() {
+        let value = json!([["tag", "a"], ["tag", "b"]]);
+        let map: MultiMap = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(2, map.len());
+        assert_eq!(value, serde_json::to_value(&map).unwrap());
+    }
+}
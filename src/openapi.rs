@@ -0,0 +1,1003 @@
+use crate::{
+    body::{MultipartPart, RequestBody},
+    collection::{self, Collection, SavedRequest},
+    multimap::MultiMap,
+    request::{HeaderCase, Request, RequestMethod},
+    variables::{Environment, VariableMap},
+};
+use serde_json::{Map, Number};
+use serde_yaml::Value;
+use std::{collections::BTreeSet, error, fmt, path::PathBuf};
+
+/// Operation keys of a path item, in the order requests are created
+const METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+/// Schemas nested deeper than this are left empty, e.g. recursive ones
+const MAX_DEPTH: usize = 8;
+
+/// A collection generated from an OpenAPI or Swagger document, with what could not be carried over
+#[derive(Debug, Clone, PartialEq)]
+pub struct Import {
+    pub collection: Collection,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Syntax(serde_yaml::Error), // Neither valid YAML nor JSON
+    NotOpenApi,
+    UnsupportedVersion(String),
+    Collection(collection::Error),
+}
+
+/// Converts an OpenAPI 3.0/3.1 or Swagger 2.0 document, in YAML or JSON.
+///
+/// Each operation becomes a request in the folder of its first tag, with path
+/// parameters as `{{variables}}` and parameters and bodies filled from the
+/// examples, defaults or schemas. Each server becomes an environment defining
+/// `baseUrl`, and security schemes become headers or parameters whose
+/// credentials are left empty in every environment.
+pub fn parse(content: &str) -> Result<Import, Error> {
+    let spec: Value = serde_yaml::from_str(content).map_err(Error::Syntax)?;
+    let swagger = match (spec.get("openapi"), spec.get("swagger")) {
+        (Some(version), _) => match scalar(version) {
+            version if version.starts_with("3.0") || version.starts_with("3.1") => false,
+            version => return Err(Error::UnsupportedVersion(version)),
+        },
+        (None, Some(version)) => match scalar(version) {
+            version if version == "2.0" => true,
+            version => return Err(Error::UnsupportedVersion(version)),
+        },
+        (None, None) => return Err(Error::NotOpenApi),
+    };
+
+    let info = spec.get("info");
+    let field = |name: &str| info.and_then(|info| info.get(name)).map(scalar);
+    let mut collection = Collection::new(field("title").unwrap_or_else(|| "imported".to_string()));
+    collection.info_mut().description = field("description").filter(|text| !text.is_empty());
+    collection.info_mut().version = field("version");
+
+    let mut importer = Importer {
+        spec: &spec,
+        swagger,
+        collection,
+        warnings: Vec::new(),
+        credentials: VariableMap::new(),
+        unresolved: BTreeSet::new(),
+    };
+    importer.operations().map_err(Error::Collection)?;
+    importer.environments();
+    Ok(importer.finish())
+}
+
+struct Importer<'a> {
+    spec: &'a Value,
+    swagger: bool, // Swagger 2.0 rather than OpenAPI 3
+    collection: Collection,
+    warnings: Vec<String>,
+    credentials: VariableMap, // Referenced by auth, added empty to every environment
+    unresolved: BTreeSet<String>,
+}
+
+impl<'a> Importer<'a> {
+    fn finish(mut self) -> Import {
+        for reference in &self.unresolved {
+            self.warnings.push(format!(
+                "`{}` could not be resolved, only local references are supported",
+                reference
+            ));
+        }
+        Import {
+            collection: self.collection,
+            warnings: self.warnings,
+        }
+    }
+
+    /// Warnings about the document as a whole are only given once
+    fn warn(&mut self, warning: String) {
+        if !self.warnings.contains(&warning) {
+            self.warnings.push(warning);
+        }
+    }
+
+    /// Follows `$ref`s within the document, `Null` when one cannot be followed
+    fn resolve(&mut self, mut value: &'a Value) -> &'a Value {
+        static NULL: Value = Value::Null;
+        for _ in 0..MAX_DEPTH {
+            let Some(reference) = value.get("$ref").and_then(Value::as_str) else {
+                return value;
+            };
+            match pointer(self.spec, reference) {
+                Some(target) => value = target,
+                None => {
+                    self.unresolved.insert(reference.to_string());
+                    return &NULL;
+                }
+            }
+        }
+        &NULL
+    }
+
+    fn operations(&mut self) -> Result<(), collection::Error> {
+        let Some(paths) = self.spec.get("paths").and_then(Value::as_mapping) else {
+            self.warn("the document has no paths".to_string());
+            return Ok(());
+        };
+        for (path, item) in paths {
+            let Some(path) = path.as_str() else { continue };
+            let item = self.resolve(item);
+            for method in METHODS {
+                if let Some(operation) = item.get(method) {
+                    let (folder, saved) = self.operation(path, method, item, operation);
+                    self.collection.add(&folder, saved)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The request for `operation`, and the folder it goes in
+    fn operation(
+        &mut self,
+        path: &str,
+        method: &str,
+        item: &'a Value,
+        operation: &'a Value,
+    ) -> (String, SavedRequest) {
+        let folder = operation
+            .get("tags")
+            .and_then(|tags| tags.get(0))
+            .map(|tag| scalar(tag).replace('/', "-"))
+            .unwrap_or_default();
+        let title = ["summary", "operationId"]
+            .iter()
+            .filter_map(|key| operation.get(key).map(scalar))
+            .find(|title| !title.trim().is_empty())
+            .unwrap_or_else(|| format!("{} {}", method.to_uppercase(), path.trim_matches('/')));
+        let name = self.collection.unique_name(&folder, &title);
+        let owner = match folder.as_str() {
+            "" => format!("`{}`", name),
+            _ => format!("`{}/{}`", folder, name),
+        };
+
+        let mut variables = VariableMap::new();
+        let mut params = MultiMap::new();
+        let mut headers = MultiMap::new();
+        let mut cookies = Vec::new();
+        let mut form = Vec::new();
+        let mut body = None;
+        for parameter in self.parameters(item, operation) {
+            let Some(name) = parameter.get("name").map(scalar) else {
+                continue;
+            };
+            let location = parameter.get("in").map(scalar).unwrap_or_default();
+            let required = parameter.get("required").and_then(Value::as_bool) == Some(true);
+            match location.as_str() {
+                "path" => {
+                    let value = self.parameter_value(parameter, true).unwrap_or_default();
+                    variables.insert(name, text(&value));
+                }
+                "query" => {
+                    for value in self
+                        .parameter_value(parameter, required)
+                        .iter()
+                        .flat_map(texts)
+                    {
+                        params.append(name.as_str(), value);
+                    }
+                }
+                // Set from the body and auth instead
+                "header"
+                    if ["accept", "content-type", "authorization"]
+                        .contains(&name.to_ascii_lowercase().as_str()) => {}
+                "header" => {
+                    if let Some(value) = self.parameter_value(parameter, required) {
+                        headers.append(name, texts(&value).join(","));
+                    }
+                }
+                "cookie" => {
+                    if let Some(value) = self.parameter_value(parameter, required) {
+                        cookies.push(format!("{}={}", name, text(&value)));
+                    }
+                }
+                "body" => {
+                    let schema = parameter.get("schema").unwrap_or(&Value::Null);
+                    body = Some(self.example(schema, 0));
+                }
+                "formData" => {
+                    let file = parameter.get("type").map(scalar).as_deref() == Some("file");
+                    if let Some(value) = self.parameter_value(parameter, required || file) {
+                        form.push((name, value, file));
+                    }
+                }
+                location => self.warn(format!(
+                    "{}: parameter `{}` in `{}` skipped",
+                    owner, name, location
+                )),
+            }
+        }
+
+        let body = match self.swagger {
+            true => self.swagger_body(&owner, operation, body, form),
+            false => match operation.get("requestBody") {
+                Some(request_body) => self.request_body(&owner, request_body),
+                None => None,
+            },
+        };
+        self.auth(&owner, operation, &mut headers, &mut params, &mut cookies);
+        if !cookies.is_empty() {
+            headers.append("Cookie", cookies.join("; "));
+        }
+
+        let method = method
+            .parse::<RequestMethod>()
+            .expect("standard methods parse");
+        let url = format!(
+            "{{{{baseUrl}}}}{}",
+            path.replace('{', "{{").replace('}', "}}")
+        );
+        let request =
+            Request::new(body, headers, method, url, params).with_header_case(HeaderCase::Verbatim);
+        let mut saved = SavedRequest::new(name, request);
+        saved.variables = variables;
+        (folder, saved)
+    }
+
+    /// Parameters of the path item, overridden by the operation's with the same name and location
+    fn parameters(&mut self, item: &'a Value, operation: &'a Value) -> Vec<&'a Value> {
+        let mut parameters: Vec<&'a Value> = Vec::new();
+        for list in [item.get("parameters"), operation.get("parameters")] {
+            for parameter in list.and_then(Value::as_sequence).into_iter().flatten() {
+                let parameter = self.resolve(parameter);
+                let key = |p: &Value| (p.get("name").cloned(), p.get("in").cloned());
+                parameters.retain(|other| key(other) != key(parameter));
+                parameters.push(parameter);
+            }
+        }
+        parameters
+    }
+
+    /// The parameter's example or default, else a value matching its schema when `required`
+    fn parameter_value(
+        &mut self,
+        parameter: &'a Value,
+        required: bool,
+    ) -> Option<serde_json::Value> {
+        if let Some(example) = parameter.get("example").or(parameter.get("x-example")) {
+            return Some(json(example));
+        }
+        if let Some(example) = self.first_example(parameter.get("examples")) {
+            return Some(example);
+        }
+        // Swagger 2.0 describes the type on the parameter itself
+        let schema = self.resolve(parameter.get("schema").unwrap_or(parameter));
+        if let Some(example) = explicit(schema) {
+            return Some(example);
+        }
+        required.then(|| self.example(schema, 0))
+    }
+
+    /// The value of the first entry of an OpenAPI 3 `examples` map
+    fn first_example(&mut self, examples: Option<&'a Value>) -> Option<serde_json::Value> {
+        let (_, example) = examples?.as_mapping()?.iter().next()?;
+        self.resolve(example).get("value").map(json)
+    }
+
+    /// A value matching `schema`: its own example when it has one, else one
+    /// built from its properties and types
+    fn example(&mut self, schema: &'a Value, depth: usize) -> serde_json::Value {
+        let schema = self.resolve(schema);
+        if let Some(example) = explicit(schema) {
+            return example;
+        }
+        if depth >= MAX_DEPTH {
+            return serde_json::Value::Null;
+        }
+        if let Some(all) = schema.get("allOf").and_then(Value::as_sequence) {
+            let mut merged = Map::new();
+            for part in all {
+                match self.example(part, depth + 1) {
+                    serde_json::Value::Object(object) => merged.extend(object),
+                    other if all.len() == 1 => return other,
+                    _ => {}
+                }
+            }
+            return serde_json::Value::Object(merged);
+        }
+        for key in ["oneOf", "anyOf"] {
+            if let Some(first) = schema.get(key).and_then(|choices| choices.get(0)) {
+                return self.example(first, depth + 1);
+            }
+        }
+
+        // OpenAPI 3.1 allows a list of types, e.g. `[string, "null"]`
+        let kind = match schema.get("type") {
+            Some(Value::Sequence(kinds)) => kinds
+                .iter()
+                .map(scalar)
+                .find(|kind| kind != "null")
+                .unwrap_or_default(),
+            Some(kind) => scalar(kind),
+            None if schema.get("properties").is_some() => "object".to_string(),
+            None if schema.get("items").is_some() => "array".to_string(),
+            None => String::new(),
+        };
+        let format = schema.get("format").map(scalar).unwrap_or_default();
+        match kind.as_str() {
+            "object" => {
+                let mut object = Map::new();
+                let properties = schema.get("properties").and_then(Value::as_mapping);
+                for (name, property) in properties.into_iter().flatten() {
+                    let property = self.resolve(property);
+                    if property.get("readOnly").and_then(Value::as_bool) != Some(true) {
+                        object.insert(scalar(name), self.example(property, depth + 1));
+                    }
+                }
+                serde_json::Value::Object(object)
+            }
+            "array" => match schema.get("items") {
+                Some(items) => serde_json::Value::Array(vec![self.example(items, depth + 1)]),
+                None => serde_json::Value::Array(Vec::new()),
+            },
+            "integer" | "number" => serde_json::Value::from(0),
+            "boolean" => serde_json::Value::Bool(false),
+            "string" => serde_json::Value::from(match format.as_str() {
+                "date" => "2024-01-01",
+                "date-time" => "2024-01-01T00:00:00Z",
+                "time" => "00:00:00",
+                "email" => "user@example.com",
+                "uuid" => "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                "uri" | "url" => "https://example.com",
+                "hostname" => "example.com",
+                "ipv4" => "192.0.2.1",
+                "ipv6" => "2001:db8::1",
+                "binary" | "byte" | "password" => "",
+                _ => "string",
+            }),
+            _ => serde_json::Value::Null,
+        }
+    }
+
+    /// An OpenAPI 3 request body, JSON when the operation accepts it
+    fn request_body(&mut self, owner: &str, request_body: &'a Value) -> Option<RequestBody> {
+        let content = self.resolve(request_body).get("content")?.as_mapping()?;
+        let (media_type, media) = content
+            .iter()
+            .find(|(media_type, _)| is_json(&scalar(media_type)))
+            .or_else(|| content.iter().next())?;
+        let media_type = scalar(media_type);
+        let schema = media.get("schema").unwrap_or(&Value::Null);
+        let value = match media.get("example") {
+            Some(example) => json(example),
+            None => match self.first_example(media.get("examples")) {
+                Some(example) => example,
+                None => self.example(schema, 0),
+            },
+        };
+
+        let fields = || match &value {
+            serde_json::Value::Object(object) => object.clone(),
+            _ => Map::new(),
+        };
+        Some(match media_type.as_str() {
+            media_type if is_json(media_type) => RequestBody::Json(value),
+            "application/x-www-form-urlencoded" => RequestBody::Form(
+                fields()
+                    .iter()
+                    .flat_map(|(name, value)| {
+                        texts(value).into_iter().map(move |v| (name.clone(), v))
+                    })
+                    .collect(),
+            ),
+            "multipart/form-data" => {
+                let schema = self.resolve(schema);
+                let mut parts = Vec::new();
+                for (name, value) in fields() {
+                    let property = schema.get("properties").and_then(|p| p.get(name.as_str()));
+                    let format = property
+                        .map(|p| self.resolve(p))
+                        .and_then(|p| p.get("format"));
+                    match format.map(scalar).as_deref() {
+                        Some("binary" | "base64") => parts.push(self.file_part(owner, name)),
+                        _ => parts.push(MultipartPart::Text {
+                            name,
+                            value: text(&value),
+                        }),
+                    }
+                }
+                RequestBody::Multipart(parts)
+            }
+            _ => RequestBody::Raw {
+                content: text(&value),
+                content_type: media_type,
+            },
+        })
+    }
+
+    /// A Swagger 2.0 body: the `body` parameter, or the `formData` ones
+    fn swagger_body(
+        &mut self,
+        owner: &str,
+        operation: &'a Value,
+        body: Option<serde_json::Value>,
+        form: Vec<(String, serde_json::Value, bool)>,
+    ) -> Option<RequestBody> {
+        let consumes: Vec<String> = operation
+            .get("consumes")
+            .or(self.spec.get("consumes"))
+            .and_then(Value::as_sequence)
+            .map(|types| types.iter().map(scalar).collect())
+            .unwrap_or_default();
+        if let Some(value) = body {
+            return Some(match consumes.iter().find(|t| !is_json(t)) {
+                Some(content_type) if !consumes.iter().any(|t| is_json(t)) => RequestBody::Raw {
+                    content: text(&value),
+                    content_type: content_type.clone(),
+                },
+                _ => RequestBody::Json(value),
+            });
+        }
+        if form.is_empty() {
+            return None;
+        }
+        let multipart = form.iter().any(|(_, _, file)| *file)
+            || consumes.iter().any(|t| t == "multipart/form-data");
+        if !multipart {
+            return Some(RequestBody::Form(
+                form.iter()
+                    .flat_map(|(name, value, _)| {
+                        texts(value).into_iter().map(move |v| (name.clone(), v))
+                    })
+                    .collect(),
+            ));
+        }
+        let parts = form
+            .into_iter()
+            .map(|(name, value, file)| match file {
+                true => self.file_part(owner, name),
+                false => MultipartPart::Text {
+                    name,
+                    value: text(&value),
+                },
+            })
+            .collect();
+        Some(RequestBody::Multipart(parts))
+    }
+
+    /// A file field, read from a file named after it
+    fn file_part(&mut self, owner: &str, name: String) -> MultipartPart {
+        self.warn(format!(
+            "{}: file field `{}` is read from `{}`, change the path to upload another file",
+            owner, name, name
+        ));
+        MultipartPart::File {
+            path: PathBuf::from(&name),
+            name,
+            content_type: None,
+        }
+    }
+
+    /// Applies every scheme of the first security requirement, the
+    /// operation's or else the document's, with `{{variables}}` as credentials
+    fn auth(
+        &mut self,
+        owner: &str,
+        operation: &'a Value,
+        headers: &mut MultiMap,
+        params: &mut MultiMap,
+        cookies: &mut Vec<String>,
+    ) {
+        let security = operation.get("security").or(self.spec.get("security"));
+        let Some(requirement) = security
+            .and_then(|security| security.get(0))
+            .and_then(Value::as_mapping)
+        else {
+            return;
+        };
+        let definitions = match self.swagger {
+            true => self.spec.get("securityDefinitions"),
+            false => self
+                .spec
+                .get("components")
+                .and_then(|components| components.get("securitySchemes")),
+        };
+
+        for name in requirement.keys().map(scalar) {
+            let Some(scheme) = definitions.and_then(|definitions| definitions.get(name.as_str()))
+            else {
+                self.warn(format!(
+                    "{}: security scheme `{}` is not defined",
+                    owner, name
+                ));
+                continue;
+            };
+            let scheme = self.resolve(scheme);
+            let kind = scheme.get("type").map(scalar).unwrap_or_default();
+            let http = scheme
+                .get("scheme")
+                .map(scalar)
+                .unwrap_or_default()
+                .to_ascii_lowercase();
+            match (kind.as_str(), http.as_str()) {
+                ("http", "basic") | ("basic", _) => {
+                    self.credential("username");
+                    self.credential("password");
+                    headers.append(
+                        "Authorization",
+                        "Basic {{$base64({{username}}:{{password}})}}",
+                    );
+                }
+                ("http", "bearer") => {
+                    self.credential("token");
+                    headers.append("Authorization", "Bearer {{token}}");
+                }
+                ("oauth2" | "openIdConnect", _) => {
+                    self.warn(format!(
+                        "`{}` is {}: obtain an access token and set `token`",
+                        name, kind
+                    ));
+                    self.credential("token");
+                    headers.append("Authorization", "Bearer {{token}}");
+                }
+                ("apiKey", _) => {
+                    self.credential(&name);
+                    let key = scheme.get("name").map(scalar).unwrap_or_default();
+                    let value = format!("{{{{{}}}}}", name);
+                    match scheme.get("in").map(scalar).as_deref() {
+                        Some("query") => params.append(key, value),
+                        Some("cookie") => cookies.push(format!("{}={}", key, value)),
+                        _ => headers.append(key, value),
+                    }
+                }
+                _ => self.warn(format!(
+                    "security scheme `{}` ({} {}) is not supported, skipped",
+                    name, kind, http
+                )),
+            }
+        }
+    }
+
+    fn credential(&mut self, name: &str) {
+        self.credentials.insert(name.to_string(), String::new());
+    }
+
+    /// One environment per server, defining `baseUrl`, the server's variables and the credentials
+    fn environments(&mut self) {
+        let mut servers = Vec::new();
+        if self.swagger {
+            let host = self.spec.get("host").map(scalar);
+            let base_path = self.spec.get("basePath").map(scalar).unwrap_or_default();
+            let schemes: Vec<String> = match self.spec.get("schemes").and_then(Value::as_sequence) {
+                Some(schemes) => schemes.iter().map(scalar).collect(),
+                None => vec!["https".to_string()],
+            };
+            for scheme in schemes {
+                let url = match &host {
+                    Some(host) => format!("{}://{}{}", scheme, host, base_path),
+                    None => base_path.clone(),
+                };
+                servers.push((scheme, url, VariableMap::new()));
+            }
+        } else {
+            let listed = self.spec.get("servers").and_then(Value::as_sequence);
+            for server in listed.into_iter().flatten() {
+                let mut url = server.get("url").map(scalar).unwrap_or_default();
+                let mut variables = VariableMap::new();
+                let defined = server.get("variables").and_then(Value::as_mapping);
+                for (name, variable) in defined.into_iter().flatten() {
+                    let name = scalar(name);
+                    url = url.replace(&format!("{{{}}}", name), &format!("{{{{{}}}}}", name));
+                    let default = variable.get("default").map(scalar).unwrap_or_default();
+                    variables.insert(name, default);
+                }
+                let name = server
+                    .get("description")
+                    .map(scalar)
+                    .filter(|description| !description.trim().is_empty())
+                    .unwrap_or_else(|| url.clone());
+                servers.push((name, url, variables));
+            }
+            if servers.is_empty() {
+                servers.push(("default".to_string(), String::new(), VariableMap::new()));
+            }
+        }
+
+        for (name, url, mut variables) in servers {
+            let mut url = url.trim_end_matches('/').to_string();
+            if !url.contains("://") && !url.starts_with("{{") {
+                self.warn(format!(
+                    "server `{}` has no host, set `baseUrl` in environment `{}`",
+                    url, name
+                ));
+                url = format!("http://localhost{}", url);
+            }
+            variables.insert("baseUrl".to_string(), url);
+            variables.extend(self.credentials.clone());
+            let mut unique = name.clone();
+            let mut n = 1;
+            while self.collection.environment(&unique).is_some() {
+                n += 1;
+                unique = format!("{} ({})", name, n);
+            }
+            self.collection.set_environment(Environment {
+                name: unique,
+                variables,
+            });
+        }
+    }
+}
+
+/// The target of a local reference like `#/components/schemas/Pet`
+fn pointer<'a>(spec: &'a Value, reference: &str) -> Option<&'a Value> {
+    let path = reference.strip_prefix("#/")?;
+    let mut value = spec;
+    for segment in path.split('/') {
+        let segment = segment.replace("~1", "/").replace("~0", "~");
+        value = match value {
+            Value::Sequence(items) => items.get(segment.parse::<usize>().ok()?)?,
+            _ => value.get(segment.as_str())?,
+        };
+    }
+    Some(value)
+}
+
+/// An example given by the schema itself
+fn explicit(schema: &Value) -> Option<serde_json::Value> {
+    if let Some(example) = schema
+        .get("example")
+        .or(schema.get("default"))
+        .or(schema.get("const"))
+    {
+        return Some(json(example));
+    }
+    ["examples", "enum"]
+        .iter()
+        .find_map(|key| schema.get(key)?.get(0))
+        .map(json)
+}
+
+fn is_json(media_type: &str) -> bool {
+    let essence = media_type.split(';').next().unwrap_or_default().trim();
+    essence == "application/json" || essence.ends_with("+json")
+}
+
+/// YAML numbers, booleans and strings as text, e.g. for `version: 3.0`
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Null => String::new(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        other => serde_yaml::to_string(other)
+            .unwrap_or_default()
+            .trim()
+            .to_string(),
+    }
+}
+
+fn json(value: &Value) -> serde_json::Value {
+    match value {
+        Value::Null => serde_json::Value::Null,
+        Value::Bool(b) => serde_json::Value::Bool(*b),
+        Value::Number(n) => match (n.as_i64(), n.as_u64(), n.as_f64()) {
+            (Some(i), _, _) => serde_json::Value::from(i),
+            (_, Some(u), _) => serde_json::Value::from(u),
+            (_, _, Some(f)) => {
+                Number::from_f64(f).map_or(serde_json::Value::Null, serde_json::Value::Number)
+            }
+            _ => serde_json::Value::Null,
+        },
+        Value::String(text) => serde_json::Value::String(text.clone()),
+        Value::Sequence(items) => serde_json::Value::Array(items.iter().map(json).collect()),
+        Value::Mapping(mapping) => serde_json::Value::Object(
+            mapping
+                .iter()
+                .map(|(key, value)| (scalar(key), json(value)))
+                .collect(),
+        ),
+        Value::Tagged(tagged) => json(&tagged.value),
+    }
+}
+
+/// A parameter value as sent: strings as is, anything else as JSON
+fn text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(text) => text.clone(),
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// Arrays are sent as repeated parameters
+fn texts(value: &serde_json::Value) -> Vec<String> {
+    match value {
+        serde_json::Value::Array(items) => items.iter().map(text).collect(),
+        other => vec![text(other)],
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Syntax(_) => write!(f, "invalid YAML or JSON"),
+            Error::NotOpenApi => write!(
+                f,
+                "not an OpenAPI document, expected an `openapi` or `swagger` field"
+            ),
+            Error::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported version `{}`, expected OpenAPI 3.0, 3.1 or Swagger 2.0",
+                version
+            ),
+            Error::Collection(_) => write!(f, "could not build the collection"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Syntax(e) => Some(e),
+            Error::Collection(e) => Some(e),
+            Error::NotOpenApi | Error::UnsupportedVersion(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, Error};
+    use crate::{
+        body::{MultipartPart, RequestBody},
+        multimap::MultiMap,
+        request::RequestMethod,
+        variables::VariableMap,
+    };
+    use serde_json::json;
+    use std::path::PathBuf;
+
+    const PETSTORE: &str = r#"
+openapi: 3.0.3
+info:
+  title: Petstore
+  version: 1.2.0
+servers:
+  - url: https://{region}.pets.test/v1/
+    description: Production
+    variables:
+      region:
+        default: eu
+  - url: /staging
+security:
+  - bearerAuth: []
+paths:
+  /pets:
+    get:
+      tags: [pets]
+      summary: List pets
+      parameters:
+        - name: limit
+          in: query
+          schema: {type: integer, default: 20}
+        - name: cursor
+          in: query
+          schema: {type: string}
+        - name: status
+          in: query
+          required: true
+          schema: {type: array, items: {type: string, enum: [available, sold]}}
+          example: [available, sold]
+        - name: X-Request-Id
+          in: header
+          example: abc
+        - name: Accept
+          in: header
+          example: text/plain
+    post:
+      tags: [pets]
+      operationId: createPet
+      security:
+        - apiKey: []
+      requestBody:
+        content:
+          application/json:
+            schema:
+              $ref: '#/components/schemas/Pet'
+  /pets/{petId}:
+    parameters:
+      - name: petId
+        in: path
+        required: true
+        schema: {type: integer, example: 7}
+    get:
+      tags: [pets]
+      summary: Get a pet
+      security: []
+    put:
+      summary: Upload a photo
+      requestBody:
+        content:
+          multipart/form-data:
+            schema:
+              type: object
+              properties:
+                caption: {type: string, example: cute}
+                photo: {type: string, format: binary}
+components:
+  schemas:
+    Pet:
+      type: object
+      required: [name]
+      properties:
+        id: {type: integer, readOnly: true}
+        name: {type: string, example: Rex}
+        tags:
+          type: array
+          items: {$ref: '#/components/schemas/Tag'}
+        born: {type: string, format: date}
+    Tag:
+      type: object
+      properties:
+        label: {type: string}
+  securitySchemes:
+    bearerAuth: {type: http, scheme: bearer}
+    apiKey: {type: apiKey, in: header, name: X-Api-Key}
+"#;
+
+    #[test]
+    fn import_openapi_3() {
+        let import = parse(PETSTORE).unwrap();
+        let collection = import.collection;
+        assert_eq!("Petstore", collection.info().name);
+        assert_eq!(Some("1.2.0"), collection.info().version.as_deref());
+        let requests = collection.requests();
+        let paths: Vec<_> = requests.iter().map(|(path, _)| path.as_str()).collect();
+        assert_eq!(
+            vec![
+                "pets/List pets",
+                "pets/createPet",
+                "pets/Get a pet",
+                "Upload a photo"
+            ],
+            paths
+        );
+
+        let list = &requests[0].1.request;
+        assert_eq!("{{baseUrl}}/pets", list.url());
+        assert_eq!(
+            &MultiMap::from([("limit", "20"), ("status", "available"), ("status", "sold")]),
+            list.params()
+        );
+        assert_eq!(
+            &MultiMap::from([
+                ("X-Request-Id", "abc"),
+                ("Authorization", "Bearer {{token}}")
+            ]),
+            list.headers()
+        );
+
+        let create = &requests[1].1.request;
+        assert_eq!(&RequestMethod::POST, create.method());
+        assert_eq!(
+            &MultiMap::from([("X-Api-Key", "{{apiKey}}")]),
+            create.headers()
+        );
+        assert_eq!(
+            Some(&RequestBody::Json(json!({
+                "name": "Rex",
+                "tags": [{"label": "string"}],
+                "born": "2024-01-01"
+            }))),
+            create.body()
+        );
+
+        let (_, get) = &requests[2];
+        assert_eq!("{{baseUrl}}/pets/{{petId}}", get.request.url());
+        assert!(get.request.headers().is_empty());
+        assert_eq!(
+            VariableMap::from([("petId".to_string(), "7".to_string())]),
+            get.variables
+        );
+
+        assert_eq!(
+            Some(&RequestBody::Multipart(vec![
+                MultipartPart::Text {
+                    name: "caption".to_string(),
+                    value: "cute".to_string(),
+                },
+                MultipartPart::File {
+                    name: "photo".to_string(),
+                    path: PathBuf::from("photo"),
+                    content_type: None,
+                },
+            ])),
+            requests[3].1.request.body()
+        );
+
+        let production = collection.environment("Production").unwrap();
+        assert_eq!(
+            VariableMap::from([
+                ("apiKey".to_string(), String::new()),
+                (
+                    "baseUrl".to_string(),
+                    "https://{{region}}.pets.test/v1".to_string()
+                ),
+                ("region".to_string(), "eu".to_string()),
+                ("token".to_string(), String::new()),
+            ]),
+            production.variables
+        );
+        let staging = collection.environment("/staging").unwrap();
+        assert_eq!("http://localhost/staging", staging.variables["baseUrl"]);
+        assert_eq!(2, import.warnings.len());
+    }
+
+    #[test]
+    fn import_swagger_2() {
+        let spec = json!({
+            "swagger": "2.0",
+            "info": {"title": "Files", "version": "1"},
+            "host": "files.test",
+            "basePath": "/api",
+            "schemes": ["https", "http"],
+            "securityDefinitions": {"login": {"type": "basic"}},
+            "security": [{"login": []}],
+            "paths": {
+                "/notes": {
+                    "post": {
+                        "parameters": [{
+                            "name": "note",
+                            "in": "body",
+                            "schema": {"type": "object", "properties": {"text": {"type": "string"}}}
+                        }]
+                    }
+                },
+                "/upload": {
+                    "post": {
+                        "consumes": ["multipart/form-data"],
+                        "parameters": [
+                            {"name": "title", "in": "formData", "type": "string", "x-example": "cv"},
+                            {"name": "file", "in": "formData", "type": "file", "required": true}
+                        ]
+                    }
+                }
+            }
+        });
+        let import = parse(&spec.to_string()).unwrap();
+        let collection = import.collection;
+        let requests = collection.requests();
+
+        let (path, notes) = &requests[0];
+        assert_eq!("POST notes", path);
+        assert_eq!(
+            Some(&RequestBody::Json(json!({"text": "string"}))),
+            notes.request.body()
+        );
+        assert_eq!(
+            Some("Basic {{$base64({{username}}:{{password}})}}"),
+            notes.request.headers().get("Authorization")
+        );
+        assert!(matches!(
+            requests[1].1.request.body(),
+            Some(RequestBody::Multipart(parts)) if parts.len() == 2
+        ));
+
+        let names: Vec<_> = collection.environments().iter().map(|e| &e.name).collect();
+        assert_eq!(vec!["https", "http"], names);
+        assert_eq!(
+            "https://files.test/api",
+            collection.environments()[0].variables["baseUrl"]
+        );
+    }
+
+    #[test]
+    fn reject_other_documents() {
+        assert!(matches!(parse("name: x"), Err(Error::NotOpenApi)));
+        assert!(matches!(
+            parse("openapi: 4.0.0"),
+            Err(Error::UnsupportedVersion(version)) if version == "4.0.0"
+        ));
+        assert!(matches!(parse("{"), Err(Error::Syntax(_))));
+    }
+}
@@ -0,0 +1,1050 @@
+use crate::{
+    body::{BinaryBody, MultipartPart, RequestBody},
+    collection::{self, Collection, SavedRequest},
+    dynamic::Generator,
+    multimap::MultiMap,
+    request::{HeaderCase, Request, RequestMethod},
+    variables::{Environment, VariableMap},
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use regex::{Captures, Regex};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error, fmt,
+    path::PathBuf,
+};
+
+/// A collection converted from a Postman export, with what could not be carried over
+#[derive(Debug, Clone, PartialEq)]
+pub struct Import {
+    pub collection: Collection,
+    pub warnings: Vec<String>,
+}
+
+/// An environment converted from a Postman export
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnvironmentImport {
+    pub environment: Environment,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Json(serde_json::Error),
+    UnsupportedSchema(String),
+    Collection(collection::Error), // e.g. an item name the collection rejects
+}
+
+#[derive(Deserialize)]
+struct PostmanCollection {
+    info: Info,
+    #[serde(default)]
+    item: Vec<PostmanItem>,
+    #[serde(default)]
+    variable: Vec<Pair>,
+    auth: Option<Auth>,
+    #[serde(default)]
+    event: Vec<Event>,
+}
+
+#[derive(Deserialize)]
+struct Info {
+    #[serde(default)]
+    name: String,
+    description: Option<Description>,
+    schema: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Description {
+    Text(String),
+    Object { content: Option<String> },
+}
+
+/// A folder when `item` is set, a request otherwise
+#[derive(Deserialize)]
+struct PostmanItem {
+    #[serde(default)]
+    name: String,
+    item: Option<Vec<PostmanItem>>,
+    request: Option<PostmanRequest>,
+    #[serde(default)]
+    variable: Vec<Pair>,
+    auth: Option<Auth>,
+    #[serde(default)]
+    event: Vec<Event>,
+    #[serde(default)]
+    response: Vec<Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PostmanRequest {
+    Url(String),
+    Definition(Box<Definition>),
+}
+
+#[derive(Deserialize, Default)]
+struct Definition {
+    method: Option<String>,
+    header: Option<Headers>,
+    url: Option<Url>,
+    body: Option<Body>,
+    auth: Option<Auth>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Headers {
+    List(Vec<Pair>),
+    Text(String), // `Name: value` lines
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Url {
+    Raw(String),
+    Parts(UrlParts),
+}
+
+#[derive(Deserialize)]
+struct UrlParts {
+    raw: Option<String>,
+    protocol: Option<String>,
+    host: Option<Segments>,
+    port: Option<String>,
+    path: Option<Segments>,
+    #[serde(default)]
+    query: Vec<Pair>,
+    #[serde(default)]
+    variable: Vec<Pair>, // Values of the `:name` path segments
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Segments {
+    Joined(String),
+    Split(Vec<Segment>),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Segment {
+    Text(String),
+    Object { value: Option<String> },
+}
+
+/// Headers, query parameters, form fields and variables all share this shape
+#[derive(Deserialize)]
+struct Pair {
+    key: Option<String>,
+    value: Option<Value>,
+    #[serde(default)]
+    disabled: bool,
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    src: Option<Value>, // Form file, a path or a list of them
+    #[serde(rename = "contentType")]
+    content_type: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Body {
+    mode: Option<String>,
+    raw: Option<String>,
+    #[serde(default)]
+    urlencoded: Vec<Pair>,
+    #[serde(default)]
+    formdata: Vec<Pair>,
+    file: Option<FileBody>,
+    graphql: Option<Graphql>,
+    options: Option<Value>,
+    #[serde(default)]
+    disabled: bool,
+}
+
+#[derive(Deserialize)]
+struct FileBody {
+    src: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Graphql {
+    query: Option<String>,
+    variables: Option<String>,
+}
+
+/// `type` names the scheme, its parameters sit under a key of the same name
+#[derive(Deserialize)]
+struct Auth {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(flatten)]
+    params: BTreeMap<String, Value>,
+}
+
+#[derive(Deserialize)]
+struct Event {
+    listen: String,
+    script: Option<Script>,
+}
+
+#[derive(Deserialize)]
+struct Script {
+    exec: Option<Exec>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Exec {
+    Lines(Vec<String>),
+    Text(String),
+}
+
+#[derive(Deserialize)]
+struct PostmanEnvironment {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    values: Vec<EnvironmentValue>,
+}
+
+#[derive(Deserialize)]
+struct EnvironmentValue {
+    key: String,
+    value: Option<Value>,
+    #[serde(default = "enabled")]
+    enabled: bool,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+/// Converts a Postman v2.0 or v2.1 collection export.
+///
+/// Folders, requests, headers, query parameters, every body mode, basic, bearer
+/// and API key auth and variables are mapped. Auth is inherited from folders and
+/// the collection like in Postman, and folder variables become variables of each
+/// request below. Scripts, disabled entries and other auth schemes are reported
+/// in the warnings.
+pub fn parse_collection(content: &str) -> Result<Import, Error> {
+    let value: Value = serde_json::from_str(content).map_err(Error::Json)?;
+    if value.get("info").is_none() && value.get("requests").is_some() {
+        return Err(Error::UnsupportedSchema("v1".to_string()));
+    }
+    let postman: PostmanCollection = serde_json::from_value(value).map_err(Error::Json)?;
+    if let Some(schema) = &postman.info.schema {
+        if !schema.contains("v2.0") && !schema.contains("v2.1") {
+            return Err(Error::UnsupportedSchema(schema.clone()));
+        }
+    }
+
+    let mut importer = Importer::new(Collection::new(&postman.info.name));
+    importer.collection.info_mut().description = match postman.info.description {
+        Some(Description::Text(text)) => Some(text),
+        Some(Description::Object { content }) => content,
+        None => None,
+    }
+    .filter(|text| !text.is_empty());
+    let variables = importer.variables("the collection", &postman.variable);
+    *importer.collection.variables_mut() = variables;
+    importer.events("the collection", &postman.event);
+    importer
+        .items(
+            &postman.item,
+            "",
+            postman.auth.as_ref(),
+            &VariableMap::new(),
+        )
+        .map_err(Error::Collection)?;
+    Ok(importer.finish())
+}
+
+/// Converts a Postman environment or globals export, disabled values are skipped
+pub fn parse_environment(content: &str) -> Result<EnvironmentImport, Error> {
+    let postman: PostmanEnvironment = serde_json::from_str(content).map_err(Error::Json)?;
+    let mut environment = Environment::new(postman.name);
+    let mut warnings = Vec::new();
+    for value in postman.values {
+        if value.enabled {
+            environment
+                .variables
+                .insert(value.key, text(value.value.as_ref()));
+        } else {
+            warnings.push(format!(
+                "disabled variable `{}` of environment `{}` skipped",
+                value.key, environment.name
+            ));
+        }
+    }
+    Ok(EnvironmentImport {
+        environment,
+        warnings,
+    })
+}
+
+struct Importer {
+    collection: Collection,
+    warnings: Vec<String>,
+    dynamic: Regex,
+    unknown: BTreeSet<String>, // Dynamic variables without an equivalent
+}
+
+impl Importer {
+    fn new(collection: Collection) -> Importer {
+        Importer {
+            collection,
+            warnings: Vec::new(),
+            dynamic: Regex::new(r"\{\{\s*\$([A-Za-z]+)\s*\}\}").expect("valid regex"),
+            unknown: BTreeSet::new(),
+        }
+    }
+
+    fn finish(mut self) -> Import {
+        for name in &self.unknown {
+            self.warnings.push(format!(
+                "`{{{{${}}}}}` has no built-in equivalent, define it as a variable",
+                name
+            ));
+        }
+        Import {
+            collection: self.collection,
+            warnings: self.warnings,
+        }
+    }
+
+    fn warn(&mut self, warning: String) {
+        self.warnings.push(warning);
+    }
+
+    fn items(
+        &mut self,
+        items: &[PostmanItem],
+        folder: &str,
+        auth: Option<&Auth>,
+        variables: &VariableMap,
+    ) -> Result<(), collection::Error> {
+        for item in items {
+            let name = self.collection.unique_name(folder, &item.name);
+            let path = match folder {
+                "" => name.clone(),
+                _ => format!("{}/{}", folder, name),
+            };
+            if name != item.name {
+                self.warn(format!("`{}` renamed to `{}`", item.name, path));
+            }
+            self.events(&format!("`{}`", path), &item.event);
+            let auth = item.auth.as_ref().or(auth);
+
+            match (&item.item, &item.request) {
+                (Some(children), _) => {
+                    self.collection.add_folder(&path)?;
+                    let mut variables = variables.clone();
+                    variables.extend(self.variables(&format!("`{}`", path), &item.variable));
+                    self.items(children, &path, auth, &variables)?;
+                }
+                (None, Some(request)) => {
+                    if !item.response.is_empty() {
+                        self.warn(format!(
+                            "`{}`: {} saved example responses skipped",
+                            path,
+                            item.response.len()
+                        ));
+                    }
+                    if let Some(saved) = self.request(&path, name, request, auth, variables) {
+                        self.collection.add(folder, saved)?;
+                    }
+                }
+                (None, None) => self.warn(format!("`{}` has no request, skipped", path)),
+            }
+        }
+        Ok(())
+    }
+
+    fn request(
+        &mut self,
+        path: &str,
+        name: String,
+        request: &PostmanRequest,
+        inherited: Option<&Auth>,
+        variables: &VariableMap,
+    ) -> Option<SavedRequest> {
+        let url_only;
+        let definition = match request {
+            PostmanRequest::Url(url) => {
+                url_only = Definition {
+                    url: Some(Url::Raw(url.clone())),
+                    ..Definition::default()
+                };
+                &url_only
+            }
+            PostmanRequest::Definition(definition) => definition,
+        };
+        let method = definition.method.as_deref().unwrap_or("GET");
+        let Ok(method) = method.parse::<RequestMethod>() else {
+            self.warn(format!("`{}`: invalid method `{}`, skipped", path, method));
+            return None;
+        };
+
+        let mut local = variables.clone();
+        let (url, mut params) = self.url(path, definition.url.as_ref(), &mut local);
+        let mut headers = MultiMap::new();
+        match &definition.header {
+            Some(Headers::List(pairs)) => {
+                for pair in pairs {
+                    let Some(key) = &pair.key else { continue };
+                    match pair.disabled {
+                        true => self.warn(format!("`{}`: disabled header `{}` skipped", path, key)),
+                        false => headers.append(key.as_str(), text(pair.value.as_ref())),
+                    }
+                }
+            }
+            Some(Headers::Text(text)) => {
+                for line in text.lines() {
+                    if let Some((key, value)) = line.split_once(':') {
+                        headers.append(key.trim(), value.trim());
+                    }
+                }
+            }
+            None => {}
+        }
+        let body = match &definition.body {
+            Some(body) => self.body(path, body, &mut headers),
+            None => None,
+        };
+        if let Some(auth) = definition.auth.as_ref().or(inherited) {
+            self.auth(path, auth, &mut headers, &mut params);
+        }
+
+        let request = Request::new(body, headers, method, url, params)
+            .with_header_case(HeaderCase::Verbatim)
+            .map_strings(&mut |text| self.dynamic(text));
+        let mut saved = SavedRequest::new(name, request);
+        saved.variables = local;
+        Some(saved)
+    }
+
+    /// The url without its query, the enabled query parameters, and path
+    /// variables turned from `:name` into `{{name}}` with their values in `local`
+    fn url(
+        &mut self,
+        path: &str,
+        url: Option<&Url>,
+        local: &mut VariableMap,
+    ) -> (String, MultiMap) {
+        let mut params = MultiMap::new();
+        let url = match url {
+            None => {
+                self.warn(format!("`{}` has no url", path));
+                String::new()
+            }
+            Some(Url::Raw(raw)) => raw.clone(),
+            Some(Url::Parts(parts)) => {
+                for pair in &parts.query {
+                    let key = pair.key.clone().unwrap_or_default();
+                    match pair.disabled {
+                        true => self.warn(format!(
+                            "`{}`: disabled query parameter `{}` skipped",
+                            path, key
+                        )),
+                        false => params.append(key, text(pair.value.as_ref())),
+                    }
+                }
+                for pair in &parts.variable {
+                    let value = text(pair.value.as_ref());
+                    if let (Some(key), false) = (&pair.key, value.is_empty()) {
+                        local.insert(key.clone(), self.dynamic(&value));
+                    }
+                }
+                match &parts.raw {
+                    // The query is rebuilt from `query`, which knows what is disabled
+                    Some(raw) if !parts.query.is_empty() => {
+                        raw.split('?').next().unwrap_or_default().to_string()
+                    }
+                    Some(raw) => raw.clone(),
+                    None => build_url(parts),
+                }
+            }
+        };
+        (path_variables(&url), params)
+    }
+
+    fn body(&mut self, path: &str, body: &Body, headers: &mut MultiMap) -> Option<RequestBody> {
+        if body.disabled {
+            self.warn(format!("`{}`: disabled body skipped", path));
+            return None;
+        }
+        match body.mode.as_deref() {
+            Some("raw") => {
+                let content = body.raw.clone().filter(|raw| !raw.is_empty())?;
+                let language = body
+                    .options
+                    .as_ref()
+                    .and_then(|options| options.pointer("/raw/language"))
+                    .and_then(Value::as_str);
+                let content_type = match headers.get_ignore_case("content-type") {
+                    Some(content_type) => content_type.to_string(),
+                    None => match language {
+                        Some("json") => "application/json",
+                        Some("xml") => "application/xml",
+                        Some("html") => "text/html",
+                        Some("javascript") => "application/javascript",
+                        _ => "text/plain",
+                    }
+                    .to_string(),
+                };
+                headers.remove_ignore_case("content-type");
+                Some(RequestBody::Raw {
+                    content,
+                    content_type,
+                })
+            }
+            Some("urlencoded") => {
+                let mut pairs = MultiMap::new();
+                for pair in &body.urlencoded {
+                    let key = pair.key.clone().unwrap_or_default();
+                    match pair.disabled {
+                        true => {
+                            self.warn(format!("`{}`: disabled form field `{}` skipped", path, key))
+                        }
+                        false => pairs.append(key, text(pair.value.as_ref())),
+                    }
+                }
+                Some(RequestBody::Form(pairs))
+            }
+            Some("formdata") => {
+                // The boundary is generated when sending, a copied one would not match
+                if headers
+                    .get_ignore_case("content-type")
+                    .is_some_and(|value| value.starts_with("multipart/"))
+                {
+                    headers.remove_ignore_case("content-type");
+                }
+                let mut parts = Vec::new();
+                for pair in &body.formdata {
+                    let name = pair.key.clone().unwrap_or_default();
+                    if pair.disabled {
+                        self.warn(format!(
+                            "`{}`: disabled form field `{}` skipped",
+                            path, name
+                        ));
+                        continue;
+                    }
+                    if pair.kind.as_deref() != Some("file") {
+                        let value = text(pair.value.as_ref());
+                        parts.push(MultipartPart::Text { name, value });
+                        continue;
+                    }
+                    let sources: Vec<&str> = match &pair.src {
+                        Some(Value::String(src)) => vec![src.as_str()],
+                        Some(Value::Array(srcs)) => srcs.iter().filter_map(Value::as_str).collect(),
+                        _ => Vec::new(),
+                    };
+                    if sources.len() > 1 {
+                        self.warn(format!(
+                            "`{}`: form field `{}` has {} files, only the first is kept",
+                            path,
+                            name,
+                            sources.len()
+                        ));
+                    }
+                    match sources.first() {
+                        Some(src) => parts.push(MultipartPart::File {
+                            name,
+                            path: PathBuf::from(src),
+                            content_type: pair.content_type.clone(),
+                        }),
+                        None => self.warn(format!(
+                            "`{}`: form field `{}` has no file selected, skipped",
+                            path, name
+                        )),
+                    }
+                }
+                Some(RequestBody::Multipart(parts))
+            }
+            Some("file") => match body.file.as_ref().and_then(|file| file.src.as_deref()) {
+                Some(src) => Some(RequestBody::Binary(BinaryBody::File(PathBuf::from(src)))),
+                None => {
+                    self.warn(format!("`{}`: body has no file selected, skipped", path));
+                    None
+                }
+            },
+            Some("graphql") => {
+                let graphql = body.graphql.as_ref()?;
+                let mut payload = json!({ "query": graphql.query.clone().unwrap_or_default() });
+                match graphql.variables.as_deref().map(str::trim) {
+                    None | Some("") => {}
+                    Some(variables) => match serde_json::from_str::<Value>(variables) {
+                        Ok(variables) => payload["variables"] = variables,
+                        Err(_) => self.warn(format!(
+                            "`{}`: GraphQL variables are not valid JSON, skipped",
+                            path
+                        )),
+                    },
+                }
+                Some(RequestBody::Json(payload))
+            }
+            Some(mode) => {
+                self.warn(format!(
+                    "`{}`: unsupported body mode `{}` skipped",
+                    path, mode
+                ));
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Adds the credentials as a header or query parameter, unless the request
+    /// sets that header itself
+    fn auth(&mut self, path: &str, auth: &Auth, headers: &mut MultiMap, params: &mut MultiMap) {
+        let param = |name: &str| -> String {
+            match auth.params.get(&auth.kind) {
+                // v2.1 lists `{key, value}` pairs, v2.0 used an object
+                Some(Value::Array(pairs)) => pairs
+                    .iter()
+                    .find(|pair| pair.get("key").and_then(Value::as_str) == Some(name))
+                    .map(|pair| text(pair.get("value")))
+                    .unwrap_or_default(),
+                Some(Value::Object(map)) => text(map.get(name)),
+                _ => String::new(),
+            }
+        };
+        let mut set_header = |name: &str, value: String| {
+            if headers.get_ignore_case(name).is_none() {
+                headers.append(name, value);
+            }
+        };
+        match auth.kind.as_str() {
+            "noauth" => {}
+            "basic" => {
+                let credentials = format!("{}:{}", param("username"), param("password"));
+                let encoded = match credentials.contains("{{") {
+                    true => format!("{{{{$base64({})}}}}", credentials),
+                    false => STANDARD.encode(credentials),
+                };
+                set_header("Authorization", format!("Basic {}", encoded));
+            }
+            "bearer" => set_header("Authorization", format!("Bearer {}", param("token"))),
+            "apikey" if param("key").trim().is_empty() => {
+                self.warn(format!("`{}`: API key auth has no key name, skipped", path))
+            }
+            "apikey" => match param("in").as_str() {
+                "query" => params.append(param("key"), param("value")),
+                _ => set_header(&param("key"), param("value")),
+            },
+            kind => self.warn(format!(
+                "`{}`: {} auth is not supported, skipped",
+                path, kind
+            )),
+        }
+    }
+
+    /// Enabled variables, with Postman's dynamic variables renamed
+    fn variables(&mut self, owner: &str, pairs: &[Pair]) -> VariableMap {
+        let mut variables = VariableMap::new();
+        for pair in pairs {
+            let Some(key) = &pair.key else { continue };
+            if pair.disabled {
+                self.warn(format!("{}: disabled variable `{}` skipped", owner, key));
+                continue;
+            }
+            let value = self.dynamic(&text(pair.value.as_ref()));
+            variables.insert(key.clone(), value);
+        }
+        variables
+    }
+
+    fn events(&mut self, owner: &str, events: &[Event]) {
+        for event in events {
+            let script = match event
+                .script
+                .as_ref()
+                .and_then(|script| script.exec.as_ref())
+            {
+                Some(Exec::Lines(lines)) => lines.join("\n"),
+                Some(Exec::Text(text)) => text.clone(),
+                None => String::new(),
+            };
+            if script.trim().is_empty() {
+                continue;
+            }
+            let kind = match event.listen.as_str() {
+                "prerequest" => "pre-request",
+                "test" => "test",
+                other => other,
+            };
+            self.warn(format!("{}: {} script skipped", owner, kind));
+        }
+    }
+
+    /// `{{$guid}}` and `{{$randomUUID}}` become `{{$uuid}}`, the names shared
+    /// with the built-in generator are kept and the others are remembered
+    fn dynamic(&mut self, text: &str) -> String {
+        let unknown = &mut self.unknown;
+        self.dynamic
+            .replace_all(text, |captures: &Captures| {
+                let name = &captures[1];
+                match name {
+                    "guid" | "randomUUID" => "{{$uuid}}".to_string(),
+                    _ => {
+                        if Generator::seeded(0).generate(name).is_err() {
+                            unknown.insert(name.to_string());
+                        }
+                        captures[0].to_string()
+                    }
+                }
+            })
+            .into_owned()
+    }
+}
+
+fn build_url(parts: &UrlParts) -> String {
+    let join = |segments: &Option<Segments>, separator: &str| match segments {
+        Some(Segments::Joined(text)) => text.clone(),
+        Some(Segments::Split(segments)) => segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Text(text) => text.as_str(),
+                Segment::Object { value } => value.as_deref().unwrap_or_default(),
+            })
+            .collect::<Vec<_>>()
+            .join(separator),
+        None => String::new(),
+    };
+    let mut url = String::new();
+    if let Some(protocol) = &parts.protocol {
+        url.push_str(&format!("{}://", protocol));
+    }
+    url.push_str(&join(&parts.host, "."));
+    if let Some(port) = &parts.port {
+        url.push_str(&format!(":{}", port));
+    }
+    let path = join(&parts.path, "/");
+    if !path.is_empty() {
+        url.push('/');
+        url.push_str(path.trim_start_matches('/'));
+    }
+    url
+}
+
+/// `:name` path segments after the host become `{{name}}`
+fn path_variables(url: &str) -> String {
+    let (scheme, rest) = match url.split_once("://") {
+        Some((scheme, rest)) => (format!("{}://", scheme), rest),
+        None => (String::new(), url),
+    };
+    let (rest, query) = match rest.split_once('?') {
+        Some((rest, query)) => (rest, format!("?{}", query)),
+        None => (rest, String::new()),
+    };
+    let segments: Vec<String> = rest
+        .split('/')
+        .enumerate()
+        .map(|(index, segment)| match segment.strip_prefix(':') {
+            Some(name)
+                if index > 0
+                    && !name.is_empty()
+                    && name
+                        .chars()
+                        .all(|c| c.is_alphanumeric() || c == '_' || c == '-') =>
+            {
+                format!("{{{{{}}}}}", name)
+            }
+            _ => segment.to_string(),
+        })
+        .collect();
+    format!("{}{}{}", scheme, segments.join("/"), query)
+}
+
+/// Postman values may be strings, numbers or booleans
+fn text(value: Option<&Value>) -> String {
+    match value {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Null) | None => String::new(),
+        Some(other) => other.to_string(),
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Json(_) => write!(f, "invalid Postman export"),
+            Error::UnsupportedSchema(schema) => write!(
+                f,
+                "unsupported Postman schema `{}`, expected v2.0 or v2.1",
+                schema
+            ),
+            Error::Collection(_) => write!(f, "could not build the collection"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Json(e) => Some(e),
+            Error::Collection(e) => Some(e),
+            Error::UnsupportedSchema(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_collection, parse_environment, Error};
+    use crate::{
+        body::{MultipartPart, RequestBody},
+        multimap::MultiMap,
+        request::RequestMethod,
+    };
+    use serde_json::json;
+    use std::path::PathBuf;
+
+    const COLLECTION: &str = r#"{
+        "info": {
+            "name": "Shop",
+            "description": "Orders API",
+            "schema": "https://schema.getpostman.com/json/collection/v2.1.0/collection.json"
+        },
+        "auth": {"type": "bearer", "bearer": [{"key": "token", "value": "{{token}}", "type": "string"}]},
+        "variable": [
+            {"key": "base", "value": "https://shop.test"},
+            {"key": "old", "value": "x", "disabled": true}
+        ],
+        "event": [{"listen": "prerequest", "script": {"exec": ["pm.environment.set('a', 1)"]}}],
+        "item": [
+            {
+                "name": "Orders",
+                "variable": [{"key": "limit", "value": 20}],
+                "item": [
+                    {
+                        "name": "GET /orders/:id",
+                        "request": {
+                            "method": "GET",
+                            "header": [
+                                {"key": "Accept", "value": "application/json"},
+                                {"key": "X-Debug", "value": "1", "disabled": true}
+                            ],
+                            "url": {
+                                "raw": "{{base}}/orders/:id?expand=items&draft=1",
+                                "host": ["{{base}}"],
+                                "path": ["orders", ":id"],
+                                "query": [
+                                    {"key": "expand", "value": "items"},
+                                    {"key": "draft", "value": "1", "disabled": true}
+                                ],
+                                "variable": [{"key": "id", "value": "42"}]
+                            }
+                        },
+                        "event": [{"listen": "test", "script": {"exec": ["pm.test('ok')"]}}],
+                        "response": [{"name": "Example"}]
+                    },
+                    {
+                        "name": "Create",
+                        "request": {
+                            "method": "POST",
+                            "header": [{"key": "Content-Type", "value": "application/json"}],
+                            "url": "{{base}}/orders",
+                            "body": {
+                                "mode": "raw",
+                                "raw": "{\"ref\": \"{{$guid}}\", \"at\": {{$timestamp}}, \"who\": \"{{$randomFirstName}}\"}",
+                                "options": {"raw": {"language": "json"}}
+                            },
+                            "auth": {"type": "basic", "basic": [
+                                {"key": "username", "value": "john"},
+                                {"key": "password", "value": "secret"}
+                            ]}
+                        }
+                    }
+                ]
+            },
+            {
+                "name": "Upload",
+                "request": {
+                    "method": "PUT",
+                    "url": {"protocol": "https", "host": ["files", "shop", "test"], "port": "8443", "path": ["v1", "upload"]},
+                    "body": {"mode": "formdata", "formdata": [
+                        {"key": "title", "value": "Q3", "type": "text"},
+                        {"key": "file", "src": ["/tmp/a.pdf", "/tmp/b.pdf"], "type": "file", "contentType": "application/pdf"},
+                        {"key": "none", "type": "file"}
+                    ]},
+                    "auth": {"type": "apikey", "apikey": [
+                        {"key": "key", "value": "api_key"},
+                        {"key": "value", "value": "k3y"},
+                        {"key": "in", "value": "query"}
+                    ]}
+                }
+            },
+            {
+                "name": "Query",
+                "request": {
+                    "method": "POST",
+                    "url": "{{base}}/graphql",
+                    "body": {"mode": "graphql", "graphql": {"query": "{ me { id } }", "variables": "{\"a\": 1}"}},
+                    "auth": {"type": "oauth2", "oauth2": []}
+                }
+            },
+            {"name": "Upload", "request": "https://shop.test/ping"},
+            {
+                "name": "Status",
+                "request": {
+                    "url": "https://shop.test/status",
+                    "auth": {"type": "apikey", "apikey": [{"key": "value", "value": "k3y"}]}
+                }
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn import_folders_requests_and_auth() {
+        let import = parse_collection(COLLECTION).unwrap();
+        let collection = import.collection;
+        assert_eq!("Shop", collection.info().name);
+        assert_eq!(Some("Orders API"), collection.info().description.as_deref());
+        assert_eq!(1, collection.variables().len());
+        assert_eq!(
+            vec![
+                "Orders/GET -orders-:id",
+                "Orders/Create",
+                "Upload",
+                "Query",
+                "Upload (2)",
+                "Status",
+            ],
+            collection
+                .requests()
+                .into_iter()
+                .map(|(path, _)| path)
+                .collect::<Vec<_>>()
+        );
+
+        let get = collection.get("Orders/GET -orders-:id").unwrap();
+        assert_eq!("{{base}}/orders/{{id}}", get.request.url());
+        assert_eq!(&MultiMap::from([("expand", "items")]), get.request.params());
+        assert_eq!(
+            &MultiMap::from([
+                ("Accept", "application/json"),
+                ("Authorization", "Bearer {{token}}")
+            ]),
+            get.request.headers()
+        );
+        assert_eq!("42", get.variables["id"]);
+        assert_eq!("20", get.variables["limit"]);
+
+        let create = collection.get("Orders/Create").unwrap();
+        assert_eq!(
+            Some("Basic am9objpzZWNyZXQ="),
+            create.request.headers().get("Authorization")
+        );
+        assert_eq!(
+            Some(&RequestBody::Raw {
+                content:
+                    r#"{"ref": "{{$uuid}}", "at": {{$timestamp}}, "who": "{{$randomFirstName}}"}"#
+                        .to_string(),
+                content_type: "application/json".to_string(),
+            }),
+            create.request.body()
+        );
+
+        let upload = collection.get("Upload").unwrap();
+        assert_eq!(&RequestMethod::PUT, upload.request.method());
+        assert_eq!(
+            "https://files.shop.test:8443/v1/upload",
+            upload.request.url()
+        );
+        assert_eq!(
+            &MultiMap::from([("api_key", "k3y")]),
+            upload.request.params()
+        );
+        assert_eq!(
+            Some(&RequestBody::Multipart(vec![
+                MultipartPart::Text {
+                    name: "title".to_string(),
+                    value: "Q3".to_string()
+                },
+                MultipartPart::File {
+                    name: "file".to_string(),
+                    path: PathBuf::from("/tmp/a.pdf"),
+                    content_type: Some("application/pdf".to_string()),
+                },
+            ])),
+            upload.request.body()
+        );
+
+        let query = collection.get("Query").unwrap();
+        assert_eq!(
+            Some(&RequestBody::Json(
+                json!({"query": "{ me { id } }", "variables": {"a": 1}})
+            )),
+            query.request.body()
+        );
+        assert!(query.request.headers().is_empty());
+        assert_eq!(
+            "https://shop.test/ping",
+            collection.get("Upload (2)").unwrap().request.url()
+        );
+        let status = collection.get("Status").unwrap();
+        assert!(status.request.headers().is_empty());
+        assert!(status.request.params().is_empty());
+    }
+
+    #[test]
+    fn report_what_is_not_mapped() {
+        let warnings = parse_collection(COLLECTION).unwrap().warnings;
+        assert_eq!(
+            vec![
+                "the collection: disabled variable `old` skipped",
+                "the collection: pre-request script skipped",
+                "`GET /orders/:id` renamed to `Orders/GET -orders-:id`",
+                "`Orders/GET -orders-:id`: test script skipped",
+                "`Orders/GET -orders-:id`: 1 saved example responses skipped",
+                "`Orders/GET -orders-:id`: disabled query parameter `draft` skipped",
+                "`Orders/GET -orders-:id`: disabled header `X-Debug` skipped",
+                "`Upload`: form field `file` has 2 files, only the first is kept",
+                "`Upload`: form field `none` has no file selected, skipped",
+                "`Query`: oauth2 auth is not supported, skipped",
+                "`Upload` renamed to `Upload (2)`",
+                "`Status`: API key auth has no key name, skipped",
+                "`{{$randomFirstName}}` has no built-in equivalent, define it as a variable",
+            ],
+            warnings
+        );
+    }
+
+    #[test]
+    fn reject_other_schemas() {
+        assert!(matches!(
+            parse_collection(r#"{"id": "1", "name": "old", "requests": []}"#),
+            Err(Error::UnsupportedSchema(schema)) if schema == "v1"
+        ));
+        assert!(matches!(parse_collection("[]"), Err(Error::Json(_))));
+    }
+
+    #[test]
+    fn import_environment() {
+        let import = parse_environment(
+            r#"{
+                "name": "staging",
+                "values": [
+                    {"key": "base", "value": "https://staging.shop.test", "enabled": true},
+                    {"key": "retries", "value": 3},
+                    {"key": "token", "value": "t", "type": "secret", "enabled": false}
+                ],
+                "_postman_variable_scope": "environment"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!("staging", import.environment.name);
+        assert_eq!("3", import.environment.variables["retries"]);
+        assert_eq!(2, import.environment.variables.len());
+        assert_eq!(
+            vec!["disabled variable `token` of environment `staging` skipped"],
+            import.warnings
+        );
+    }
+}
@@ -0,0 +1,728 @@
+use crate::{
+    har,
+    multimap::MultiMap,
+    runner::{Outcome, RequestResult, RunSummary},
+};
+use serde::Serialize;
+use std::{error, fmt, fs, io, path::PathBuf, str::FromStr, time::Duration};
+
+/// Headers whose values are hidden in reports, they carry credentials
+const SENSITIVE_HEADERS: [&str; 4] = [
+    "authorization",
+    "cookie",
+    "proxy-authorization",
+    "set-cookie",
+];
+
+/// Last words of header names that carry credentials, e.g. `X-Api-Key` or `X-Auth-Token`
+const SENSITIVE_SUFFIXES: [&str; 5] = ["apikey", "key", "password", "secret", "token"];
+
+/// Whether reports hide the value of header `name`
+pub(crate) fn sensitive_header(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    let last = name.rsplit(['-', '_']).next().unwrap_or_default();
+    SENSITIVE_HEADERS.contains(&name.as_str()) || SENSITIVE_SUFFIXES.contains(&last)
+}
+
+/// Machine-readable outputs of a collection run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Junit,
+    Tap,
+    Json,
+    Html,
+    Har,
+}
+
+/// A format and where to write it, parsed from `junit:out.xml` or `tap`.
+/// Without a path the report goes to stdout.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reporter {
+    pub format: ReportFormat,
+    pub path: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnknownFormat(String);
+
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    passed: bool,
+    stats: Stats,
+    #[serde(flatten)]
+    summary: &'a RunSummary,
+}
+
+#[derive(Serialize)]
+struct Stats {
+    requests: usize,
+    failed_requests: usize,
+    assertions: usize,
+    failed_assertions: usize,
+}
+
+impl ReportFormat {
+    pub fn render(self, summary: &RunSummary) -> String {
+        match self {
+            ReportFormat::Junit => junit(summary),
+            ReportFormat::Tap => tap(summary),
+            ReportFormat::Json => json(summary),
+            ReportFormat::Html => html(summary),
+            ReportFormat::Har => har::export(summary),
+        }
+    }
+}
+
+impl Reporter {
+    pub fn write(&self, summary: &RunSummary) -> io::Result<()> {
+        let report = self.format.render(summary);
+        match &self.path {
+            Some(path) => fs::write(path, report),
+            None => {
+                print!("{}", report);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// JUnit XML, one test suite per iteration and one test case per request
+pub fn junit(summary: &RunSummary) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(&format!(
+        "<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{}\">\n",
+        xml(&summary.collection),
+        summary.results.len(),
+        count(summary, |r| !r.passed() && !is_error(r)),
+        count(summary, is_error),
+        seconds(summary.elapsed)
+    ));
+
+    let iterations = summary.results.last().map_or(0, |r| r.iteration + 1);
+    for iteration in 0..iterations {
+        let results: Vec<_> = summary
+            .results
+            .iter()
+            .filter(|r| r.iteration == iteration)
+            .collect();
+        let name = match iterations {
+            1 => summary.collection.clone(),
+            _ => format!("{} (iteration {})", summary.collection, iteration + 1),
+        };
+        let elapsed = results.iter().map(|r| elapsed(r)).sum();
+        out.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{}\">\n",
+            xml(&name),
+            results.len(),
+            results
+                .iter()
+                .filter(|r| !r.passed() && !is_error(r))
+                .count(),
+            results.iter().filter(|r| is_error(r)).count(),
+            seconds(elapsed)
+        ));
+        for result in results {
+            junit_case(&mut out, &summary.collection, result);
+        }
+        out.push_str("  </testsuite>\n");
+    }
+    out.push_str("</testsuites>\n");
+    out
+}
+
+fn junit_case(out: &mut String, collection: &str, result: &RequestResult) {
+    // Folders become the class name, so CI tools group requests like packages
+    let classname = match result.path.rsplit_once('/') {
+        Some((folder, _)) => format!("{}.{}", collection, folder.replace('/', ".")),
+        None => collection.to_string(),
+    };
+    out.push_str(&format!(
+        "    <testcase name=\"{}\" classname=\"{}\" time=\"{}\">\n",
+        xml(&result.path),
+        xml(&classname),
+        seconds(elapsed(result))
+    ));
+
+    let snippet = result
+        .snippet
+        .as_ref()
+        .map(|snippet| format!("\n\nResponse:\n{}", snippet))
+        .unwrap_or_default();
+    match &result.outcome {
+        Outcome::Error(error) => out.push_str(&format!(
+            "      <error message=\"{}\" type=\"RequestError\">{}{}</error>\n",
+            xml(error),
+            xml(error),
+            xml(&snippet)
+        )),
+        Outcome::Response { .. } if !result.passed() => {
+            let failures: Vec<_> = result
+                .assertions
+                .iter()
+                .filter(|a| !a.passed)
+                .map(|a| format!("{}: expected {}, got {}", a.name, a.expected, a.actual))
+                .collect();
+            out.push_str(&format!(
+                "      <failure message=\"{}\" type=\"AssertionFailure\">{}{}</failure>\n",
+                xml(&format!(
+                    "{} of {} assertions failed",
+                    failures.len(),
+                    result.assertions.len()
+                )),
+                xml(&failures.join("\n")),
+                xml(&snippet)
+            ));
+        }
+        Outcome::Response { .. } => {}
+    }
+    out.push_str(&format!(
+        "      <system-out>{}</system-out>\n",
+        xml(&request_line(result))
+    ));
+    out.push_str("    </testcase>\n");
+}
+
+/// TAP version 13, failures carry a YAML block with the details
+pub fn tap(summary: &RunSummary) -> String {
+    let mut out = format!("TAP version 13\n1..{}\n", summary.results.len());
+    let iterations = summary.results.last().map_or(0, |r| r.iteration + 1);
+    for (index, result) in summary.results.iter().enumerate() {
+        let iteration = match iterations {
+            1 => String::new(),
+            _ => format!(" [iteration {}]", result.iteration + 1),
+        };
+        out.push_str(&format!(
+            "{} {} - {}{} # {}\n",
+            if result.passed() { "ok" } else { "not ok" },
+            index + 1,
+            result.path,
+            iteration,
+            request_line(result)
+        ));
+        if result.passed() {
+            continue;
+        }
+
+        out.push_str("  ---\n");
+        if let Outcome::Error(error) = &result.outcome {
+            out.push_str(&format!("  error: {}\n", yaml(error)));
+        }
+        let failures: Vec<_> = result.assertions.iter().filter(|a| !a.passed).collect();
+        if !failures.is_empty() {
+            out.push_str("  failures:\n");
+            for failure in failures {
+                out.push_str(&format!(
+                    "    - name: {}\n      expected: {}\n      actual: {}\n",
+                    yaml(&failure.name),
+                    yaml(&failure.expected),
+                    yaml(&failure.actual)
+                ));
+            }
+        }
+        if let Some(snippet) = &result.snippet {
+            out.push_str("  response: |\n");
+            for line in snippet.lines() {
+                out.push_str(&format!("    {}\n", line));
+            }
+        }
+        out.push_str("  ...\n");
+    }
+    if summary.bailed {
+        out.push_str("Bail out! Stopped at the first failure\n");
+    }
+    out
+}
+
+/// The run summary as pretty JSON, with aggregate stats
+pub fn json(summary: &RunSummary) -> String {
+    let assertions: Vec<_> = summary.assertions().collect();
+    let report = JsonReport {
+        passed: summary.passed(),
+        stats: Stats {
+            requests: summary.results.len(),
+            failed_requests: summary.failed_requests(),
+            assertions: assertions.len(),
+            failed_assertions: assertions.iter().filter(|a| !a.passed).count(),
+        },
+        summary,
+    };
+    let mut out = serde_json::to_string_pretty(&report).expect("run summaries serialize to JSON");
+    out.push('\n');
+    out
+}
+
+/// A self-contained HTML page for people: the summary, a chart of the
+/// outcomes and timings, then every request with its exchange
+pub fn html(summary: &RunSummary) -> String {
+    let mut out = String::new();
+    let title = format!("{} - run report", summary.collection);
+    out.push_str(&format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{}</title>\n<style>{}</style>\n</head>\n<body>\n",
+        xml(&title),
+        HTML_STYLE
+    ));
+
+    let assertions: Vec<_> = summary.assertions().collect();
+    out.push_str(&format!(
+        "<h1>{}</h1>\n<p class=\"{}\"><strong>{}</strong> &middot; {} requests, {} failed \
+         &middot; {} assertions, {} failed &middot; {}ms{}</p>\n",
+        xml(&summary.collection),
+        if summary.passed() { "pass" } else { "fail" },
+        if summary.passed() { "Passed" } else { "Failed" },
+        summary.results.len(),
+        summary.failed_requests(),
+        assertions.len(),
+        assertions.iter().filter(|a| !a.passed).count(),
+        summary.elapsed.as_millis(),
+        if summary.bailed {
+            " &middot; stopped at the first failure"
+        } else {
+            ""
+        }
+    ));
+    out.push_str(&chart(summary));
+
+    let iterations = summary.results.last().map_or(0, |r| r.iteration + 1);
+    for result in &summary.results {
+        html_request(&mut out, result, iterations > 1);
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+const HTML_STYLE: &str = "\
+body{font-family:system-ui,sans-serif;margin:2em;color:#222}\
+h1{margin-bottom:.2em}\
+.pass{color:#1a7f37}.fail{color:#cf222e}\
+details{border:1px solid #ddd;border-radius:6px;margin:.5em 0;padding:.4em .8em}\
+details.fail{border-color:#cf222e}\
+summary{cursor:pointer}\
+summary .badge{display:inline-block;width:3em;font-weight:bold}\
+summary code{font-weight:bold}\
+.muted{color:#777}\
+table{border-collapse:collapse;margin:.5em 0}\
+td,th{border:1px solid #ddd;padding:.2em .6em;text-align:left;vertical-align:top}\
+pre{background:#f6f8fa;padding:.6em;overflow:auto;max-height:30em}\
+h4{margin:.8em 0 .2em}";
+
+/// Bar of passed, failed and errored requests, then one timing bar per request
+fn chart(summary: &RunSummary) -> String {
+    const WIDTH: f64 = 720.0;
+    const LABEL: f64 = 240.0;
+    const ROW: f64 = 18.0;
+    let total = summary.results.len().max(1) as f64;
+    let errors = count(summary, is_error);
+    let failures = summary.failed_requests() - errors;
+    let passes = summary.results.len() - summary.failed_requests();
+
+    let rows = summary.results.len() as f64;
+    let height = 60.0 + rows * ROW;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" \
+         font-size=\"12\" role=\"img\" aria-label=\"Run summary chart\">\n",
+        WIDTH, height
+    );
+
+    let mut x = 0.0;
+    for (n, color, label) in [
+        (passes, "#2da44e", "passed"),
+        (failures, "#cf222e", "failed"),
+        (errors, "#bf8700", "errors"),
+    ] {
+        let width = WIDTH * n as f64 / total;
+        if n > 0 {
+            svg.push_str(&format!(
+                "<rect x=\"{:.1}\" y=\"0\" width=\"{:.1}\" height=\"20\" fill=\"{}\">\
+                 <title>{} {}</title></rect>\n",
+                x, width, color, n, label
+            ));
+        }
+        x += width;
+    }
+    svg.push_str(&format!(
+        "<text x=\"0\" y=\"38\">{} passed, {} failed, {} errors</text>\n",
+        passes, failures, errors
+    ));
+
+    let slowest = summary
+        .results
+        .iter()
+        .map(|r| elapsed(r).as_secs_f64())
+        .fold(0.0, f64::max);
+    for (index, result) in summary.results.iter().enumerate() {
+        let y = 50.0 + index as f64 * ROW;
+        let ms = elapsed(result).as_millis();
+        let width = match slowest {
+            0.0 => 0.0,
+            _ => (WIDTH - LABEL - 60.0) * elapsed(result).as_secs_f64() / slowest,
+        };
+        let color = match (result.passed(), is_error(result)) {
+            (true, _) => "#2da44e",
+            (false, false) => "#cf222e",
+            (false, true) => "#bf8700",
+        };
+        svg.push_str(&format!(
+            "<text x=\"0\" y=\"{:.1}\">{}</text>\
+             <rect x=\"{}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{}\" fill=\"{}\"/>\
+             <text x=\"{:.1}\" y=\"{:.1}\">{}ms</text>\n",
+            y + 12.0,
+            xml(&truncate_label(&result.path, 36)),
+            LABEL,
+            y + 2.0,
+            width,
+            ROW - 4.0,
+            color,
+            LABEL + width + 4.0,
+            y + 12.0,
+            ms
+        ));
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn truncate_label(label: &str, max: usize) -> String {
+    match label.char_indices().nth(max) {
+        Some((end, _)) => format!("{}...", &label[..end]),
+        None => label.to_string(),
+    }
+}
+
+fn html_request(out: &mut String, result: &RequestResult, show_iteration: bool) {
+    let passed = result.passed();
+    let status = match &result.outcome {
+        Outcome::Response {
+            status, elapsed, ..
+        } => format!("{} &middot; {}ms", status, elapsed.as_millis()),
+        Outcome::Error(_) => "error".to_string(),
+    };
+    let iteration = match show_iteration {
+        true => format!(
+            " <span class=\"muted\">(iteration {})</span>",
+            result.iteration + 1
+        ),
+        false => String::new(),
+    };
+    out.push_str(&format!(
+        "<details class=\"{}\"{}>\n<summary><span class=\"badge {}\">{}</span> {}{} \
+         &middot; <code>{}</code> {} &middot; {}</summary>\n",
+        if passed { "pass" } else { "fail" },
+        if passed { "" } else { " open" },
+        if passed { "pass" } else { "fail" },
+        if passed { "PASS" } else { "FAIL" },
+        xml(&result.path),
+        iteration,
+        xml(&result.method),
+        xml(&result.url),
+        status
+    ));
+
+    if let Outcome::Error(error) = &result.outcome {
+        out.push_str(&format!("<p class=\"fail\">{}</p>\n", xml(error)));
+    }
+    if !result.assertions.is_empty() {
+        out.push_str(
+            "<table>\n<tr><th></th><th>Assertion</th><th>Expected</th><th>Actual</th></tr>\n",
+        );
+        for assertion in &result.assertions {
+            out.push_str(&format!(
+                "<tr class=\"{}\"><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                if assertion.passed { "pass" } else { "fail" },
+                if assertion.passed {
+                    "&#10003;"
+                } else {
+                    "&#10007;"
+                },
+                xml(&assertion.name),
+                xml(&assertion.expected),
+                xml(&assertion.actual)
+            ));
+        }
+        out.push_str("</table>\n");
+    }
+    if let Some(exchange) = &result.exchange {
+        let headers = |headers: &MultiMap| {
+            headers
+                .iter()
+                .map(|(name, value)| match sensitive_header(name) {
+                    true => format!("{}: [redacted]", name),
+                    false => format!("{}: {}", name, value),
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        let mut section = |title: &str, content: &str| {
+            out.push_str(&format!(
+                "<h4>{}</h4>\n<pre>{}</pre>\n",
+                title,
+                xml(content)
+            ));
+        };
+        section("Request headers", &headers(&exchange.request_headers));
+        if let Some(body) = &exchange.request_body {
+            section("Request body", body);
+        }
+        section("Response headers", &headers(&exchange.response_headers));
+        section("Response body", &exchange.response_body);
+    }
+    out.push_str("</details>\n");
+}
+
+fn is_error(result: &RequestResult) -> bool {
+    matches!(result.outcome, Outcome::Error(_))
+}
+
+fn count(summary: &RunSummary, filter: impl Fn(&RequestResult) -> bool) -> usize {
+    summary.results.iter().filter(|r| filter(r)).count()
+}
+
+fn elapsed(result: &RequestResult) -> Duration {
+    match result.outcome {
+        Outcome::Response { elapsed, .. } => elapsed,
+        Outcome::Error(_) => Duration::ZERO,
+    }
+}
+
+fn seconds(duration: Duration) -> String {
+    format!("{:.3}", duration.as_secs_f64())
+}
+
+/// e.g. `GET http://localhost/users -> 200 in 12ms`
+fn request_line(result: &RequestResult) -> String {
+    match &result.outcome {
+        Outcome::Response {
+            status, elapsed, ..
+        } => format!(
+            "{} {} -> {} in {}ms",
+            result.method,
+            result.url,
+            status,
+            elapsed.as_millis()
+        ),
+        Outcome::Error(_) => format!("{} {} -> error", result.method, result.url),
+    }
+}
+
+/// Escapes text for attributes and content, dropping characters XML 1.0 can't hold
+fn xml(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\t' | '\n' | '\r' => out.push(c),
+            c if c < ' ' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// A double-quoted YAML scalar, JSON strings are valid YAML
+fn yaml(text: &str) -> String {
+    serde_json::Value::from(text).to_string()
+}
+
+impl FromStr for Reporter {
+    type Err = UnknownFormat;
+
+    fn from_str(s: &str) -> Result<Reporter, UnknownFormat> {
+        let (format, path) = match s.split_once(':') {
+            Some((format, path)) => (format, Some(PathBuf::from(path))),
+            None => (s, None),
+        };
+        let format = match format.to_ascii_lowercase().as_str() {
+            "junit" => ReportFormat::Junit,
+            "tap" => ReportFormat::Tap,
+            "json" => ReportFormat::Json,
+            "html" => ReportFormat::Html,
+            "har" => ReportFormat::Har,
+            _ => return Err(UnknownFormat(format.to_string())),
+        };
+        Ok(Reporter { format, path })
+    }
+}
+
+impl fmt::Display for UnknownFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown report format `{}`, expected junit, tap, json, html or har",
+            self.0
+        )
+    }
+}
+
+impl error::Error for UnknownFormat {}
+
+#[cfg(test)]
+mod tests {
+    use super::{html, json, junit, tap, ReportFormat, Reporter};
+    use crate::{
+        assertion::AssertionResult,
+        multimap::MultiMap,
+        runner::{Exchange, Outcome, RequestResult, RunSummary},
+    };
+    use std::{path::PathBuf, time::Duration};
+
+    fn result(path: &str, status: u16, passed: bool) -> RequestResult {
+        RequestResult {
+            iteration: 0,
+            path: path.to_string(),
+            method: "GET".to_string(),
+            url: format!("http://localhost/{}", path),
+            outcome: Outcome::Response {
+                status,
+                elapsed: Duration::from_millis(12),
+                size: 2,
+            },
+            assertions: vec![AssertionResult {
+                name: "status is 200".to_string(),
+                passed,
+                expected: "200".to_string(),
+                actual: status.to_string(),
+            }],
+            snippet: (!passed).then(|| "{\"error\": \"<not found>\"}".to_string()),
+            exchange: Some(Exchange {
+                started: "2024-05-01T10:00:00.000Z".to_string(),
+                url: format!("http://localhost/{}?page=2", path),
+                http_version: "HTTP/1.1".to_string(),
+                status,
+                waiting: Duration::from_millis(8),
+                dns: None,
+                request_headers: MultiMap::from([
+                    ("accept", "application/json"),
+                    ("Authorization", "Bearer s3cret"),
+                    ("X-Api-Key", "s3cret-key"),
+                ]),
+                request_body: None,
+                response_headers: MultiMap::from([
+                    ("content-type", "application/json"),
+                    ("Set-Cookie", "session=s3cret; HttpOnly"),
+                ]),
+                response_body: "{\n  \"ok\": true\n}".to_string(),
+            }),
+        }
+    }
+
+    fn summary() -> RunSummary {
+        RunSummary {
+            collection: "shop".to_string(),
+            results: vec![
+                result("auth/login", 200, true),
+                result("orders/get", 404, false),
+                RequestResult {
+                    outcome: Outcome::Error("unresolved variables: token".to_string()),
+                    assertions: Vec::new(),
+                    snippet: None,
+                    exchange: None,
+                    ..result("orders/delete", 0, true)
+                },
+            ],
+            elapsed: Duration::from_millis(1500),
+            bailed: false,
+        }
+    }
+
+    #[test]
+    fn parse_reporter_specs() {
+        assert_eq!(
+            Reporter {
+                format: ReportFormat::Junit,
+                path: Some(PathBuf::from("out.xml")),
+            },
+            "junit:out.xml".parse().unwrap()
+        );
+        assert_eq!(
+            Reporter {
+                format: ReportFormat::Tap,
+                path: None,
+            },
+            "TAP".parse().unwrap()
+        );
+        assert!("xunit:out.xml".parse::<Reporter>().is_err());
+    }
+
+    #[test]
+    fn junit_cases_failures_and_errors() {
+        let report = junit(&summary());
+        assert!(report.contains(
+            "<testsuites name=\"shop\" tests=\"3\" failures=\"1\" errors=\"1\" time=\"1.500\">"
+        ));
+        assert!(report
+            .contains("<testcase name=\"auth/login\" classname=\"shop.auth\" time=\"0.012\">"));
+        assert!(report.contains(
+            "<failure message=\"1 of 1 assertions failed\" type=\"AssertionFailure\">\
+             status is 200: expected 200, got 404\n\nResponse:\n\
+             {&quot;error&quot;: &quot;&lt;not found&gt;&quot;}</failure>"
+        ));
+        assert!(report.contains("<error message=\"unresolved variables: token\""));
+        assert_eq!(1, report.matches("<testsuite ").count());
+    }
+
+    #[test]
+    fn tap_lines_and_diagnostics() {
+        let report = tap(&summary());
+        let lines: Vec<_> = report.lines().collect();
+        assert_eq!(
+            &[
+                "TAP version 13",
+                "1..3",
+                "ok 1 - auth/login # GET http://localhost/auth/login -> 200 in 12ms",
+                "not ok 2 - orders/get # GET http://localhost/orders/get -> 404 in 12ms",
+                "  ---",
+                "  failures:",
+                "    - name: \"status is 200\"",
+                "      expected: \"200\"",
+                "      actual: \"404\"",
+                "  response: |",
+                "    {\"error\": \"<not found>\"}",
+                "  ...",
+                "not ok 3 - orders/delete # GET http://localhost/orders/delete -> error",
+                "  ---",
+                "  error: \"unresolved variables: token\"",
+                "  ...",
+            ],
+            lines.as_slice()
+        );
+    }
+
+    #[test]
+    fn json_with_stats() {
+        let report: serde_json::Value = serde_json::from_str(&json(&summary())).unwrap();
+        assert_eq!(false, report["passed"]);
+        assert_eq!(3, report["stats"]["requests"]);
+        assert_eq!(2, report["stats"]["failed_requests"]);
+        assert_eq!(1, report["stats"]["failed_assertions"]);
+        assert_eq!(1500, report["elapsed_ms"]);
+        assert_eq!(404, report["results"][1]["outcome"]["response"]["status"]);
+        assert_eq!(
+            "unresolved variables: token",
+            report["results"][2]["outcome"]["error"]
+        );
+        assert_eq!(None, report["results"][0].get("exchange"));
+    }
+
+    #[test]
+    fn html_is_self_contained() {
+        let report = html(&summary());
+        assert!(report.starts_with("<!DOCTYPE html>"));
+        assert!(report.contains("<svg "));
+        assert_eq!(3, report.matches("<details ").count());
+        assert!(report.contains("<details class=\"fail\" open>"));
+        assert!(report.contains("<pre>{\n  &quot;ok&quot;: true\n}</pre>"));
+        assert!(report.contains("unresolved variables: token"));
+        assert!(report.contains("Authorization: [redacted]"));
+        assert!(report.contains("X-Api-Key: [redacted]"));
+        assert!(report.contains("Set-Cookie: [redacted]"));
+        assert!(report.contains("accept: application/json"));
+        assert!(!report.contains("s3cret"));
+        for external in ["src=", "href=", "@import", "url("] {
+            assert!(!report.contains(external), "{}", external);
+        }
+    }
+}
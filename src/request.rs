@@ -1,151 +1,568 @@
+use crate::{
+    body::{RequestBody, ResponseBody},
+    multimap::MultiMap,
+};
 use convert_case::{Case, Casing};
 use reqwest::{
-    header::{HeaderName, HeaderValue},
-    Client, Url,
+    header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE},
+    Client, Method, Url,
 };
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
-use std::collections::HashMap;
+use std::{
+    error, fmt, io,
+    str::FromStr,
+    time::{Duration, Instant},
+};
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Request {
-    body: Option<String>,
-    headers: HashMap<String, String>, // Headers key is converted to kebab-case, value is untouched
+    body: Option<RequestBody>,
+    #[serde(default)]
+    headers: MultiMap, // Stored as typed, names are normalized with `header_case` when sent
     method: RequestMethod,
     url: String,
-    params: HashMap<String, String>,
+    #[serde(default)]
+    params: MultiMap,
+    #[serde(default)]
+    header_case: HeaderCase,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// How header names are rewritten before a request is sent.
+/// reqwest lowercases names on the wire (HTTP/2 requires it), so the policy
+/// decides which characters are sent, not their case.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HeaderCase {
+    Verbatim,
+    Lowercase,
+    #[default]
+    KebabFromCamel, // `randomHeader` and `X_Custom_1` become `random-header` and `x-custom-1`
+    TitleCase, // `content-type` becomes `Content-Type`
+}
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum RequestMethod {
     GET,
     POST,
+    PUT,
+    PATCH,
+    DELETE,
+    HEAD,
+    OPTIONS,
+    TRACE,
+    CONNECT,
+    Custom(String), // Any other verb, sent as-is (e.g. PROPFIND, PURGE)
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Response {
     status: u16,
-    headers: HashMap<String, String>,
-    body: Value,
+    headers: MultiMap,
+    body: ResponseBody,
+    #[serde(default)]
+    elapsed: Duration, // From sending the request to reading the whole body
+    #[serde(default)]
+    waiting: Duration, // From sending the request to receiving the headers
+    #[serde(default = "http_1_1")]
+    version: String, // e.g. `HTTP/1.1` or `HTTP/2.0`
+    #[serde(default)]
+    dns: Option<Duration>, // Host name lookups, `None` when a connection was reused or not measured
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct Error {
-    status: Option<u16>,
-    url: Option<String>,
+#[derive(Debug)]
+pub enum Error {
+    InvalidUrl {
+        url: String,
+        source: url::ParseError,
+    },
+    InvalidMethod(String),
+    InvalidHeader {
+        name: String,
+        source: Box<dyn error::Error + Send + Sync>, // Either the name or the value was rejected
+    },
+    BodyNotAllowed(RequestMethod),
+    Dns(reqwest::Error),
+    ConnectionRefused(reqwest::Error),
+    Tls(reqwest::Error),
+    Timeout(reqwest::Error),
+    RedirectLoop(reqwest::Error),
+    BodyDecode(reqwest::Error),
+    Io(io::Error),
+    Http(reqwest::Error), // Any transport failure not covered above
 }
 
 impl Request {
     pub fn new(
-        body: Option<String>,
-        headers: HashMap<String, String>,
+        body: Option<RequestBody>,
+        headers: MultiMap,
         method: RequestMethod,
         url: String,
-        params: HashMap<String, String>,
+        params: MultiMap,
     ) -> Request {
         Request {
             body,
-            headers: headers
-                .into_iter()
-                .map(|(k, v)| (k.to_case(Case::Kebab), v.to_string()))
-                .collect(),
+            headers,
             method,
             url,
             params,
+            header_case: HeaderCase::default(),
         }
     }
 
-    async fn send_request(&self) -> Result<Response, Error> {
-        let client = Client::new();
-        let headers = &self.headers;
-        let response = match &self.method {
-            RequestMethod::GET => {
-                client.get(Url::parse_with_params(&self.url, &self.params).unwrap())
+    pub fn with_header_case(mut self, header_case: HeaderCase) -> Request {
+        self.header_case = header_case;
+        self
+    }
+
+    /// Copy of the request with `f` applied to the url, header values, params
+    /// and every string inside the body, e.g. to substitute variables
+    pub fn map_strings(&self, f: &mut impl FnMut(&str) -> String) -> Request {
+        Request {
+            body: self.body.as_ref().map(|body| body.map_strings(f)),
+            headers: self.headers.iter().map(|(k, v)| (k, f(v))).collect(),
+            method: self.method.clone(),
+            url: f(&self.url),
+            params: self.params.iter().map(|(k, v)| (f(k), f(v))).collect(),
+            header_case: self.header_case,
+        }
+    }
+
+    pub fn method(&self) -> &RequestMethod {
+        &self.method
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Headers as typed, see `normalized_headers` for the names actually sent
+    pub fn headers(&self) -> &MultiMap {
+        &self.headers
+    }
+
+    pub fn params(&self) -> &MultiMap {
+        &self.params
+    }
+
+    pub fn body(&self) -> Option<&RequestBody> {
+        self.body.as_ref()
+    }
+
+    pub fn header_case(&self) -> HeaderCase {
+        self.header_case
+    }
+
+    /// Headers with their names normalized according to the request's `HeaderCase`
+    pub fn normalized_headers(&self) -> MultiMap {
+        self.headers
+            .iter()
+            .map(|(k, v)| (self.header_case.apply(k), v))
+            .collect()
+    }
+
+    /// Normalized headers plus the body's Content-Type, unless one is set explicitly
+    pub fn headers_as_sent(&self) -> MultiMap {
+        let mut headers = self.normalized_headers();
+        let content_type = self.body.as_ref().and_then(RequestBody::content_type);
+        if let Some(content_type) = content_type {
+            if headers.get_ignore_case("content-type").is_none() {
+                headers.append("content-type", content_type);
             }
-            RequestMethod::POST => client.post(&self.url),
         }
-        .headers(
-            headers
-                .into_iter()
-                .map(|(k, v)| (k.parse().unwrap(), v.parse().unwrap()))
-                .collect(),
-        )
-        .send()
-        .await;
-
-        match response {
-            Ok(response) => {
-                return Ok(Response {
-                    status: response.status().as_u16(),
-                    headers: response
-                        .headers()
-                        .iter()
-                        .map(|(k, v): (&HeaderName, &HeaderValue)| {
-                            (k.to_string(), v.to_str().unwrap().to_string())
-                        })
-                        .collect(),
-                    // May crash if there is no body in the response
-                    body: serde_json::from_str(response.text().await.ok().unwrap().as_str())
-                        .unwrap(),
-                });
+        headers
+    }
+
+    /// The url with `params` appended to its query, as sent
+    pub fn full_url(&self) -> String {
+        if self.params.is_empty() {
+            return self.url.clone();
+        }
+        match Url::parse(&self.url) {
+            Ok(mut url) => {
+                url.query_pairs_mut().extend_pairs(self.params.iter());
+                url.to_string()
             }
-            Err(error) => {
-                return Err(Error {
-                    status: error.status().map(|s| s.as_u16()),
-                    url: error.url().map(|u| u.to_string()),
+            Err(_) => {
+                let query = url::form_urlencoded::Serializer::new(String::new())
+                    .extend_pairs(self.params.iter())
+                    .finish();
+                let separator = if self.url.contains('?') { '&' } else { '?' };
+                format!("{}{}{}", self.url, separator, query)
+            }
+        }
+    }
+
+    /// Builds the reqwest request without sending it.
+    /// Headers are applied after the body so they override its default Content-Type.
+    pub async fn build_request(&self, client: &Client) -> Result<reqwest::Request, Error> {
+        let method = self
+            .method
+            .to_reqwest()
+            .ok_or_else(|| Error::InvalidMethod(self.method.to_string()))?;
+        // Bodies are sent for every method, GET included, except the ones whose
+        // semantics forbid a payload
+        if self.body.is_some() && !self.method.allows_body() {
+            return Err(Error::BodyNotAllowed(self.method.clone()));
+        }
+        let mut url = Url::parse(&self.url).map_err(|source| Error::InvalidUrl {
+            url: self.url.clone(),
+            source,
+        })?;
+        if !self.params.is_empty() {
+            url.query_pairs_mut().extend_pairs(self.params.iter());
+        }
+        let headers = header_map(&self.normalized_headers())?;
+
+        let mut builder = client.request(method, url);
+        if let Some(body) = &self.body {
+            // reqwest sets Content-Length from the body size
+            builder = body.apply(builder).await.map_err(Error::Io)?;
+        }
+        builder.headers(headers).build().map_err(Error::from)
+    }
+
+    /// Sends the request with a one-off client, use a `Session` to send many
+    pub async fn send_request(&self) -> Result<Response, Error> {
+        self.send_with(&Client::new()).await
+    }
+
+    pub(crate) async fn send_with(&self, client: &Client) -> Result<Response, Error> {
+        let request = self.build_request(client).await?;
+        let start = Instant::now();
+        let response = client.execute(request).await?;
+        let waiting = start.elapsed();
+
+        let status = response.status().as_u16();
+        let headers = response
+            .headers()
+            .iter()
+            .map(|(k, v): (&HeaderName, &HeaderValue)| {
+                // Non UTF-8 values are kept lossily rather than failing the whole response
+                (
+                    k.to_string(),
+                    String::from_utf8_lossy(v.as_bytes()).into_owned(),
+                )
+            })
+            .collect();
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let version = format!("{:?}", response.version());
+        let bytes = response.bytes().await.map_err(Error::BodyDecode)?;
+
+        Ok(Response {
+            status,
+            headers,
+            body: ResponseBody::new(bytes.to_vec(), content_type.as_deref()),
+            elapsed: start.elapsed(),
+            waiting,
+            version,
+            dns: None,
+        })
+    }
+}
+
+/// Validates header names and values, keeping repeated headers
+pub(crate) fn header_map(headers: &MultiMap) -> Result<HeaderMap, Error> {
+    let mut map = HeaderMap::new();
+    for (name, value) in headers.iter() {
+        let invalid = |source: Box<dyn error::Error + Send + Sync>| Error::InvalidHeader {
+            name: name.to_string(),
+            source,
+        };
+        map.append(
+            HeaderName::from_bytes(name.as_bytes()).map_err(|e| invalid(e.into()))?,
+            HeaderValue::from_str(value).map_err(|e| invalid(e.into()))?,
+        );
+    }
+    Ok(map)
+}
+
+impl HeaderCase {
+    pub fn apply(&self, name: &str) -> String {
+        match self {
+            HeaderCase::Verbatim => name.to_string(),
+            HeaderCase::Lowercase => name.to_ascii_lowercase(),
+            HeaderCase::KebabFromCamel => name.to_case(Case::Kebab),
+            HeaderCase::TitleCase => name
+                .split('-')
+                .map(|word| {
+                    let mut chars = word.chars();
+                    match chars.next() {
+                        Some(first) => {
+                            first.to_ascii_uppercase().to_string()
+                                + &chars.as_str().to_ascii_lowercase()
+                        }
+                        None => String::new(),
+                    }
                 })
+                .collect::<Vec<_>>()
+                .join("-"),
+        }
+    }
+}
+
+impl FromStr for HeaderCase {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "verbatim" => Ok(HeaderCase::Verbatim),
+            "lowercase" => Ok(HeaderCase::Lowercase),
+            "kebab" => Ok(HeaderCase::KebabFromCamel),
+            "title" => Ok(HeaderCase::TitleCase),
+            _ => Err(format!(
+                "invalid header case `{}`, expected verbatim, lowercase, kebab or title",
+                s
+            )),
+        }
+    }
+}
+
+impl RequestMethod {
+    pub fn as_str(&self) -> &str {
+        match self {
+            RequestMethod::GET => "GET",
+            RequestMethod::POST => "POST",
+            RequestMethod::PUT => "PUT",
+            RequestMethod::PATCH => "PATCH",
+            RequestMethod::DELETE => "DELETE",
+            RequestMethod::HEAD => "HEAD",
+            RequestMethod::OPTIONS => "OPTIONS",
+            RequestMethod::TRACE => "TRACE",
+            RequestMethod::CONNECT => "CONNECT",
+            RequestMethod::Custom(method) => method,
+        }
+    }
+
+    /// HEAD and TRACE requests must not carry a payload
+    pub fn allows_body(&self) -> bool {
+        !matches!(self, RequestMethod::HEAD | RequestMethod::TRACE)
+    }
+
+    /// Returns `None` when a custom verb is not a valid HTTP token
+    fn to_reqwest(&self) -> Option<Method> {
+        Method::from_bytes(self.as_str().as_bytes()).ok()
+    }
+}
+
+impl FromStr for RequestMethod {
+    type Err = String;
+
+    /// Standard methods are matched case-insensitively, anything else becomes a `Custom` verb
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "GET" => Ok(RequestMethod::GET),
+            "POST" => Ok(RequestMethod::POST),
+            "PUT" => Ok(RequestMethod::PUT),
+            "PATCH" => Ok(RequestMethod::PATCH),
+            "DELETE" => Ok(RequestMethod::DELETE),
+            "HEAD" => Ok(RequestMethod::HEAD),
+            "OPTIONS" => Ok(RequestMethod::OPTIONS),
+            "TRACE" => Ok(RequestMethod::TRACE),
+            "CONNECT" => Ok(RequestMethod::CONNECT),
+            _ if Method::from_bytes(s.as_bytes()).is_ok() => {
+                Ok(RequestMethod::Custom(s.to_string()))
             }
+            _ => Err(format!("invalid method: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for RequestMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Response {
+    pub fn new(status: u16, headers: MultiMap, body: ResponseBody, elapsed: Duration) -> Response {
+        Response {
+            status,
+            headers,
+            body,
+            elapsed,
+            waiting: Duration::ZERO,
+            version: http_1_1(),
+            dns: None,
+        }
+    }
+
+    pub(crate) fn with_dns(mut self, dns: Option<Duration>) -> Response {
+        self.dns = dns;
+        self
+    }
+
+    pub fn status(&self) -> u16 {
+        self.status
+    }
+
+    pub fn headers(&self) -> &MultiMap {
+        &self.headers
+    }
+
+    pub fn body(&self) -> &ResponseBody {
+        &self.body
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn waiting(&self) -> Duration {
+        self.waiting
+    }
+
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// Time spent resolving the host name, part of `waiting`
+    pub fn dns(&self) -> Option<Duration> {
+        self.dns
+    }
+}
+
+fn http_1_1() -> String {
+    "HTTP/1.1".to_string()
+}
+
+impl From<reqwest::Error> for Error {
+    /// Sorts transport failures into categories by walking the source chain,
+    /// reqwest doesn't expose DNS, refused connections or TLS as kinds of its own
+    fn from(error: reqwest::Error) -> Error {
+        if error.is_timeout() {
+            return Error::Timeout(error);
+        }
+        if error.is_redirect() {
+            return Error::RedirectLoop(error);
+        }
+        if error.is_body() || error.is_decode() {
+            return Error::BodyDecode(error);
+        }
+        if error.is_connect() {
+            let mut source = error::Error::source(&error);
+            while let Some(cause) = source {
+                if let Some(io_error) = cause.downcast_ref::<io::Error>() {
+                    if io_error.kind() == io::ErrorKind::ConnectionRefused {
+                        return Error::ConnectionRefused(error);
+                    }
+                }
+                let message = cause.to_string().to_ascii_lowercase();
+                if message.contains("dns error") || message.contains("lookup address") {
+                    return Error::Dns(error);
+                }
+                if message.contains("ssl")
+                    || message.contains("tls")
+                    || message.contains("certificate")
+                {
+                    return Error::Tls(error);
+                }
+                source = cause.source();
+            }
+        }
+        Error::Http(error)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidUrl { url, .. } => write!(f, "invalid url `{}`", url),
+            Error::InvalidMethod(method) => write!(f, "invalid method `{}`", method),
+            Error::InvalidHeader { name, .. } => write!(f, "invalid header `{}`", name),
+            Error::BodyNotAllowed(method) => write!(f, "{} requests cannot carry a body", method),
+            Error::Dns(e) => write!(f, "could not resolve host for {}", url_of(e)),
+            Error::ConnectionRefused(e) => write!(f, "connection refused by {}", url_of(e)),
+            Error::Tls(e) => write!(f, "TLS handshake failed with {}", url_of(e)),
+            Error::Timeout(e) => write!(f, "request to {} timed out", url_of(e)),
+            Error::RedirectLoop(e) => write!(f, "too many redirects from {}", url_of(e)),
+            Error::BodyDecode(_) => write!(f, "could not read response body"),
+            Error::Io(_) => write!(f, "could not read request body"),
+            Error::Http(e) => write!(f, "request to {} failed", url_of(e)),
+        }
+    }
+}
+
+fn url_of(error: &reqwest::Error) -> &str {
+    error.url().map_or("<unknown url>", |url| url.as_str())
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::InvalidUrl { source, .. } => Some(source),
+            Error::InvalidHeader { source, .. } => Some(source.as_ref()),
+            Error::InvalidMethod(_) | Error::BodyNotAllowed(_) => None,
+            Error::Dns(e)
+            | Error::ConnectionRefused(e)
+            | Error::Tls(e)
+            | Error::Timeout(e)
+            | Error::RedirectLoop(e)
+            | Error::BodyDecode(e)
+            | Error::Http(e) => Some(e),
+            Error::Io(e) => Some(e),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
-
-    use super::{Error, Request, RequestMethod, Response};
+    use super::{Error, HeaderCase, Request, RequestMethod, Response};
+    use crate::{
+        body::{MultipartPart, RequestBody},
+        echo::EchoServer,
+        multimap::MultiMap,
+    };
+    use reqwest::Client;
+    use serde_json::json;
 
     #[tokio::test]
     async fn make_get_request() {
+        let server = EchoServer::start().unwrap();
         let req = Request::new(
             None,
-            HashMap::new(),
+            MultiMap::new(),
             RequestMethod::GET,
-            String::from("https://postman-echo.com/get"),
-            HashMap::new(),
+            server.url("/get"),
+            MultiMap::new(),
         );
 
         let res = req.send_request().await;
-        assert_eq!(true, res.is_ok());
+        assert!(res.is_ok());
     }
 
     #[tokio::test]
     async fn make_get_request_with_params() {
+        let server = EchoServer::start().unwrap();
         let req = Request::new(
             None,
-            HashMap::new(),
+            MultiMap::new(),
             RequestMethod::GET,
-            String::from("https://postman-echo.com/get"),
-            HashMap::from([("name".to_string(), "john".to_string())]),
+            server.url("/get"),
+            MultiMap::from([("name".to_string(), "john".to_string())]),
         );
 
         let res: Result<Response, Error> = req.send_request().await;
-        assert_eq!(true, res.is_ok());
+        assert!(res.is_ok());
         assert_eq!("john", res.ok().unwrap().body["args"]["name"]);
     }
 
     #[tokio::test]
     async fn make_get_request_with_headers() {
+        let server = EchoServer::start().unwrap();
         let req = Request::new(
             None,
-            HashMap::from([("randomHeader".to_string(), "1337".to_string())]),
+            MultiMap::from([("randomHeader".to_string(), "1337".to_string())]),
             RequestMethod::GET,
-            String::from("https://postman-echo.com/get"),
-            HashMap::from([("name".to_string(), "john".to_string())]),
+            server.url("/get"),
+            MultiMap::from([("name".to_string(), "john".to_string())]),
         );
 
         let res = req.send_request().await;
-        assert_eq!(true, res.is_ok());
+        assert!(res.is_ok());
         assert_eq!(200, res.as_ref().ok().unwrap().status);
         assert_eq!(
             "1337",
@@ -153,20 +570,349 @@ mod tests {
         );
     }
 
-    // #[tokio::test]
-    // async fn make_get_request_with_body() {
-    //     let req = Request::new(
-    //         Some("RAWR!! x3 nuzzles! pounces on u uwu u so warm.".to_string()),
-    //         HashMap::new(),
-    //         RequestMethod::GET,
-    //         String::from("https://postman-echo.com/get"),
-    //         HashMap::new(),
-    //     );
+    #[tokio::test]
+    async fn make_head_request() {
+        let server = EchoServer::start().unwrap();
+        let req = Request::new(
+            None,
+            MultiMap::new(),
+            RequestMethod::HEAD,
+            server.url("/get"),
+            MultiMap::new(),
+        );
+
+        let res = req.send_request().await;
+        assert!(res.is_ok());
+        assert_eq!(200, res.as_ref().ok().unwrap().status);
+        assert!(res.as_ref().ok().unwrap().body.is_empty());
+    }
+
+    #[tokio::test]
+    async fn make_put_patch_delete_requests() {
+        let server = EchoServer::start().unwrap();
+        for (method, path) in [
+            (RequestMethod::PUT, "put"),
+            (RequestMethod::PATCH, "patch"),
+            (RequestMethod::DELETE, "delete"),
+        ] {
+            let req = Request::new(
+                None,
+                MultiMap::new(),
+                method,
+                server.url(&format!("/{}", path)),
+                MultiMap::new(),
+            );
+
+            let res = req.send_request().await;
+            assert!(res.is_ok());
+            assert_eq!(200, res.as_ref().ok().unwrap().status);
+        }
+    }
+
+    #[test]
+    fn parse_request_method() {
+        assert_eq!(RequestMethod::GET, "get".parse().unwrap());
+        assert_eq!(RequestMethod::OPTIONS, "OPTIONS".parse().unwrap());
+        assert_eq!(
+            RequestMethod::Custom("PROPFIND".to_string()),
+            "PROPFIND".parse().unwrap()
+        );
+        assert_eq!("PURGE", RequestMethod::Custom("PURGE".to_string()).as_str());
+        assert!("BAD VERB".parse::<RequestMethod>().is_err());
+    }
+
+    #[tokio::test]
+    async fn make_get_request_with_body() {
+        let server = EchoServer::start().unwrap();
+        let req = Request::new(
+            Some(RequestBody::Raw {
+                content: "RAWR!! x3 nuzzles! pounces on u uwu u so warm.".to_string(),
+                content_type: "text/plain".to_string(),
+            }),
+            MultiMap::new(),
+            RequestMethod::GET,
+            server.url("/get"),
+            MultiMap::new(),
+        );
+
+        let res = req.send_request().await;
+        assert!(res.is_ok());
+        assert_eq!(200, res.as_ref().ok().unwrap().status);
+        assert_eq!(
+            "RAWR!! x3 nuzzles! pounces on u uwu u so warm.",
+            res.as_ref().ok().unwrap().body["data"]
+        );
+    }
+
+    #[tokio::test]
+    async fn make_post_request_with_body() {
+        let server = EchoServer::start().unwrap();
+        let body = "RAWR!! x3 nuzzles! pounces on u uwu u so warm.";
+        let req = Request::new(
+            Some(RequestBody::Raw {
+                content: body.to_string(),
+                content_type: "text/plain".to_string(),
+            }),
+            MultiMap::new(),
+            RequestMethod::POST,
+            server.url("/post"),
+            MultiMap::new(),
+        );
+
+        let res = req.send_request().await;
+        assert!(res.is_ok());
+        let res = res.ok().unwrap();
+        assert_eq!(200, res.status);
+        assert_eq!(body, res.body["data"]);
+        assert_eq!(
+            body.len().to_string(),
+            res.body["headers"]["content-length"]
+        );
+    }
+
+    #[tokio::test]
+    async fn reject_head_request_with_body() {
+        let req = Request::new(
+            Some(RequestBody::Raw {
+                content: "body".to_string(),
+                content_type: "text/plain".to_string(),
+            }),
+            MultiMap::new(),
+            RequestMethod::HEAD,
+            String::from("http://localhost/get"),
+            MultiMap::new(),
+        );
+
+        let res = req.send_request().await;
+        assert!(matches!(
+            res,
+            Err(Error::BodyNotAllowed(RequestMethod::HEAD))
+        ));
+    }
+
+    #[tokio::test]
+    async fn make_post_request_with_json_body() {
+        let server = EchoServer::start().unwrap();
+        let req = Request::new(
+            Some(RequestBody::Json(json!({"name": "john"}))),
+            MultiMap::new(),
+            RequestMethod::POST,
+            server.url("/post"),
+            MultiMap::new(),
+        );
+
+        let res = req.send_request().await;
+        assert!(res.is_ok());
+        assert_eq!("john", res.ok().unwrap().body["json"]["name"]);
+    }
+
+    #[tokio::test]
+    async fn body_content_type_can_be_overridden() {
+        let req = Request::new(
+            Some(RequestBody::Json(json!({"name": "john"}))),
+            MultiMap::from([(
+                "Content-Type".to_string(),
+                "application/vnd.api+json".to_string(),
+            )]),
+            RequestMethod::POST,
+            String::from("http://localhost/post"),
+            MultiMap::new(),
+        );
+
+        let built = req.build_request(&Client::new()).await.ok().unwrap();
+        assert_eq!(1, built.headers().get_all("content-type").iter().count());
+        assert_eq!("application/vnd.api+json", built.headers()["content-type"]);
+    }
+
+    #[tokio::test]
+    async fn make_request_with_no_content() {
+        let server = EchoServer::start().unwrap();
+        let req = Request::new(
+            None,
+            MultiMap::new(),
+            RequestMethod::GET,
+            server.url("/status/204"),
+            MultiMap::new(),
+        );
+
+        let res = req.send_request().await;
+        assert!(res.is_ok());
+        let res = res.ok().unwrap();
+        assert_eq!(204, res.status);
+        assert!(res.body.is_empty());
+        assert!(res.body.json().is_none());
+    }
+
+    #[tokio::test]
+    async fn invalid_url_is_an_error() {
+        let req = Request::new(
+            None,
+            MultiMap::new(),
+            RequestMethod::GET,
+            String::from("not a url"),
+            MultiMap::new(),
+        );
+
+        let res = req.send_request().await;
+        assert!(matches!(res, Err(Error::InvalidUrl { .. })));
+    }
+
+    #[tokio::test]
+    async fn invalid_header_is_an_error() {
+        let req = Request::new(
+            None,
+            MultiMap::from([("name".to_string(), "line\nbreak".to_string())]),
+            RequestMethod::GET,
+            String::from("http://localhost/"),
+            MultiMap::new(),
+        );
+
+        let res = req.send_request().await;
+        assert!(matches!(res, Err(Error::InvalidHeader { name, .. }) if name == "name"));
+    }
+
+    #[tokio::test]
+    async fn refused_connection_is_an_error() {
+        // Nothing listens on the discard port
+        let req = Request::new(
+            None,
+            MultiMap::new(),
+            RequestMethod::GET,
+            String::from("http://127.0.0.1:9/"),
+            MultiMap::new(),
+        );
+
+        let res = req.send_request().await;
+        assert!(matches!(res, Err(Error::ConnectionRefused(_))));
+        assert!(std::error::Error::source(&res.err().unwrap()).is_some());
+    }
+
+    #[tokio::test]
+    async fn repeated_headers_and_params_are_kept() {
+        let req = Request::new(
+            None,
+            MultiMap::from([("Accept", "text/html"), ("Accept", "application/json")]),
+            RequestMethod::GET,
+            String::from("http://localhost/get"),
+            MultiMap::from([("tag", "a"), ("tag", "b")]),
+        );
+
+        let built = req.build_request(&Client::new()).await.ok().unwrap();
+        assert_eq!("tag=a&tag=b", built.url().query().unwrap());
+        assert_eq!(
+            vec!["text/html", "application/json"],
+            built.headers().get_all("accept").iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn normalize_header_names() {
+        let names = ["X-API-Key", "X_Custom_1", "randomHeader", "etag"];
+        let apply = |case: HeaderCase| names.map(|name| case.apply(name));
 
-    //     let res = req.send_request().await;
-    //     assert_eq!(true, res.is_ok());
-    //     assert_eq!(200, res.as_ref().ok().unwrap().status);
-    //     dbg!(res.as_ref().ok().unwrap());
-    //     assert_eq!("1337", res.as_ref().ok().unwrap().body["args"]["body"]);
-    // }
+        assert_eq!(names, apply(HeaderCase::Verbatim));
+        assert_eq!(
+            ["x-api-key", "x_custom_1", "randomheader", "etag"],
+            apply(HeaderCase::Lowercase)
+        );
+        assert_eq!(
+            ["x-api-key", "x-custom-1", "random-header", "etag"],
+            apply(HeaderCase::KebabFromCamel)
+        );
+        assert_eq!(
+            ["X-Api-Key", "X_custom_1", "Randomheader", "Etag"],
+            apply(HeaderCase::TitleCase)
+        );
+    }
+
+    #[tokio::test]
+    async fn verbatim_header_names_are_not_rewritten() {
+        let req = Request::new(
+            None,
+            MultiMap::from([("X_Custom_1", "1")]),
+            RequestMethod::GET,
+            String::from("http://localhost/get"),
+            MultiMap::new(),
+        )
+        .with_header_case(HeaderCase::Verbatim);
+
+        let built = req.build_request(&Client::new()).await.ok().unwrap();
+        assert_eq!("1", built.headers()["x_custom_1"]);
+        assert!(built.headers().get("x-custom-1").is_none());
+    }
+
+    #[tokio::test]
+    async fn make_post_request_with_form_bodies() {
+        let server = EchoServer::start().unwrap();
+        let req = Request::new(
+            Some(RequestBody::Form(MultiMap::from([
+                ("tag", "a"),
+                ("tag", "b"),
+            ]))),
+            MultiMap::new(),
+            RequestMethod::POST,
+            server.url("/post"),
+            MultiMap::new(),
+        );
+
+        let res = req.send_request().await.ok().unwrap();
+        assert_eq!(json!(["a", "b"]), res.body["form"]["tag"]);
+
+        let req = Request::new(
+            Some(RequestBody::Multipart(vec![MultipartPart::Text {
+                name: "name".to_string(),
+                value: "john".to_string(),
+            }])),
+            MultiMap::new(),
+            RequestMethod::POST,
+            server.url("/post"),
+            MultiMap::new(),
+        );
+
+        let res = req.send_request().await.ok().unwrap();
+        assert_eq!("john", res.body["form"]["name"]);
+    }
+
+    #[tokio::test]
+    async fn make_requests_with_non_json_responses() {
+        let server = EchoServer::start().unwrap();
+        let req = Request::new(
+            None,
+            MultiMap::new(),
+            RequestMethod::GET,
+            server.url("/html"),
+            MultiMap::new(),
+        );
+
+        let res = req.send_request().await.ok().unwrap();
+        assert_eq!(Some("text/html"), res.body.content_type());
+        assert!(res.body.text().starts_with("<!DOCTYPE html>"));
+
+        let req = Request::new(
+            None,
+            MultiMap::new(),
+            RequestMethod::GET,
+            server.url("/bytes/300"),
+            MultiMap::new(),
+        );
+
+        let res = req.send_request().await.ok().unwrap();
+        assert!(!res.body.is_text());
+        assert_eq!(300, res.body.len());
+    }
+
+    #[tokio::test]
+    async fn redirect_loop_is_an_error() {
+        let server = EchoServer::start().unwrap();
+        let req = Request::new(
+            None,
+            MultiMap::new(),
+            RequestMethod::GET,
+            server.url("/redirect/20"),
+            MultiMap::new(),
+        );
+
+        let res = req.send_request().await;
+        assert!(matches!(res, Err(Error::RedirectLoop(_))));
+    }
 }
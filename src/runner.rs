@@ -0,0 +1,402 @@
+use crate::{
+    assertion::{self, AssertionResult},
+    collection::{self, Collection, SavedRequest},
+    data::Rows,
+    extract,
+    multimap::MultiMap,
+    session::Session,
+    variables::{VariableMap, Variables},
+};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Serialize, Serializer};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Characters of the response body kept for failed requests
+const SNIPPET_CHARS: usize = 1000;
+/// Characters of each body kept in the exchange
+const BODY_CHARS: usize = 100_000;
+
+/// How a collection is run
+#[derive(Debug, Clone)]
+pub struct RunOptions {
+    pub folder: Option<String>, // Only run the requests under this folder
+    pub bail: bool,             // Stop at the first failed request
+    pub delay: Duration,        // Pause between two requests
+    pub iterations: usize,      // Times the whole selection is run
+    pub data: Rows,             // One row per iteration, cycled when there are fewer
+}
+
+/// What happened to one request of a run
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct RequestResult {
+    pub iteration: usize, // Starting at 0
+    pub path: String,
+    pub method: String,
+    pub url: String, // After variable substitution, when it succeeded
+    pub outcome: Outcome,
+    pub assertions: Vec<AssertionResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>, // Start of the response body, kept when the request failed
+    // Set whenever a response came back. Kept out of JSON reports, which end up
+    // in CI artifacts and must not carry credentials or full bodies.
+    #[serde(skip)]
+    pub exchange: Option<Exchange>,
+}
+
+/// What was sent and received, with bodies in readable form
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exchange {
+    pub started: String, // RFC 3339, when the request was sent
+    pub url: String,     // With the params in the query
+    pub http_version: String,
+    pub status: u16,
+    pub waiting: Duration,     // Until the response headers arrived
+    pub dns: Option<Duration>, // Part of `waiting`, when a new connection looked up the host
+    pub request_headers: MultiMap,
+    pub request_body: Option<String>,
+    pub response_headers: MultiMap,
+    pub response_body: String,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    Response {
+        status: u16,
+        #[serde(rename = "elapsed_ms", serialize_with = "millis")]
+        elapsed: Duration,
+        size: usize,
+    },
+    /// The request could not be sent, or its response could not be used
+    Error(String),
+}
+
+/// Every result of a run, in execution order
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct RunSummary {
+    pub collection: String, // Name of the collection
+    pub results: Vec<RequestResult>,
+    #[serde(rename = "elapsed_ms", serialize_with = "millis")]
+    pub elapsed: Duration,
+    pub bailed: bool,
+}
+
+impl Default for RunOptions {
+    fn default() -> RunOptions {
+        RunOptions {
+            folder: None,
+            bail: false,
+            delay: Duration::ZERO,
+            iterations: 1,
+            data: Rows::new(),
+        }
+    }
+}
+
+impl RequestResult {
+    /// Failed when it errored or any assertion failed
+    pub fn passed(&self) -> bool {
+        matches!(self.outcome, Outcome::Response { .. }) && self.assertions.iter().all(|a| a.passed)
+    }
+}
+
+impl RunSummary {
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(RequestResult::passed)
+    }
+
+    pub fn failed_requests(&self) -> usize {
+        self.results.iter().filter(|r| !r.passed()).count()
+    }
+
+    pub fn assertions(&self) -> impl Iterator<Item = &AssertionResult> {
+        self.results.iter().flat_map(|r| &r.assertions)
+    }
+}
+
+/// Runs the selected requests of `collection` in order, `iterations` times.
+/// With data rows, iteration `i` sees the columns of row `i` as variables.
+///
+/// Each request sees what the previous ones extracted. Runtime variables start
+/// afresh on every iteration, while changes to the environment carry over.
+/// `on_result` is called as soon as each request is done, e.g. to print progress.
+pub async fn run(
+    collection: &Collection,
+    session: &Session,
+    variables: &mut Variables,
+    options: &RunOptions,
+    mut on_result: impl FnMut(&RequestResult),
+) -> Result<RunSummary, collection::Error> {
+    let requests = match &options.folder {
+        Some(folder) => collection.requests_in(folder)?,
+        None => collection.requests(),
+    };
+    variables.collection = collection.variables().clone();
+
+    let start = Instant::now();
+    let mut results = Vec::new();
+    for iteration in 0..options.iterations {
+        variables.runtime = VariableMap::new();
+        if !options.data.is_empty() {
+            variables.data = options.data[iteration % options.data.len()].clone();
+        }
+        for (path, saved) in &requests {
+            if !results.is_empty() && !options.delay.is_zero() {
+                tokio::time::sleep(options.delay).await;
+            }
+
+            let result = run_request(iteration, path, saved, session, variables).await;
+            on_result(&result);
+            let failed = !result.passed();
+            results.push(result);
+            if failed && options.bail {
+                return Ok(RunSummary {
+                    collection: collection.info().name.clone(),
+                    results,
+                    elapsed: start.elapsed(),
+                    bailed: true,
+                });
+            }
+        }
+    }
+
+    Ok(RunSummary {
+        collection: collection.info().name.clone(),
+        results,
+        elapsed: start.elapsed(),
+        bailed: false,
+    })
+}
+
+fn millis<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u128(duration.as_millis())
+}
+
+/// Resolves, sends, checks then extracts
+async fn run_request(
+    iteration: usize,
+    path: &str,
+    saved: &SavedRequest,
+    session: &Session,
+    variables: &mut Variables,
+) -> RequestResult {
+    let result = |url: &str, outcome, assertions| RequestResult {
+        iteration,
+        path: path.to_string(),
+        method: saved.request.method().to_string(),
+        url: url.to_string(),
+        outcome,
+        assertions,
+        snippet: None,
+        exchange: None,
+    };
+    let req = match variables.scope(&saved.variables).resolve(&saved.request) {
+        Ok(req) => req,
+        Err(e) => {
+            let outcome = Outcome::Error(e.to_string());
+            return result(saved.request.url(), outcome, Vec::new());
+        }
+    };
+    let started =
+        DateTime::<Utc>::from(SystemTime::now()).to_rfc3339_opts(SecondsFormat::Millis, true);
+    let res = match session.send(&req).await {
+        Ok(res) => res,
+        Err(e) => return result(req.url(), Outcome::Error(e.to_string()), Vec::new()),
+    };
+
+    let assertions = assertion::check_all(&saved.assertions, &res);
+    let outcome = match extract::apply(&saved.extract, &res, variables) {
+        Ok(()) => Outcome::Response {
+            status: res.status(),
+            elapsed: res.elapsed(),
+            size: res.body().len(),
+        },
+        Err(e) => Outcome::Error(e.to_string()),
+    };
+    let mut result = result(req.url(), outcome, assertions);
+    let response_body = res.body().preview();
+    if !result.passed() {
+        result.snippet = Some(truncate(response_body.clone(), SNIPPET_CHARS));
+    }
+    result.exchange = Some(Exchange {
+        started,
+        url: req.full_url(),
+        http_version: res.version().to_string(),
+        status: res.status(),
+        waiting: res.waiting(),
+        dns: res.dns(),
+        request_headers: req.headers_as_sent(),
+        request_body: req.body().map(|body| truncate(body.preview(), BODY_CHARS)),
+        response_headers: res.headers().clone(),
+        response_body: truncate(response_body, BODY_CHARS),
+    });
+    result
+}
+
+/// Keeps the first `max` characters of `text`
+fn truncate(mut text: String, max: usize) -> String {
+    if let Some((end, _)) = text.char_indices().nth(max) {
+        text.truncate(end);
+        text.push_str("...");
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run, Outcome, RunOptions};
+    use crate::{
+        assertion::{Assertion, StatusCheck},
+        collection::{Collection, SavedRequest},
+        echo::EchoServer,
+        extract::{Extraction, Source},
+        multimap::MultiMap,
+        request::{Request, RequestMethod},
+        session::Session,
+        variables::Variables,
+    };
+
+    fn saved(name: &str, url: &str, status: u16) -> SavedRequest {
+        let mut saved = SavedRequest::new(
+            name,
+            Request::new(
+                None,
+                MultiMap::new(),
+                RequestMethod::GET,
+                url.to_string(),
+                MultiMap::new(),
+            ),
+        );
+        saved
+            .assertions
+            .push(Assertion::Status(StatusCheck::Code(status)));
+        saved
+    }
+
+    /// `auth/login` extracts a token that `orders/get` sends back, `orders/missing` fails
+    fn collection(server: &EchoServer) -> Collection {
+        let mut collection = Collection::new("shop");
+        collection
+            .variables_mut()
+            .insert("base".to_string(), server.url(""));
+        let mut login = saved("login", "{{base}}/response-headers?token=t0k", 200);
+        login.extract.push(Extraction::new(
+            "token",
+            Source::JsonPath("$.token".to_string()),
+        ));
+        collection.add("auth", login).unwrap();
+        collection
+            .add("orders", saved("get", "{{base}}/get?token={{token}}", 200))
+            .unwrap();
+        collection
+            .add("orders", saved("missing", "{{base}}/status/404", 200))
+            .unwrap();
+        collection
+    }
+
+    #[tokio::test]
+    async fn run_every_request_in_order() {
+        let server = EchoServer::start().unwrap();
+        let collection = collection(&server);
+        let mut seen = Vec::new();
+        let summary = run(
+            &collection,
+            &Session::new(),
+            &mut Variables::new(),
+            &RunOptions {
+                iterations: 2,
+                ..RunOptions::default()
+            },
+            |result| seen.push(result.path.clone()),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            ["auth/login", "orders/get", "orders/missing"].repeat(2),
+            seen
+        );
+        assert_eq!(server.url("/get?token=t0k"), summary.results[1].url);
+        assert!(matches!(
+            summary.results[2].outcome,
+            Outcome::Response { status: 404, .. }
+        ));
+        assert!(!summary.passed());
+        assert_eq!(2, summary.failed_requests());
+        assert_eq!(None, summary.results[0].snippet);
+        assert!(summary.results[2].snippet.is_some());
+        assert_eq!(1, summary.results[5].iteration);
+    }
+
+    #[tokio::test]
+    async fn one_iteration_per_data_row() {
+        let server = EchoServer::start().unwrap();
+        let mut collection = Collection::new("users");
+        collection
+            .add(
+                "",
+                saved(
+                    "create",
+                    &server.url("/get?name={{name}}&n={{$randomInt}}"),
+                    200,
+                ),
+            )
+            .unwrap();
+        let rows = crate::data::parse_csv("name\njohn\njane\n").unwrap();
+        let summary = run(
+            &collection,
+            &Session::new(),
+            &mut Variables::new(),
+            &RunOptions {
+                iterations: rows.len(),
+                data: rows,
+                ..RunOptions::default()
+            },
+            |_| {},
+        )
+        .await
+        .unwrap();
+
+        let tagged: Vec<_> = summary
+            .results
+            .iter()
+            .map(|r| (r.iteration, r.url.split("&n=").next().unwrap().to_string()))
+            .collect();
+        assert_eq!(
+            vec![
+                (0, server.url("/get?name=john")),
+                (1, server.url("/get?name=jane"))
+            ],
+            tagged
+        );
+        assert!(summary.passed());
+    }
+
+    #[tokio::test]
+    async fn bail_and_select_a_folder() {
+        let server = EchoServer::start().unwrap();
+        let collection = collection(&server);
+        let summary = run(
+            &collection,
+            &Session::new(),
+            &mut Variables::new(),
+            &RunOptions {
+                folder: Some("orders".to_string()),
+                bail: true,
+                iterations: 3,
+                ..RunOptions::default()
+            },
+            |_| {},
+        )
+        .await
+        .unwrap();
+
+        // Without the login, the token is unresolved and the first request fails
+        assert!(summary.bailed);
+        assert_eq!(1, summary.results.len());
+        assert_eq!(
+            Outcome::Error("unresolved variables: token".to_string()),
+            summary.results[0].outcome
+        );
+    }
+}
@@ -0,0 +1,287 @@
+use crate::{
+    multimap::MultiMap,
+    request::{header_map, Error, Request, Response},
+};
+use hyper::client::connect::dns::Name;
+use reqwest::{
+    dns::{Addrs, Resolve, Resolving},
+    Certificate, Client, ClientBuilder, Proxy, Url,
+};
+use std::{
+    path::Path,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+/// A client shared by many requests, so connections, TLS sessions and
+/// HTTP/2 streams are reused between them
+#[derive(Debug, Clone)]
+pub struct Session {
+    client: Client,
+    lookups: Lookups,
+}
+
+/// Resolves host names the way reqwest does, keeping how long each lookup
+/// took since reqwest doesn't report it. Lookups only happen for new connections.
+#[derive(Debug, Clone, Default)]
+struct Lookups(Arc<Mutex<Vec<Lookup>>>);
+
+#[derive(Debug)]
+struct Lookup {
+    host: String,
+    started: Instant,
+    took: Duration,
+}
+
+/// Configuration for a `Session`, every setting is optional
+#[derive(Debug, Default)]
+pub struct SessionBuilder {
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    default_headers: MultiMap,
+    proxy: Option<String>,
+    accept_invalid_certs: bool,
+    root_certificates: Vec<Vec<u8>>, // PEM encoded
+}
+
+impl Session {
+    pub fn new() -> Session {
+        Session::builder()
+            .build()
+            .expect("a session without settings always builds")
+    }
+
+    pub fn builder() -> SessionBuilder {
+        SessionBuilder::default()
+    }
+
+    /// Sends `request` over the session's connection pool
+    pub async fn send(&self, request: &Request) -> Result<Response, Error> {
+        let started = Instant::now();
+        let response = request.send_with(&self.client).await;
+        let host = Url::parse(request.url())
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string));
+        let dns = host.and_then(|host| self.lookups.take(&host, started));
+        response.map(|response| response.with_dns(dns))
+    }
+}
+
+impl Default for Session {
+    fn default() -> Session {
+        Session::new()
+    }
+}
+
+impl SessionBuilder {
+    /// Limit for the whole exchange, from connecting to reading the body
+    pub fn timeout(mut self, timeout: Duration) -> SessionBuilder {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn connect_timeout(mut self, timeout: Duration) -> SessionBuilder {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Headers sent with every request unless the request sets them itself
+    pub fn default_header(
+        mut self,
+        name: impl Into<String>,
+        value: impl Into<String>,
+    ) -> SessionBuilder {
+        self.default_headers.append(name, value);
+        self
+    }
+
+    /// Proxy used for every scheme, e.g. `http://localhost:8080`
+    pub fn proxy(mut self, url: impl Into<String>) -> SessionBuilder {
+        self.proxy = Some(url.into());
+        self
+    }
+
+    pub fn accept_invalid_certs(mut self, accept: bool) -> SessionBuilder {
+        self.accept_invalid_certs = accept;
+        self
+    }
+
+    /// Trusts an extra PEM encoded root certificate
+    pub fn root_certificate(mut self, pem: Vec<u8>) -> SessionBuilder {
+        self.root_certificates.push(pem);
+        self
+    }
+
+    pub fn root_certificate_file(self, path: impl AsRef<Path>) -> Result<SessionBuilder, Error> {
+        let pem = std::fs::read(path).map_err(Error::Io)?;
+        Ok(self.root_certificate(pem))
+    }
+
+    pub fn build(self) -> Result<Session, Error> {
+        let mut builder =
+            ClientBuilder::new().danger_accept_invalid_certs(self.accept_invalid_certs);
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(proxy) = self.proxy {
+            builder = builder.proxy(Proxy::all(proxy)?);
+        }
+        for pem in self.root_certificates {
+            builder = builder.add_root_certificate(Certificate::from_pem(&pem)?);
+        }
+
+        let lookups = Lookups::default();
+        Ok(Session {
+            client: builder
+                .default_headers(header_map(&self.default_headers)?)
+                .dns_resolver(Arc::new(lookups.clone()))
+                .build()?,
+            lookups,
+        })
+    }
+}
+
+impl Lookups {
+    /// Total time of the lookups of `host` started since `since`, which are forgotten
+    fn take(&self, host: &str, since: Instant) -> Option<Duration> {
+        let mut lookups = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut total = None;
+        lookups.retain(|lookup| {
+            let mine = lookup.host == host && lookup.started >= since;
+            if mine {
+                total = Some(total.unwrap_or(Duration::ZERO) + lookup.took);
+            }
+            !mine
+        });
+        total
+    }
+}
+
+impl Resolve for Lookups {
+    fn resolve(&self, name: Name) -> Resolving {
+        let lookups = self.clone();
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let started = Instant::now();
+            // The port is replaced by the one of the url
+            let addrs: Vec<_> = tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+            let took = started.elapsed();
+            lookups
+                .0
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(Lookup {
+                    host,
+                    started,
+                    took,
+                });
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Session;
+    use crate::{
+        echo::EchoServer,
+        multimap::MultiMap,
+        request::{Error, Request, RequestMethod},
+    };
+    use std::time::Duration;
+
+    #[test]
+    fn build_configured_session() {
+        let session = Session::builder()
+            .timeout(Duration::from_secs(5))
+            .connect_timeout(Duration::from_secs(1))
+            .default_header("User-Agent", "asterios")
+            .proxy("http://localhost:8080")
+            .accept_invalid_certs(true)
+            .build();
+        assert!(session.is_ok());
+    }
+
+    #[test]
+    fn invalid_default_header_is_an_error() {
+        let session = Session::builder()
+            .default_header("bad header", "value")
+            .build();
+        assert!(matches!(session, Err(Error::InvalidHeader { .. })));
+    }
+
+    #[test]
+    fn invalid_root_certificate_is_an_error() {
+        let session = Session::builder()
+            .root_certificate(b"not a certificate".to_vec())
+            .build();
+        assert!(session.is_err());
+    }
+
+    #[tokio::test]
+    async fn send_default_headers() {
+        let server = EchoServer::start().unwrap();
+        let session = Session::builder()
+            .default_header("x-team", "api")
+            .build()
+            .unwrap();
+        let req = Request::new(
+            None,
+            MultiMap::new(),
+            RequestMethod::GET,
+            server.url("/get"),
+            MultiMap::new(),
+        );
+
+        for _ in 0..3 {
+            let res = session.send(&req).await.ok().unwrap();
+            assert_eq!("api", res.body()["headers"]["x-team"]);
+        }
+    }
+
+    #[tokio::test]
+    async fn measure_lookups_of_new_connections() {
+        let server = EchoServer::start().unwrap();
+        let session = Session::new();
+        let request = |url: String| {
+            Request::new(
+                None,
+                MultiMap::new(),
+                RequestMethod::GET,
+                url,
+                MultiMap::new(),
+            )
+        };
+        let by_name = request(server.url("/get").replace("127.0.0.1", "localhost"));
+
+        let res = session.send(&by_name).await.ok().unwrap();
+        assert!(res.dns().is_some_and(|dns| dns <= res.waiting()));
+        let reused = session.send(&by_name).await.ok().unwrap();
+        assert_eq!(None, reused.dns());
+        let by_address = request(server.url("/get"));
+        let res = Session::new().send(&by_address).await.ok().unwrap();
+        assert_eq!(None, res.dns());
+    }
+
+    #[tokio::test]
+    async fn timeout_is_an_error() {
+        let server = EchoServer::start().unwrap();
+        let session = Session::builder()
+            .timeout(Duration::from_millis(100))
+            .build()
+            .unwrap();
+        let req = Request::new(
+            None,
+            MultiMap::new(),
+            RequestMethod::GET,
+            server.url("/delay/1"),
+            MultiMap::new(),
+        );
+
+        let res = session.send(&req).await;
+        assert!(matches!(res, Err(Error::Timeout(_))));
+    }
+}
@@ -0,0 +1,405 @@
+use crate::{
+    collection::{self, Format},
+    dynamic::{self, Generator},
+    request::Request,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    error, fmt, fs,
+    path::Path,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+pub type VariableMap = BTreeMap<String, String>;
+
+/// Values referencing other variables are expanded up to this depth,
+/// deeper references are reported as unresolved to break cycles
+const MAX_DEPTH: usize = 8;
+
+/// A named set of variables, e.g. `dev`, `staging` or `prod`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Environment {
+    pub name: String,
+    #[serde(default)]
+    pub variables: VariableMap,
+}
+
+/// The variables visible to a request.
+///
+/// Scopes are layered from the broadest to the narrowest, each overriding the
+/// previous ones: global, environment, collection, then request-local.
+/// Names starting with `$` are built-in generators, see [`Generator`].
+#[derive(Debug, Clone, Default)]
+pub struct Scope {
+    values: VariableMap,
+    generator: Arc<Mutex<Generator>>, // Shared so a seeded sequence spans a whole run
+}
+
+/// Every variable layer of a run, from the broadest to the narrowest.
+///
+/// Values extracted from responses land in `runtime` or `environment`,
+/// where the following requests see them. `overrides` win over everything.
+#[derive(Debug, Clone, Default)]
+pub struct Variables {
+    pub globals: VariableMap,
+    pub environment: Option<Environment>,
+    pub collection: VariableMap,
+    pub data: VariableMap, // Columns of the current data file row
+    pub runtime: VariableMap,
+    pub overrides: VariableMap,
+    generator: Arc<Mutex<Generator>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    Unresolved(Vec<String>), // Sorted, without duplicates
+    Dynamic(dynamic::Error),
+}
+
+/// What went wrong while substituting, reported once everything was visited
+#[derive(Default)]
+struct Problems {
+    missing: Vec<String>,
+    dynamic: Option<dynamic::Error>,
+}
+
+impl Environment {
+    pub fn new(name: impl Into<String>) -> Environment {
+        Environment {
+            name: name.into(),
+            variables: VariableMap::new(),
+        }
+    }
+
+    /// Reads an environment from a .json or .toml file
+    pub fn load(path: impl AsRef<Path>) -> Result<Environment, collection::Error> {
+        let path = path.as_ref();
+        let format = Format::from_path(path)?;
+        format.parse(&fs::read_to_string(path).map_err(collection::Error::Io)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), collection::Error> {
+        let path = path.as_ref();
+        let content = Format::from_path(path)?.to_string(self)?;
+        fs::write(path, content).map_err(collection::Error::Io)
+    }
+}
+
+impl Variables {
+    pub fn new() -> Variables {
+        Variables::default()
+    }
+
+    pub fn with_generator(mut self, generator: Generator) -> Variables {
+        self.generator = Arc::new(Mutex::new(generator));
+        self
+    }
+
+    /// Scope of a request with its own `local` variables.
+    /// Data rows win over the collection, runtime values over data rows,
+    /// local ones over the runtime.
+    pub fn scope(&self, local: &VariableMap) -> Scope {
+        let mut scope = Scope {
+            values: self.globals.clone(),
+            generator: Arc::clone(&self.generator),
+        };
+        if let Some(environment) = &self.environment {
+            scope.extend(&environment.variables);
+        }
+        scope
+            .with(&self.collection)
+            .with(&self.data)
+            .with(&self.runtime)
+            .with(local)
+            .with(&self.overrides)
+    }
+}
+
+impl Scope {
+    pub fn new() -> Scope {
+        Scope::default()
+    }
+
+    /// Layers `variables` over the current ones
+    pub fn with(mut self, variables: &VariableMap) -> Scope {
+        self.extend(variables);
+        self
+    }
+
+    /// Generator for `$` variables, e.g. a seeded one for reproducible runs
+    pub fn with_generator(mut self, generator: Generator) -> Scope {
+        self.generator = Arc::new(Mutex::new(generator));
+        self
+    }
+
+    pub fn extend(&mut self, variables: &VariableMap) {
+        self.values
+            .extend(variables.iter().map(|(k, v)| (k.clone(), v.clone())));
+    }
+
+    pub fn set(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        self.values.insert(name.into(), value.into());
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+
+    pub fn values(&self) -> &VariableMap {
+        &self.values
+    }
+
+    /// Replaces every `{{name}}` in `input`
+    pub fn interpolate(&self, input: &str) -> Result<String, Error> {
+        let mut problems = Problems::default();
+        let output = self.substitute(input, &mut problems, 0);
+        problems.into_result(output)
+    }
+
+    /// Copy of `request` with variables substituted in its url, headers,
+    /// params and body. Every unresolved name is reported at once.
+    pub fn resolve(&self, request: &Request) -> Result<Request, Error> {
+        let mut problems = Problems::default();
+        let resolved = request.map_strings(&mut |s| self.substitute(s, &mut problems, 0));
+        problems.into_result(resolved)
+    }
+
+    /// Substitutes what it can, failing references are left as-is and collected.
+    /// References nest, so `{{$base64({{user}}:{{password}})}}` works.
+    fn substitute(&self, input: &str, problems: &mut Problems, depth: usize) -> String {
+        let mut output = String::with_capacity(input.len());
+        let mut rest = input;
+        while let Some(start) = rest.find("{{") {
+            output.push_str(&rest[..start]);
+            let inner = &rest[start + 2..];
+            let end = match closing(inner) {
+                Some(end) => end,
+                None => {
+                    output.push_str("{{");
+                    rest = inner;
+                    continue;
+                }
+            };
+            let reference = &rest[start..start + 2 + end + 2];
+            let name = if inner[..end].contains("{{") {
+                self.substitute(&inner[..end], problems, depth)
+            } else {
+                inner[..end].to_string()
+            };
+            let name = name.trim();
+
+            if let Some(expr) = name.strip_prefix('$') {
+                let generated = self
+                    .generator
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .generate(expr);
+                match generated {
+                    Ok(value) => output.push_str(&value),
+                    Err(e) => {
+                        problems.dynamic.get_or_insert(e);
+                        output.push_str(reference);
+                    }
+                }
+            } else {
+                match self.values.get(name) {
+                    Some(value) if depth < MAX_DEPTH => {
+                        output.push_str(&self.substitute(value, problems, depth + 1))
+                    }
+                    _ => {
+                        problems.missing.push(name.to_string());
+                        output.push_str(reference);
+                    }
+                }
+            }
+            rest = &inner[end + 2..];
+        }
+        output.push_str(rest);
+        output
+    }
+}
+
+/// Position of the `}}` closing a reference whose `{{` precedes `s`
+fn closing(s: &str) -> Option<usize> {
+    let bytes = s.as_bytes();
+    let mut depth = 1;
+    let mut i = 0;
+    while i + 1 < bytes.len() {
+        match &bytes[i..i + 2] {
+            b"{{" => {
+                depth += 1;
+                i += 2;
+            }
+            b"}}" => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+                i += 2;
+            }
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+impl Problems {
+    fn into_result<T>(mut self, value: T) -> Result<T, Error> {
+        if let Some(e) = self.dynamic {
+            return Err(Error::Dynamic(e));
+        }
+        if self.missing.is_empty() {
+            return Ok(value);
+        }
+        self.missing.sort();
+        self.missing.dedup();
+        Err(Error::Unresolved(self.missing))
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Unresolved(names) => {
+                write!(f, "unresolved variables: {}", names.join(", "))
+            }
+            Error::Dynamic(e) => e.fmt(f),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, Scope, VariableMap};
+    use crate::{
+        body::RequestBody,
+        dynamic::{self, Generator},
+        multimap::MultiMap,
+        request::{Request, RequestMethod},
+    };
+    use serde_json::json;
+
+    fn vars(pairs: &[(&str, &str)]) -> VariableMap {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn narrower_scopes_win() {
+        let scope = Scope::new()
+            .with(&vars(&[("host", "global"), ("a", "1")]))
+            .with(&vars(&[("host", "env"), ("b", "2")]))
+            .with(&vars(&[("host", "collection")]))
+            .with(&vars(&[("host", "local")]));
+        assert_eq!(
+            "local 1 2",
+            scope.interpolate("{{host}} {{ a }} {{b}}").unwrap()
+        );
+    }
+
+    #[test]
+    fn expand_nested_references() {
+        let scope = Scope::new().with(&vars(&[
+            ("host", "localhost"),
+            ("base", "http://{{host}}/api"),
+            ("loop", "{{loop}}"),
+        ]));
+        assert_eq!(
+            "http://localhost/api/users",
+            scope.interpolate("{{base}}/users").unwrap()
+        );
+        assert_eq!(
+            Err(Error::Unresolved(vec!["loop".to_string()])),
+            scope.interpolate("{{loop}}")
+        );
+    }
+
+    #[test]
+    fn leave_unclosed_braces_alone() {
+        let scope = Scope::new();
+        assert_eq!("{{ not closed", scope.interpolate("{{ not closed").unwrap());
+    }
+
+    #[test]
+    fn resolve_every_part_of_a_request() {
+        let scope = Scope::new().with(&vars(&[
+            ("host", "localhost"),
+            ("token", "secret"),
+            ("id", "42"),
+        ]));
+        let req = Request::new(
+            Some(RequestBody::Json(
+                json!({"id": "{{id}}", "tags": ["{{id}}"]}),
+            )),
+            MultiMap::from([("Authorization", "Bearer {{token}}")]),
+            RequestMethod::POST,
+            "http://{{host}}/users".to_string(),
+            MultiMap::from([("id", "{{id}}")]),
+        );
+
+        let resolved = scope.resolve(&req).unwrap();
+        assert_eq!("http://localhost/users", resolved.url());
+        assert_eq!(
+            Some("Bearer secret"),
+            resolved.headers().get("Authorization")
+        );
+        assert_eq!(Some("42"), resolved.params().get("id"));
+        assert_eq!(
+            Some(&RequestBody::Json(json!({"id": "42", "tags": ["42"]}))),
+            resolved.body()
+        );
+    }
+
+    #[test]
+    fn report_every_missing_variable() {
+        let req = Request::new(
+            Some(RequestBody::Raw {
+                content: "{{b}} {{a}}".to_string(),
+                content_type: "text/plain".to_string(),
+            }),
+            MultiMap::from([("X-Key", "{{a}}")]),
+            RequestMethod::POST,
+            "http://{{host}}/".to_string(),
+            MultiMap::new(),
+        );
+
+        let err = Scope::new().resolve(&req).unwrap_err();
+        assert_eq!(
+            Error::Unresolved(vec!["a".to_string(), "b".to_string(), "host".to_string()]),
+            err
+        );
+        assert_eq!("unresolved variables: a, b, host", err.to_string());
+    }
+
+    #[test]
+    fn generate_dynamic_variables() {
+        let scope = Scope::new()
+            .with(&vars(&[("user", "john"), ("password", "secret")]))
+            .with_generator(Generator::seeded(3));
+        let key = scope.interpolate("{{$uuid}}").unwrap();
+        assert_eq!(36, key.len());
+        assert_ne!(key, scope.interpolate("{{ $uuid }}").unwrap());
+        assert_eq!(
+            "Basic am9objpzZWNyZXQ=",
+            scope
+                .interpolate("Basic {{$base64({{user}}:{{password}})}}")
+                .unwrap()
+        );
+
+        let seeded = |seed| Scope::new().with_generator(Generator::seeded(seed));
+        let input = "{{$randomEmail}} {{$randomInt(1,100)}} {{$randomString(8)}}";
+        assert_eq!(
+            seeded(42).interpolate(input).unwrap(),
+            seeded(42).interpolate(input).unwrap()
+        );
+        assert_eq!(
+            Err(Error::Dynamic(dynamic::Error::Unknown("nope".to_string()))),
+            seeded(42).interpolate("{{$nope}}")
+        );
+    }
+}